@@ -0,0 +1,93 @@
+//! Times frames of the main board on Expert and 100x100 boards, on the software renderer. Run with `cargo bench --bench board`.
+//!
+//! A frame is the board building what it draws, which is what redrawing only changed tiles saves on.
+//! Each size is timed drawing every cell straight to the renderer, with no tiles at all,
+//! then with the tiles drawn from scratch, with every tile cached, and right after a flag, when one tile is drawn again.
+//The game's modules are borrowed whole, along with what only the game and their tests use.
+#![allow(dead_code, unused_imports)]
+
+#[path = "../src/board.rs"]
+mod board;
+#[path = "../src/cell.rs"]
+mod cell;
+
+use iced::advanced::{layout, mouse, renderer, widget::tree, Renderer as _, Widget};
+use minesweeper::action::Action;
+use minesweeper::config::GameConfig;
+use minesweeper::engine::{CellStatus, CellValue, Engine};
+use std::time::{Duration, Instant};
+
+const LENGTH: f32 = 20.0;
+const SIZES: [(usize, usize, usize); 2] = [(30, 16, 99), (100, 100, 2000)];
+
+/// What the cells carry, in place of the game's messages.
+#[derive(Clone, Debug)]
+enum Message {
+  Reveal(usize, usize),
+  Flag(usize, usize),
+}
+
+//Close to how the game shows a cell when nothing is happening on it.
+fn look(engine: &Engine, x: usize, y: usize) -> cell::Cell<Message> {
+  let cell = engine.board[x][y];
+  match cell.status {
+    CellStatus::Revealed => match cell.value {
+      CellValue::Number(0) => cell::Cell {revealed: true, ..Default::default()},
+      CellValue::Number(number) => cell::Cell {revealed: true, content: char::from_digit(number as u32, 10).unwrap_or('?'), size: 20, padding: [0, 4].into(), ..Default::default()},
+      _ => cell::Cell {revealed: true, content: '💣', size: 14, padding: 2.into(), ..Default::default()},
+    },
+    CellStatus::Covered => cell::Cell {on_left_click: Some(Message::Reveal(x, y)), on_right_click: Some(Message::Flag(x, y)), ..Default::default()},
+    _ => cell::Cell {content: '🚩', size: 14, padding: 2.into(), on_right_click: Some(Message::Flag(x, y)), ..Default::default()},
+  }
+}
+
+/// Runs `frame` for at least a second and 10 times, and gives how long one took on average.
+fn measure(mut frame: impl FnMut()) -> Duration {
+  let start = Instant::now();
+  let mut runs = 0;
+  while runs < 10 || start.elapsed() < Duration::from_secs(1) {
+    frame();
+    runs += 1;
+  }
+  start.elapsed() / runs
+}
+
+fn main() {
+  let mut renderer = iced::Renderer::TinySkia(iced_tiny_skia::Renderer::new(iced_tiny_skia::Backend::new(iced_tiny_skia::Settings {
+    default_font: iced::Font::DEFAULT,
+    default_text_size: 16.0,
+  })));
+  let theme = iced::Theme::Dark;
+  let style = renderer::Style {text_color: iced::Color::WHITE};
+  println!("board,every_cell,full_redraw,cached,after_a_flag");
+  for (width, height, mines) in SIZES {
+    let mut engine = GameConfig::new(3).with_size(width, height, mines).with_safe_start(true).build();
+    engine.act(Action::Reveal(width / 2, height / 2));
+    let node = layout::Node::new(iced::Size::new(width as f32 * LENGTH, height as f32 * LENGTH));
+    let viewport = iced::Rectangle::new(iced::Point::ORIGIN, node.size());
+    let draw = |renderer: &mut iced::Renderer, tiles: &board::Tiles, engine: &Engine| {
+      renderer.clear();
+      let board = board::Board::new(tiles, (width, height), |x, y| look(engine, x, y)).length(LENGTH);
+      let tree = tree::Tree::new(&board as &dyn Widget<Message, iced::Renderer>);
+      board.draw(&tree, renderer, &theme, &style, iced::advanced::Layout::new(&node), mouse::Cursor::Unavailable, &viewport);
+    };
+    let every_cell = measure(|| {
+      renderer.clear();
+      for (x, y) in engine.board.positions() {
+        let bounds = iced::Rectangle::new(iced::Point::new(x as f32 * LENGTH, y as f32 * LENGTH), iced::Size::new(LENGTH, LENGTH));
+        look(&engine, x, y).paint(&mut renderer, bounds, &theme, false);
+      }
+    });
+    let full_redraw = measure(|| draw(&mut renderer, &board::Tiles::new((width, height)), &engine));
+    let mut tiles = board::Tiles::new((width, height));
+    let cached = measure(|| draw(&mut renderer, &tiles, &engine));
+    //The same cell is flagged and unflagged, so every frame changes one cell.
+    let (x, y) = engine.board.positions().find(|&(x, y)| engine.board[x][y].status == CellStatus::Covered).expect("a covered cell");
+    let after_a_flag = measure(|| {
+      engine.act(Action::Flag(x, y));
+      tiles.clear(engine.changed());
+      draw(&mut renderer, &tiles, &engine);
+    });
+    println!("{}x{},{:?},{:?},{:?},{:?}", width, height, every_cell, full_redraw, cached, after_a_flag);
+  }
+}
@@ -0,0 +1,134 @@
+//! Python bindings for the engine, so agents can be trained and evaluated against exactly these rules.
+//!
+//! Build with `maturin develop` from this directory.
+use minesweeper::action::Action;
+use minesweeper::config::GameConfig;
+use minesweeper::engine::{CellStatus, CellValue, Engine, GameStatus};
+use minesweeper::solver;
+use pyo3::prelude::*;
+
+//What `Board.observation()` puts in place of a number.
+const COVERED: i8 = -1;
+const FLAGGED: i8 = -2;
+const MINE: i8 = -3;
+
+#[pyclass]
+struct Board {
+  engine: Engine,
+}
+
+#[pymethods]
+impl Board {
+  /// A new board, expert sized unless given another size. Without a seed, a random one is picked.
+  /// With `safe_start`, the first cell revealed is never a mine.
+  #[new]
+  #[pyo3(signature = (seed=None, width=GameConfig::default().width, height=GameConfig::default().height, mines=GameConfig::default().mines, safe_start=false))]
+  fn new(seed: Option<u64>, width: usize, height: usize, mines: usize, safe_start: bool) -> Self {
+    let config = GameConfig::new(seed.unwrap_or_else(rand_seed)).with_size(width, height, mines).with_safe_start(safe_start);
+    Board {engine: config.build()}
+  }
+
+  #[getter]
+  fn seed(&self) -> u64 {
+    self.engine.seed
+  }
+
+  #[getter]
+  fn width(&self) -> usize {
+    self.engine.board.width
+  }
+
+  #[getter]
+  fn height(&self) -> usize {
+    self.engine.board.height
+  }
+
+  #[getter]
+  fn mines(&self) -> usize {
+    self.engine.board.mines
+  }
+
+  #[getter]
+  fn safe_start(&self) -> bool {
+    self.engine.safe_start
+  }
+
+  /// "playing", "won" or "lost".
+  #[getter]
+  fn status(&self) -> &'static str {
+    match self.engine.status {
+      GameStatus::Won => "won",
+      GameStatus::Lost => "lost",
+      _ => "playing",
+    }
+  }
+
+  #[getter]
+  fn revealed_count(&self) -> usize {
+    self.engine.revealed_count
+  }
+
+  #[getter]
+  fn flag_count(&self) -> usize {
+    self.engine.flag_count
+  }
+
+  fn reveal(&mut self, x: usize, y: usize) {
+    self.engine.act(Action::Reveal(x, y));
+  }
+
+  /// Flags a covered cell, or removes the flag from a flagged one.
+  fn flag(&mut self, x: usize, y: usize) {
+    self.engine.act(Action::Flag(x, y));
+  }
+
+  /// Reveals the neighbors of a number which has all its flags.
+  fn chord(&mut self, x: usize, y: usize) {
+    self.engine.act(Action::Chord(x, y));
+  }
+
+  /// What the player can see, as rows of cells. Numbers are 0 to 8, covered cells are -1, flags are -2 and revealed mines are -3.
+  fn observation(&self) -> Vec<Vec<i8>> {
+    (0..self.engine.board.height).map(|y| (0..self.engine.board.width).map(|x| {
+      let cell = self.engine.board[x][y];
+      match (cell.status, cell.value) {
+        (CellStatus::Covered, _) => COVERED,
+        (CellStatus::Flagged | CellStatus::AntiFlagged, _) => FLAGGED,
+        (CellStatus::Revealed, CellValue::Mined | CellValue::AntiMine) => MINE,
+        (CellStatus::Revealed, CellValue::Number(number) | CellValue::Treasure(number)) => number,
+      }
+    }).collect()).collect()
+  }
+
+  /// The solver's next move as `(action, x, y, certain)`, where action is "reveal" or "flag".
+  /// `certain` is false when the solver had to guess.
+  fn next_move(&self) -> Option<(&'static str, usize, usize, bool)> {
+    let step = solver::next_move(&self.engine.board, self.engine.neighbors)?;
+    let (x, y) = step.action.position();
+    let action = match step.action {
+      Action::Flag(..) => "flag",
+      _ => "reveal",
+    };
+    Some((action, x, y, step.reason != solver::Reason::Guess))
+  }
+
+  /// Stable hash of the whole position, matching the game's replays.
+  fn state_hash(&self) -> u64 {
+    self.engine.state_hash()
+  }
+}
+
+fn rand_seed() -> u64 {
+  use std::hash::{BuildHasher, RandomState};
+  RandomState::new().hash_one(std::time::SystemTime::now())
+}
+
+#[pymodule]
+#[pyo3(name = "minesweeper")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+  //The size boards are without one given.
+  module.add("WIDTH", GameConfig::default().width)?;
+  module.add("HEIGHT", GameConfig::default().height)?;
+  module.add("MINES", GameConfig::default().mines)?;
+  module.add_class::<Board>()
+}
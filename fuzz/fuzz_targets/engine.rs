@@ -0,0 +1,82 @@
+//! Plays random actions, including ones off the board, on boards of random sizes under random rules, and checks the engine stays consistent.
+//!
+//! Run with `cargo fuzz run engine` from the repository root.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper::action::Action;
+use minesweeper::config::GameConfig;
+use minesweeper::engine::{CellStatus, CellValue, Engine, Neighbors, Reward, Transform};
+
+//The seed, then one byte for each of the rules below.
+const HEADER: usize = 8 + 10;
+
+/// The size and rules picked by the bytes after the seed.
+fn config(seed: u64, rules: &[u8]) -> GameConfig {
+  let neighbors = [Neighbors::All, Neighbors::Diagonal, Neighbors::Orthogonal][rules[0] as usize % 3];
+  let transform = [Transform::None, Transform::Mirror, Transform::Flip, Transform::Rotate][rules[1] as usize % 4];
+  let reward = if rules[2] & 0x80 == 0 { Reward::Time } else { Reward::Life };
+  //0 is no limit, anything else is a limit which can be over the mine count.
+  let flag_limit = (rules[4] > 0).then(|| rules[4] as usize);
+  //Sizes of 0 and mine counts too big for the board are clamped by the engine. Up to 64 wide keeps each run quick.
+  GameConfig::new(seed)
+    .with_size(rules[7] as usize % 65, rules[8] as usize % 65, rules[9] as usize)
+    .with_safe_start(rules[6] & 8 == 8)
+    .with_neighbors(neighbors)
+    .with_transform(transform)
+    .with_treasures(rules[2] as usize % 8, reward)
+    .with_antimines(rules[3] as usize % 16)
+    .with_flag_limit(flag_limit)
+    .with_lives(rules[5] as usize % 4)
+    .with_flag_win(rules[6] & 1 == 1)
+    .with_fair_layout(rules[6] & 2 == 2)
+    .with_gradient(rules[6] & 4 == 4)
+}
+
+fn check(engine: &Engine) {
+  let mut revealed = 0;
+  let mut flagged = 0;
+  let mut anti_flagged = 0;
+  for column in &engine.board {
+    for cell in column {
+      match (cell.status, cell.value) {
+        (CellStatus::Revealed, CellValue::Number(_) | CellValue::Treasure(_)) => revealed += 1,
+        (CellStatus::Flagged, _) => flagged += 1,
+        (CellStatus::AntiFlagged, _) => anti_flagged += 1,
+        _ => (),
+      }
+    }
+  }
+  assert_eq!(engine.revealed_count, revealed);
+  //Antimine flags are flags too, and count towards the limit.
+  assert_eq!(engine.flag_count, flagged + anti_flagged);
+  assert!(engine.antimines > 0 || anti_flagged == 0);
+  assert!(engine.flag_count <= engine.flag_budget());
+  assert!(engine.flag_count <= engine.board.mines);
+  assert!(engine.board.mines < engine.board.width * engine.board.height);
+}
+
+fuzz_target!(|data: &[u8]| {
+  //The first bytes pick the board and its rules, then every 3 bytes are an action and its position.
+  if data.len() < HEADER {
+    return;
+  }
+  let (header, actions) = data.split_at(HEADER);
+  let (seed, rules) = header.split_at(8);
+  let mut engine = config(u64::from_le_bytes(seed.try_into().unwrap()), rules).build();
+  for chunk in actions.chunks_exact(3) {
+    let (x, y) = (chunk[1] as usize, chunk[2] as usize);
+    let action = match chunk[0] % 3 {
+      0 => Action::Reveal(x, y),
+      1 => Action::Flag(x, y),
+      _ => Action::Chord(x, y),
+    };
+    let was_over = engine.is_over();
+    let before = engine.state_hash();
+    engine.act(action);
+    check(&engine);
+    if was_over {
+      assert_eq!(engine.state_hash(), before, "{:?} changed a finished game", action);
+    }
+  }
+});
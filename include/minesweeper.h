@@ -0,0 +1,107 @@
+#ifndef MINESWEEPER_H
+#define MINESWEEPER_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define MS_ACTION_REVEAL 0
+
+#define MS_ACTION_FLAG 1
+
+#define MS_ACTION_CHORD 2
+
+#define MS_STATUS_PLAYING 0
+
+#define MS_STATUS_WON 1
+
+#define MS_STATUS_LOST 2
+
+#define MS_CELL_COVERED -1
+
+#define MS_CELL_FLAGGED -2
+
+#define MS_CELL_MINE -3
+
+#define MS_CELL_OFF_BOARD -4
+
+// Opaque handle to a game.
+typedef struct MsEngine MsEngine;
+
+
+
+// Width of the board `ms_engine_new` makes. Kept for older callers, since a game's own size comes from `ms_engine_width`.
+uint32_t ms_board_width(void);
+
+// Kept for older callers, since a game's own size comes from `ms_engine_height`.
+uint32_t ms_board_height(void);
+
+// Kept for older callers, since a game's own mine count comes from `ms_engine_mines`.
+uint32_t ms_board_mines(void);
+
+// Generate the expert board for `seed`. Free it with `ms_engine_free`.
+struct MsEngine *ms_engine_new(uint64_t seed);
+
+// Generate a `width`×`height` board with `mines` for `seed`, where the first reveal is never a mine if `safe_start` is set.
+// Free it with `ms_engine_free`.
+struct MsEngine *ms_engine_new_sized(uint64_t seed,
+                                     uint32_t width,
+                                     uint32_t height,
+                                     uint32_t mines,
+                                     bool safe_start);
+
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint32_t ms_engine_width(const struct MsEngine *engine);
+
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint32_t ms_engine_height(const struct MsEngine *engine);
+
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint32_t ms_engine_mines(const struct MsEngine *engine);
+
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized` and not be used afterwards. Null is ignored.
+void ms_engine_free(struct MsEngine *engine);
+
+// Play one of the `MS_ACTION_` values at a position. Unknown actions and positions off the board are ignored.
+//
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+void ms_engine_act(struct MsEngine *engine,
+                   uint32_t action,
+                   uint32_t x,
+                   uint32_t y);
+
+// One of the `MS_STATUS_` values.
+//
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint32_t ms_engine_status(const struct MsEngine *engine);
+
+// What the player sees at a position: a number from 0 to 8, or one of the `MS_CELL_` values.
+//
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+int8_t ms_engine_cell(const struct MsEngine *engine, uint32_t x, uint32_t y);
+
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint32_t ms_engine_revealed_count(const struct MsEngine *engine);
+
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint32_t ms_engine_flag_count(const struct MsEngine *engine);
+
+// Stable hash of the whole position, matching the game's replays.
+//
+// # Safety
+// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+uint64_t ms_engine_state_hash(const struct MsEngine *engine);
+
+#endif  /* MINESWEEPER_H */
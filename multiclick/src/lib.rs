@@ -0,0 +1,323 @@
+//! Right, middle, double and chorded clicks for [iced](https://iced.rs) widgets.
+//!
+//! iced's own button only knows the left button. [`MultiClick`] wraps any content and reports which click it got:
+//!
+//! ```no_run
+//! use iced_multiclick::{Click, MultiClick};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Message {
+//!   Open,
+//!   Flag,
+//!   Chord,
+//! }
+//!
+//! fn view<'a>() -> iced::Element<'a, Message> {
+//!   MultiClick::new(iced::widget::text("?"))
+//!     .on_click(Click::Left, Message::Open)
+//!     .on_click(Click::Right, Message::Flag)
+//!     .on_click(Click::Chord, Message::Chord)
+//!     .on_click(Click::Middle, Message::Chord)
+//!     .into()
+//! }
+//! ```
+//!
+//! Widgets which draw themselves can keep a [`Tracker`] in their state instead, and feed it mouse events, resetting it when the window loses focus.
+use iced::advanced::widget::{tree, Operation, Tree};
+use iced::advanced::{layout, mouse, overlay, renderer, Clipboard, Layout, Shell, Widget};
+use iced::{event, window, Element, Event, Length, Rectangle};
+use std::time::{Duration, Instant};
+
+/// Two left clicks on the same widget closer together than this are a double click.
+pub const DOUBLE_CLICK: Duration = Duration::from_millis(300);
+
+/// What a press and release added up to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Click {
+  Left,
+  Right,
+  Middle,
+  /// Left and right held together.
+  Chord,
+  /// A left click soon after another.
+  Double,
+}
+
+/// What a mouse event did to a [`Tracker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Update {
+  /// The first button went down over the widget.
+  Pressed,
+  /// Another button went down while the widget was already pressed.
+  Joined,
+  /// The buttons were let go. Holds the click, unless the cursor had slid off the widget.
+  Released(Option<Click>),
+}
+
+/// Follows the mouse buttons pressed on one widget.
+///
+/// Once pressed, the widget keeps every button pressed after it, wherever the cursor is,
+/// so a chord belongs to where it started and sliding off before letting go cancels it.
+/// A press is ignored while a button is held down somewhere else.
+#[derive(Clone, Debug, Default)]
+pub struct Tracker {
+  left: bool,
+  right: bool,
+  middle: bool,
+  /// Mouse buttons held down anywhere, not just on this widget.
+  buttons_held: u8,
+  previous_click: Option<Instant>,
+}
+
+impl Tracker {
+  pub fn new() -> Tracker {
+    Tracker::default()
+  }
+
+  /// Whether any button went down on this widget and hasn't been let go.
+  pub fn is_pressed(&self) -> bool {
+    self.left || self.right || self.middle
+  }
+
+  /// Lets go of every button without clicking.
+  pub fn reset(&mut self) {
+    self.left = false;
+    self.right = false;
+    self.middle = false;
+    self.buttons_held = 0;
+  }
+
+  fn press(&mut self, button: mouse::Button) {
+    match button {
+      mouse::Button::Left => self.left = true,
+      mouse::Button::Right => self.right = true,
+      mouse::Button::Middle => self.middle = true,
+      //Extra buttons are left to the app, like in on_event.
+      mouse::Button::Other(_) => (),
+    }
+  }
+
+  /// Feeds a mouse event in. `over` is whether the cursor is over the widget.
+  ///
+  /// Extra buttons are ignored, so the app can bind them itself.
+  pub fn on_event(&mut self, event: &mouse::Event, over: bool) -> Option<Update> {
+    match event {
+      mouse::Event::ButtonPressed(mouse::Button::Other(_)) => None,
+      mouse::Event::ButtonPressed(button) => {
+        let held_elsewhere = self.buttons_held > 0 && !self.is_pressed();
+        self.buttons_held = self.buttons_held.saturating_add(1);
+        if self.is_pressed() {
+          self.press(*button);
+          Some(Update::Joined)
+        } else if over && !held_elsewhere {
+          self.press(*button);
+          Some(Update::Pressed)
+        } else {
+          None
+        }
+      },
+      mouse::Event::ButtonReleased(mouse::Button::Other(_)) => None,
+      mouse::Event::ButtonReleased(_) => {
+        self.buttons_held = self.buttons_held.saturating_sub(1);
+        let double = self.previous_click.is_some_and(|previous| previous.elapsed() <= DOUBLE_CLICK);
+        let click = match (self.left, self.right, self.middle) {
+          (_, _, true) => Click::Middle,
+          (true, true, false) => Click::Chord,
+          (true, false, false) if double => Click::Double,
+          (true, false, false) => Click::Left,
+          (false, true, false) => Click::Right,
+          (false, false, false) => return None,
+        };
+        self.left = false;
+        self.right = false;
+        self.middle = false;
+        //Only a left click can start a double click, so a left click after a flag stays a left click.
+        self.previous_click = matches!(click, Click::Left | Click::Double).then(Instant::now);
+        Some(Update::Released(over.then_some(click)))
+      },
+      //A release outside the window never arrives, so leaving it lets go of the buttons.
+      mouse::Event::CursorLeft => {
+        self.reset();
+        None
+      },
+      _ => None,
+    }
+  }
+}
+
+/// Wraps any content, publishing a message for each kind of click on it.
+pub struct MultiClick<'a, Message, Renderer = iced::Renderer> {
+  content: Element<'a, Message, Renderer>,
+  clicks: Vec<(Click, Message)>,
+  on_press: Option<Message>,
+  on_release: Option<Message>,
+}
+
+impl<'a, Message, Renderer> MultiClick<'a, Message, Renderer> {
+  pub fn new(content: impl Into<Element<'a, Message, Renderer>>) -> Self {
+    MultiClick {content: content.into(), clicks: Vec::new(), on_press: None, on_release: None}
+  }
+
+  /// Publishes `message` for `click`.
+  ///
+  /// A double click without its own message counts as a left click.
+  pub fn on_click(mut self, click: Click, message: Message) -> Self {
+    self.clicks.retain(|(existing, _)| *existing != click);
+    self.clicks.push((click, message));
+    self
+  }
+
+  /// Publishes `message` when the first button goes down on the content.
+  pub fn on_press(mut self, message: Message) -> Self {
+    self.on_press = Some(message);
+    self
+  }
+
+  /// Publishes `message` when the buttons are let go, even if the click was cancelled.
+  pub fn on_release(mut self, message: Message) -> Self {
+    self.on_release = Some(message);
+    self
+  }
+
+  fn message(&self, click: Click) -> Option<&Message> {
+    let find = |click| self.clicks.iter().find(|(existing, _)| *existing == click).map(|(_, message)| message);
+    match click {
+      Click::Double => find(Click::Double).or_else(|| find(Click::Left)),
+      click => find(click),
+    }
+  }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for MultiClick<'a, Message, Renderer>
+where Message: Clone, Renderer: iced::advanced::Renderer
+{
+  fn tag(&self) -> tree::Tag {
+    tree::Tag::of::<Tracker>()
+  }
+
+  fn state(&self) -> tree::State {
+    tree::State::new(Tracker::new())
+  }
+
+  fn children(&self) -> Vec<Tree> {
+    vec![Tree::new(&self.content)]
+  }
+
+  fn diff(&self, tree: &mut Tree) {
+    tree.diff_children(std::slice::from_ref(&self.content));
+  }
+
+  fn width(&self) -> Length {
+    self.content.as_widget().width()
+  }
+
+  fn height(&self) -> Length {
+    self.content.as_widget().height()
+  }
+
+  fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+    self.content.as_widget().layout(renderer, limits)
+  }
+
+  fn operate(&self, tree: &mut Tree, layout: Layout<'_>, renderer: &Renderer, operation: &mut dyn Operation<Message>) {
+    self.content.as_widget().operate(&mut tree.children[0], layout, renderer, operation);
+  }
+
+  fn on_event(&mut self, tree: &mut Tree, event: Event, layout: Layout<'_>, cursor: mouse::Cursor,
+    renderer: &Renderer, clipboard: &mut dyn Clipboard, shell: &mut Shell<'_, Message>, viewport: &Rectangle,
+  ) -> event::Status {
+    //The content sees the event first, so things like text inputs inside keep working.
+    let status = self.content.as_widget_mut().on_event(&mut tree.children[0], event.clone(), layout, cursor, renderer, clipboard, shell, viewport);
+    if status == event::Status::Captured {
+      return status;
+    }
+    let tracker = tree.state.downcast_mut::<Tracker>();
+    let event = match event {
+      Event::Mouse(event) => event,
+      //The buttons can be let go in another window, where the release never reaches this one.
+      Event::Window(window::Event::Unfocused) => {
+        tracker.reset();
+        return status;
+      },
+      _ => return status,
+    };
+    match tracker.on_event(&event, cursor.is_over(layout.bounds())) {
+      Some(Update::Pressed) => {
+        if let Some(on_press) = &self.on_press {
+          shell.publish(on_press.clone());
+        }
+        event::Status::Captured
+      },
+      Some(Update::Joined) => event::Status::Captured,
+      Some(Update::Released(click)) => {
+        if let Some(on_release) = &self.on_release {
+          shell.publish(on_release.clone());
+        }
+        if let Some(message) = click.and_then(|click| self.message(click)) {
+          shell.publish(message.clone());
+        }
+        event::Status::Captured
+      },
+      None => status,
+    }
+  }
+
+  fn draw(&self, tree: &Tree, renderer: &mut Renderer, theme: &Renderer::Theme, style: &renderer::Style, layout: Layout<'_>, cursor: mouse::Cursor, viewport: &Rectangle) {
+    self.content.as_widget().draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+  }
+
+  fn mouse_interaction(&self, tree: &Tree, layout: Layout<'_>, cursor: mouse::Cursor, viewport: &Rectangle, renderer: &Renderer) -> mouse::Interaction {
+    let interaction = self.content.as_widget().mouse_interaction(&tree.children[0], layout, cursor, viewport, renderer);
+    if interaction == mouse::Interaction::default() && !self.clicks.is_empty() && cursor.is_over(layout.bounds()) {
+      mouse::Interaction::Pointer
+    } else {
+      interaction
+    }
+  }
+
+  fn overlay<'b>(&'b mut self, tree: &'b mut Tree, layout: Layout<'_>, renderer: &Renderer) -> Option<overlay::Element<'b, Message, Renderer>> {
+    self.content.as_widget_mut().overlay(&mut tree.children[0], layout, renderer)
+  }
+}
+
+impl<'a, Message, Renderer> From<MultiClick<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where Message: Clone + 'a, Renderer: iced::advanced::Renderer + 'a
+{
+  fn from(multi_click: MultiClick<'a, Message, Renderer>) -> Self {
+    Element::new(multi_click)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn only_left_clicks_start_double_clicks() {
+    let mut tracker = Tracker::new();
+    let mut click = |button| {
+      tracker.on_event(&mouse::Event::ButtonPressed(button), true);
+      tracker.on_event(&mouse::Event::ButtonReleased(button), true)
+    };
+    assert_eq!(click(mouse::Button::Right), Some(Update::Released(Some(Click::Right))));
+    assert_eq!(click(mouse::Button::Left), Some(Update::Released(Some(Click::Left))));
+    assert_eq!(click(mouse::Button::Left), Some(Update::Released(Some(Click::Double))));
+  }
+
+  #[test]
+  fn sliding_off_cancels_a_chord() {
+    let mut tracker = Tracker::new();
+    let mut other = Tracker::new();
+    assert_eq!(tracker.on_event(&mouse::Event::ButtonPressed(mouse::Button::Left), true), Some(Update::Pressed));
+    assert_eq!(other.on_event(&mouse::Event::ButtonPressed(mouse::Button::Left), false), None);
+    //The second button lands on another widget, but joins the first one.
+    assert_eq!(other.on_event(&mouse::Event::ButtonPressed(mouse::Button::Right), true), None);
+    assert_eq!(tracker.on_event(&mouse::Event::ButtonPressed(mouse::Button::Right), false), Some(Update::Joined));
+    assert_eq!(tracker.on_event(&mouse::Event::ButtonReleased(mouse::Button::Right), true), Some(Update::Released(Some(Click::Chord))));
+    assert!(!tracker.is_pressed());
+
+    tracker.on_event(&mouse::Event::ButtonReleased(mouse::Button::Left), true);
+    tracker.on_event(&mouse::Event::ButtonPressed(mouse::Button::Right), true);
+    assert_eq!(tracker.on_event(&mouse::Event::ButtonReleased(mouse::Button::Right), false), Some(Update::Released(None)));
+  }
+}
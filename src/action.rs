@@ -0,0 +1,18 @@
+//! Everything a player can do to the board. Games are logged as a list of these.
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+  Reveal(usize, usize),
+  Flag(usize, usize),
+  /// Reveal the neighbors of a number which has all its flags. (Middle click / double click.)
+  Chord(usize, usize),
+}
+
+impl Action {
+  pub fn position(&self) -> (usize, usize) {
+    match *self {
+      Action::Reveal(x, y) | Action::Flag(x, y) | Action::Chord(x, y) => (x, y),
+    }
+  }
+}
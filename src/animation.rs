@@ -0,0 +1,79 @@
+//! Short animations on single cells, like flags popping in.
+//!
+//! While any are playing, the game subscribes to window frames and redraws every frame.
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+  FlagPlaced,
+  FlagRemoved,
+  /// A flag around a number with more flags than it needs, after trying to chord it.
+  TooManyFlags,
+}
+
+impl Kind {
+  fn duration(self) -> Duration {
+    match self {
+      Kind::FlagPlaced | Kind::FlagRemoved => Duration::from_millis(200),
+      Kind::TooManyFlags => Duration::from_millis(900),
+    }
+  }
+}
+
+struct Animation {
+  x: usize,
+  y: usize,
+  kind: Kind,
+  started: Instant,
+}
+
+#[derive(Default)]
+pub struct Animations {
+  playing: Vec<Animation>,
+}
+
+impl Animations {
+  /// Starts an animation on a cell, replacing any already playing there.
+  pub fn start(&mut self, x: usize, y: usize, kind: Kind) {
+    self.start_after(x, y, kind, Duration::ZERO);
+  }
+
+  /// Starts an animation on a cell once `delay` has passed. Until then it stays at the very beginning.
+  pub fn start_after(&mut self, x: usize, y: usize, kind: Kind, delay: Duration) {
+    self.playing.retain(|animation| (animation.x, animation.y) != (x, y));
+    self.playing.push(Animation {x, y, kind, started: Instant::now() + delay});
+  }
+
+  pub fn is_playing(&self) -> bool {
+    !self.playing.is_empty()
+  }
+
+  /// Drops animations which have finished.
+  pub fn step(&mut self) {
+    self.playing.retain(|animation| animation.started.elapsed() < animation.kind.duration());
+  }
+
+  /// Cells with an animation playing, which change every frame.
+  pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.playing.iter().map(|animation| (animation.x, animation.y))
+  }
+
+  /// The animation playing on a cell, and how far along it is from 0 to 1.
+  pub fn progress(&self, x: usize, y: usize) -> Option<(Kind, f32)> {
+    let animation = self.playing.iter().find(|animation| (animation.x, animation.y) == (x, y))?;
+    Some((animation.kind, (animation.started.elapsed().as_secs_f32() / animation.kind.duration().as_secs_f32()).min(1.0)))
+  }
+}
+
+/// Three pulses, each going from 0 to 1 and back.
+pub fn blink(progress: f32) -> f32 {
+  (progress * std::f32::consts::PI * 3.0).sin().abs()
+}
+
+/// Grows from nothing, overshoots a little, and settles at 1.
+pub fn pop(progress: f32) -> f32 {
+  //Ease out back.
+  const OVERSHOOT: f32 = 2.5;
+  let t = progress - 1.0;
+  1.0 + (OVERSHOOT + 1.0) * t * t * t + OVERSHOOT * t * t
+}
@@ -0,0 +1,65 @@
+//! Sound effects, synthesized on the fly so there are no sound files to ship.
+//!
+//! Sounds are played on their own thread, which opens the audio device the first time something plays.
+use rodio::Source;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::thread;
+
+/// What the sound settings ask for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  MasterVolume(f32),
+  EffectsVolume(f32),
+  RevealSounds(bool),
+  OpeningSounds(bool),
+  /// Play a couple of sounds at the volume set.
+  Test,
+}
+
+#[derive(Clone, Copy)]
+pub enum Sound {
+  /// A number was revealed. Higher numbers play higher notes.
+  Number(u8),
+  /// A big area opened up at once.
+  Opening,
+}
+
+impl Sound {
+  fn play(self, output: &rodio::OutputStreamHandle, volume: f32) {
+    let (frequency, length, loudness) = match self {
+      //A whole tone higher for each number, starting from A4.
+      Sound::Number(number) => (440.0 * 2f32.powf(number.saturating_sub(1) as f32 / 6.0), 60, 0.15),
+      Sound::Opening => (70.0, 180, 0.5),
+    };
+    let length = Duration::from_millis(length);
+    let source = rodio::source::SineWave::new(frequency).take_duration(length).fade_out(length).amplify(loudness * volume);
+    let _ = output.play_raw(source);
+  }
+}
+
+#[derive(Default)]
+pub struct Audio {
+  sender: Option<mpsc::Sender<(Sound, f32)>>,
+}
+
+impl Audio {
+  /// Play a sound at a volume from 0 to 1.
+  pub fn play(&mut self, sound: Sound, volume: f32) {
+    if volume <= 0.0 {
+      return;
+    }
+    let sender = self.sender.get_or_insert_with(|| {
+      let (sender, receiver) = mpsc::channel::<(Sound, f32)>();
+      thread::spawn(move || {
+        //Without an audio device, sounds are quietly dropped.
+        let Ok((_stream, output)) = rodio::OutputStream::try_default() else { return };
+        for (sound, volume) in receiver {
+          sound.play(&output, volume);
+        }
+      });
+      sender
+    });
+    let _ = sender.send((sound, volume));
+  }
+}
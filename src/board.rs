@@ -0,0 +1,304 @@
+//! The main board, drawn as one widget instead of one per cell.
+//!
+//! Cells are drawn in square tiles, each cached in a [`canvas::Cache`] until a cell in it changes,
+//! so a move only draws again the tiles it touched, however big the board is.
+//! Cells which change on their own, like animated, pressed or highlighted ones and the one under the cursor, are drawn on top every frame.
+//! Clicks are worked out by a [`Tracker`] from `iced_multiclick`, like on a single [`cell::Cell`], and go to the cell they started on.
+use crate::cell::{self, Surface};
+use iced::advanced::{layout, mouse, overlay, renderer, widget::tree};
+use iced::advanced::graphics::geometry::Renderer as _;
+use iced::event;
+use iced::widget::canvas;
+use iced_multiclick::{Click, Tracker, Update};
+use std::time;
+
+/// Cells along each side of a tile.
+pub const TILE: usize = 8;
+
+/// The cached drawings of a board's tiles, kept by the game between frames.
+#[derive(Default)]
+pub struct Tiles {
+  /// Tiles across the board.
+  columns: usize,
+  caches: Vec<canvas::Cache>,
+}
+
+impl Tiles {
+  /// Empty tiles for a board of `columns` by `rows` cells, so it is all drawn again.
+  pub fn new((columns, rows): (usize, usize)) -> Tiles {
+    let columns = columns.div_ceil(TILE);
+    Tiles {columns, caches: (0..columns * rows.div_ceil(TILE)).map(|_| canvas::Cache::new()).collect()}
+  }
+
+  /// Draws again the tiles holding `cells`.
+  pub fn clear(&mut self, cells: &[(usize, usize)]) {
+    for &(x, y) in cells {
+      if let Some(cache) = self.caches.get((y / TILE) * self.columns + x / TILE) {
+        cache.clear();
+      }
+    }
+  }
+}
+
+pub struct Board<'a, Message> {
+  columns: usize,
+  rows: usize,
+  /// The side of a cell, in pixels.
+  length: f32,
+  spacing: f32,
+  /// Drawn behind the cells, showing in the spacing between them.
+  background: Option<iced::Color>,
+  /// How a cell looks when nothing is happening on it, and what clicking it does.
+  look: Box<dyn Fn(usize, usize) -> cell::Cell<Message> + 'a>,
+  /// Cells drawn every frame over their tiles, looking different from how they are cached.
+  live: Vec<((usize, usize), cell::Cell<Message>)>,
+  tiles: &'a Tiles,
+}
+
+impl<'a, Message> Board<'a, Message> {
+  pub fn new(tiles: &'a Tiles, (columns, rows): (usize, usize), look: impl Fn(usize, usize) -> cell::Cell<Message> + 'a) -> Self {
+    Board {columns, rows, length: 20.0, spacing: 0.0, background: None, look: Box::new(look), live: Vec::new(), tiles}
+  }
+
+  pub fn length(mut self, length: f32) -> Self {
+    self.length = length;
+    self
+  }
+
+  pub fn spacing(mut self, spacing: f32) -> Self {
+    self.spacing = spacing;
+    self
+  }
+
+  pub fn background(mut self, background: Option<iced::Color>) -> Self {
+    self.background = background;
+    self
+  }
+
+  pub fn live(mut self, live: Vec<((usize, usize), cell::Cell<Message>)>) -> Self {
+    self.live = live;
+    self
+  }
+
+  fn size(&self) -> iced::Size {
+    let side = |cells: usize| cells as f32 * (self.length + self.spacing) - self.spacing;
+    iced::Size::new(side(self.columns), side(self.rows))
+  }
+
+  /// Where a cell is, from the top left of the board.
+  fn cell_bounds(&self, x: usize, y: usize) -> iced::Rectangle {
+    let step = self.length + self.spacing;
+    iced::Rectangle {x: x as f32 * step, y: y as f32 * step, width: self.length, height: self.length}
+  }
+
+  /// The cell at `point`, from the top left of the board. Nothing in the spacing between cells.
+  fn cell_at(&self, point: iced::Point) -> Option<(usize, usize)> {
+    let step = self.length + self.spacing;
+    let (x, y) = ((point.x / step).floor(), (point.y / step).floor());
+    if x < 0.0 || y < 0.0 || point.x - x * step >= self.length || point.y - y * step >= self.length {
+      return None;
+    }
+    Some((x as usize, y as usize)).filter(|&(x, y)| x < self.columns && y < self.rows)
+  }
+
+  fn live_cell(&self, position: (usize, usize)) -> Option<&cell::Cell<Message>> {
+    self.live.iter().find(|(live, _)| *live == position).map(|(_, cell)| cell)
+  }
+
+  fn paint_tile(&self, frame: &mut canvas::Frame, index: usize, theme: &iced::Theme) {
+    let columns = self.columns.div_ceil(TILE);
+    let (left, top) = ((index % columns) * TILE, (index / columns) * TILE);
+    let (right, bottom) = ((left + TILE).min(self.columns), (top + TILE).min(self.rows));
+    if let Some(background) = self.background {
+      //Up to where the next tiles start, so the spacing between tiles is filled too.
+      let step = self.length + self.spacing;
+      let size = self.size();
+      let start = iced::Point::new(left as f32 * step, top as f32 * step);
+      let end = iced::Point::new((right as f32 * step).min(size.width), (bottom as f32 * step).min(size.height));
+      frame.fill_rectangle(start, iced::Size::new(end.x - start.x, end.y - start.y), background);
+    }
+    for x in left..right {
+      for y in top..bottom {
+        (self.look)(x, y).paint(frame, self.cell_bounds(x, y), theme, false);
+      }
+    }
+  }
+}
+
+/// What the board remembers between frames.
+#[derive(Default)]
+struct State {
+  tracker: Tracker,
+  /// The cell the buttons went down on.
+  pressed: Option<(usize, usize)>,
+  /// The cell under the cursor, and since when.
+  hovered: Option<((usize, usize), time::Instant)>,
+}
+
+impl<'a, Message> iced::advanced::Widget<Message, iced::Renderer> for Board<'a, Message>
+where Message: Clone
+{
+  fn tag(&self) -> tree::Tag {
+    tree::Tag::of::<State>()
+  }
+
+  fn state(&self) -> tree::State {
+    tree::State::new(State::default())
+  }
+
+  fn width(&self) -> iced::Length {
+    iced::Length::Fixed(self.size().width)
+  }
+
+  fn height(&self) -> iced::Length {
+    iced::Length::Fixed(self.size().height)
+  }
+
+  fn layout(&self, _renderer: &iced::Renderer, _limits: &layout::Limits) -> layout::Node {
+    layout::Node::new(self.size())
+  }
+
+  fn on_event(&mut self, tree: &mut tree::Tree, event: event::Event, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor,
+    _renderer: &iced::Renderer, _clipboard: &mut dyn iced::advanced::Clipboard, shell: &mut iced::advanced::Shell<'_, Message>, _viewport: &iced::Rectangle,
+  ) -> event::Status {
+    let state = tree.state.downcast_mut::<State>();
+    let event = match event {
+      event::Event::Mouse(event) => event,
+      //The buttons can be let go in another window, where the release never reaches this one.
+      event::Event::Window(iced::window::Event::Unfocused) => {
+        state.tracker.reset();
+        state.pressed = None;
+        return event::Status::Ignored;
+      },
+      _ => return event::Status::Ignored,
+    };
+    let under = cursor.position_in(layout.bounds()).and_then(|point| self.cell_at(point));
+    match event {
+      mouse::Event::CursorMoved {..} if state.hovered.map(|(cell, _)| cell) != under => {
+        let now = time::Instant::now();
+        state.hovered = under.map(|cell| (cell, now));
+        if let Some((x, y)) = under {
+          let look = (self.look)(x, y);
+          if look.tooltip.is_some() {
+            shell.request_redraw(iced::window::RedrawRequest::At(now + look.tooltip_delay));
+          }
+        }
+      },
+      //Clicking hides the tooltip until the cursor moves again.
+      mouse::Event::ButtonPressed(_) | mouse::Event::CursorLeft => state.hovered = None,
+      _ => {},
+    }
+    //Once pressed, only the cell the press started on counts as being over it.
+    let over = match state.pressed {
+      Some(pressed) if state.tracker.is_pressed() => under == Some(pressed),
+      _ => under.is_some(),
+    };
+    match state.tracker.on_event(&event, over) {
+      Some(Update::Pressed) => {
+        state.pressed = under;
+        let Some((x, y)) = under else { return event::Status::Ignored };
+        if let Some(on_press) = (self.look)(x, y).on_press {
+          shell.publish(on_press);
+          return event::Status::Captured;
+        }
+        event::Status::Ignored
+      },
+      Some(Update::Joined) => event::Status::Captured,
+      Some(Update::Released(click)) => {
+        let Some((x, y)) = state.pressed.take() else { return event::Status::Captured };
+        let look = (self.look)(x, y);
+        if let Some(on_release) = look.on_release {
+          shell.publish(on_release);
+        }
+        let on_click = match click {
+          Some(Click::Left) => look.on_left_click,
+          Some(Click::Right) => look.on_right_click,
+          Some(Click::Middle | Click::Chord | Click::Double) => look.on_middle_click,
+          None => None,
+        };
+        if let Some(on_click) = on_click {
+          shell.publish(on_click);
+        }
+        event::Status::Captured
+      },
+      None => event::Status::Ignored,
+    }
+  }
+
+  fn draw(&self, _tree: &tree::Tree, renderer: &mut iced::Renderer, theme: &iced::Theme, _style: &renderer::Style, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor, _viewport: &iced::Rectangle) {
+    let bounds = layout.bounds();
+    let size = bounds.size();
+    let tiles = self.columns.div_ceil(TILE) * self.rows.div_ceil(TILE);
+    let mut layers: Vec<canvas::Geometry> = (0..tiles).map(|index| match self.tiles.caches.get(index).filter(|_| self.tiles.caches.len() == tiles) {
+      Some(cache) => cache.draw(renderer, size, |frame| self.paint_tile(frame, index, theme)),
+      //Tiles made for another board aren't any use, so this one is drawn without them until the game makes new ones.
+      None => {
+        let mut frame = canvas::Frame::new(renderer, size);
+        self.paint_tile(&mut frame, index, theme);
+        frame.into_geometry()
+      },
+    }).collect();
+    let mut frame = canvas::Frame::new(renderer, size);
+    let hovered = cursor.position_in(bounds).and_then(|point| self.cell_at(point));
+    let covered = self.background.unwrap_or(theme.palette().background);
+    for (position, cell) in &self.live {
+      let bounds = self.cell_bounds(position.0, position.1);
+      //Covering the cached cell first, so nothing of it shows around a shrunk or see-through one.
+      frame.quad(bounds, 0.0, (0.0, iced::Color::TRANSPARENT), covered);
+      cell.paint(&mut frame, bounds, theme, hovered == Some(*position));
+    }
+    if let Some((x, y)) = hovered.filter(|&position| self.live_cell(position).is_none()) {
+      let bounds = self.cell_bounds(x, y);
+      frame.quad(bounds, 0.0, (0.0, iced::Color::TRANSPARENT), covered);
+      (self.look)(x, y).paint(&mut frame, bounds, theme, true);
+    }
+    layers.push(frame.into_geometry());
+    iced::advanced::Renderer::with_translation(renderer, iced::Vector::new(bounds.x, bounds.y), |renderer| renderer.draw(layers));
+  }
+
+  fn mouse_interaction(&self, _tree: &tree::Tree, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor, _viewport: &iced::Rectangle, _renderer: &iced::Renderer) -> mouse::Interaction {
+    match cursor.position_in(layout.bounds()).and_then(|point| self.cell_at(point)) {
+      Some((x, y)) if (self.look)(x, y).on_left_click.is_some() => mouse::Interaction::Pointer,
+      _ => mouse::Interaction::default(),
+    }
+  }
+
+  fn overlay<'b>(&'b mut self, tree: &'b mut tree::Tree, layout: iced::advanced::Layout<'_>, _renderer: &iced::Renderer) -> Option<overlay::Element<'b, Message, iced::Renderer>> {
+    let ((x, y), hovered_since) = tree.state.downcast_ref::<State>().hovered?;
+    let look = (self.look)(x, y);
+    let text = look.tooltip?;
+    if hovered_since.elapsed() < look.tooltip_delay {
+      return None;
+    }
+    let bounds = self.cell_bounds(x, y);
+    let position = layout.position() + iced::Vector::new(bounds.x, bounds.y);
+    Some(overlay::Element::new(position, Box::new(cell::Tooltip {text, cell: bounds.size()})))
+  }
+}
+
+impl<'a, Message> From<Board<'a, Message>> for iced::Element<'a, Message>
+where Message: Clone + 'a
+{
+  fn from(board: Board<'a, Message>) -> Self {
+    Self::new(board)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cells_are_found_under_the_cursor() {
+    let tiles = Tiles::new((10, 9));
+    let board: Board<'_, crate::Message> = Board::new(&tiles, (10, 9), |_, _| cell::Cell::default()).spacing(2.0);
+    assert_eq!(board.size(), iced::Size::new(218.0, 196.0));
+    assert_eq!(board.cell_at(iced::Point::new(0.0, 0.0)), Some((0, 0)));
+    assert_eq!(board.cell_at(iced::Point::new(30.0, 25.0)), Some((1, 1)));
+    //In the spacing, or past the last cell.
+    assert_eq!(board.cell_at(iced::Point::new(21.0, 5.0)), None);
+    assert_eq!(board.cell_at(iced::Point::new(222.0, 5.0)), None);
+    //Tiles along the edges are cut short by the board.
+    assert_eq!(tiles.caches.len(), 4);
+  }
+}
@@ -0,0 +1,112 @@
+//! Zoom and pan state of the board viewport.
+//!
+//! The board sits inside a scrollable. Ctrl+scroll changes the cell scale, and dragging with the middle button pans it.
+use iced::widget::scrollable::AbsoluteOffset;
+
+pub const MIN_ZOOM: f32 = 0.5;
+pub const MAX_ZOOM: f32 = 3.0;
+const ZOOM_STEP: f32 = 1.1;
+//How much of the remaining distance to the target zoom is covered each frame.
+const EASING: f32 = 0.3;
+//Cursor movement needed before a middle press counts as a drag rather than a click.
+const DRAG_THRESHOLD: f32 = 3.0;
+
+pub struct Camera {
+  pub zoom: f32,
+  target_zoom: f32,
+  //Point in the viewport which should stay still while zooming.
+  anchor: iced::Point,
+  offset: iced::Vector,
+  viewport: iced::Size,
+  pan_from: Option<iced::Point>,
+  pan_distance: f32,
+}
+
+impl Camera {
+  pub fn new(viewport: iced::Size) -> Camera {
+    Camera {
+      zoom: 1.0,
+      target_zoom: 1.0,
+      anchor: iced::Point::ORIGIN,
+      offset: iced::Vector::new(0.0, 0.0),
+      viewport,
+      pan_from: None,
+      pan_distance: 0.0,
+    }
+  }
+
+  pub fn is_animating(&self) -> bool {
+    self.zoom != self.target_zoom
+  }
+
+  /// A middle press which moved further than a click would counts as a pan, so it should not chord.
+  pub fn is_panning(&self) -> bool {
+    self.pan_from.is_some() && self.pan_distance > DRAG_THRESHOLD
+  }
+
+  /// Change the target zoom by a number of scroll lines, keeping `anchor` (in viewport coordinates) in place.
+  pub fn zoom_by(&mut self, lines: f32, anchor: iced::Point) {
+    self.target_zoom = (self.target_zoom * ZOOM_STEP.powf(lines)).clamp(MIN_ZOOM, MAX_ZOOM);
+    self.anchor = anchor;
+  }
+
+  /// Move the zoom a step closer to its target. Returns the offset the board should be scrolled to.
+  pub fn step<F>(&mut self, content_size: F) -> AbsoluteOffset where F: Fn(f32) -> iced::Size {
+    let old_size = content_size(self.zoom);
+    self.zoom += (self.target_zoom - self.zoom) * EASING;
+    if (self.target_zoom - self.zoom).abs() < 0.005 {
+      self.zoom = self.target_zoom;
+    }
+    let new_size = content_size(self.zoom);
+
+    //Scale the offset so the anchored point covers the same part of the board as before.
+    self.offset = iced::Vector::new(
+      (self.offset.x + self.anchor.x) * new_size.width / old_size.width - self.anchor.x,
+      (self.offset.y + self.anchor.y) * new_size.height / old_size.height - self.anchor.y,
+    );
+    self.clamp(new_size)
+  }
+
+  /// Zoom towards a fixed level, e.g. to fit the board into a resized window.
+  pub fn zoom_to(&mut self, zoom: f32) {
+    self.target_zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    self.anchor = iced::Point::ORIGIN;
+  }
+
+  pub fn resize(&mut self, viewport: iced::Size) {
+    self.viewport = viewport;
+  }
+
+  pub fn pan_start(&mut self, cursor: iced::Point) {
+    self.pan_from = Some(cursor);
+    self.pan_distance = 0.0;
+  }
+
+  pub fn pan_end(&mut self) {
+    self.pan_from = None;
+  }
+
+  /// Follow the cursor while panning. Returns the new offset if the board should scroll.
+  pub fn pan_to(&mut self, cursor: iced::Point, content_size: iced::Size) -> Option<AbsoluteOffset> {
+    let from = self.pan_from?;
+    let delta = cursor - from;
+    self.pan_from = Some(cursor);
+    self.pan_distance += delta.x.abs() + delta.y.abs();
+    if !self.is_panning() {
+      return None;
+    }
+    self.offset = self.offset - delta;
+    Some(self.clamp(content_size))
+  }
+
+  /// The user scrolled the board directly.
+  pub fn scrolled(&mut self, offset: AbsoluteOffset) {
+    self.offset = iced::Vector::new(offset.x, offset.y);
+  }
+
+  fn clamp(&mut self, content_size: iced::Size) -> AbsoluteOffset {
+    self.offset.x = self.offset.x.min(content_size.width - self.viewport.width).max(0.0);
+    self.offset.y = self.offset.y.min(content_size.height - self.viewport.height).max(0.0);
+    AbsoluteOffset {x: self.offset.x, y: self.offset.y}
+  }
+}
@@ -1,13 +1,37 @@
-//! Allow your users to perform actions by pressing a button.
+//! One cell of the board, drawn directly and clicked with any mouse button.
 //!
-//! A [`CellWidget`] has some local [`State`].
+//! Which click a cell got is worked out by a [`Tracker`] from `iced_multiclick` kept in its state.
 use iced::{alignment, event};
-use iced::advanced::{layout, mouse, renderer, widget::tree};
-use iced::widget::button;
+use iced::advanced::{layout, mouse, overlay, renderer, widget::tree};
+use iced::widget::{button, canvas};
 use iced::widget::text as widget_text;
 use iced::advanced::text as advanced_text;
+use iced_multiclick::{Click, Tracker, Update};
 use std::time;
 
+const TOOLTIP_SIZE: f32 = 14.0;
+const TOOLTIP_PADDING: f32 = 4.0;
+
+/// Theme dependent looks, shared by all cells.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Style {
+  pub revealed: iced::Color,
+  pub border_width: f32,
+  pub border_color: iced::Color,
+  pub font: iced::Font,
+}
+
+impl Default for Style {
+  fn default() -> Self {
+    Style {
+      revealed: iced::Color::WHITE,
+      border_width: 0.0,
+      border_color: iced::Color::BLACK,
+      font: iced::Font::MONOSPACE,
+    }
+  }
+}
+
 pub struct Cell<Message> {
   pub content: char,
   pub size: u8,
@@ -15,6 +39,26 @@ pub struct Cell<Message> {
   pub padding: iced::Padding,
   pub revealed: bool,
   pub color: iced::Color,
+  /// Multiplies `length`, `size` and `padding`. Used for zooming the board.
+  pub scale: f32,
+  /// Multiplies the size of the content only, around the middle of the cell. Used for animations.
+  pub content_scale: f32,
+  /// Draws an outline around the cell, used to point at cells the demo is playing.
+  pub highlight: bool,
+  /// Draws the cell held down, while a mouse button is pressed on it.
+  pub pressed: bool,
+  /// Tints the cell with the theme's danger color, from 0 for not at all to 1 for fully. Used to point out wrong flags.
+  pub flash: f32,
+  /// Replaces the background of a revealed cell.
+  pub background: Option<iced::Color>,
+  /// Draws stripes across the cell, so it can be told apart by more than its color.
+  pub stripes: bool,
+  /// Draws a bar under the content, so it can be told apart by more than its color.
+  pub underline: bool,
+  /// Shown above the cell once the cursor has rested on it for `tooltip_delay`.
+  pub tooltip: Option<String>,
+  pub tooltip_delay: time::Duration,
+  pub style: Style,
   pub on_left_click: Option<Message>,
   pub on_middle_click: Option<Message>,
   pub on_right_click: Option<Message>,
@@ -31,6 +75,17 @@ impl Default for Cell<crate::Message> {
       padding: iced::Padding::ZERO,
       color: iced::Color::WHITE,
       revealed: false,
+      scale: 1.0,
+      content_scale: 1.0,
+      highlight: false,
+      pressed: false,
+      flash: 0.0,
+      background: None,
+      stripes: false,
+      underline: false,
+      tooltip: None,
+      tooltip_delay: time::Duration::from_millis(600),
+      style: Style::default(),
       on_left_click: None, on_middle_click: None, on_right_click: None, on_press: None, on_release: None,
     }
   }
@@ -39,145 +94,242 @@ impl Default for Cell<crate::Message> {
 impl<Message> iced::advanced::Widget<Message, iced::Renderer> for Cell<Message>
 where Message: Clone
 {
+  fn tag(&self) -> tree::Tag {
+    tree::Tag::of::<State>()
+  }
+
   fn state(&self) -> tree::State {
-    tree::State::new(State::new())
+    tree::State::new(State::default())
   }
     
   fn width(&self) -> iced::Length {
-    iced::Length::Fixed(self.length as f32)
+    iced::Length::Fixed(self.length as f32 * self.scale)
   }
 
   fn height(&self) -> iced::Length {
-    iced::Length::Fixed(self.length as f32)
+    iced::Length::Fixed(self.length as f32 * self.scale)
   }
 
   fn layout(&self, _renderer: &iced::Renderer, limits: &layout::Limits) -> layout::Node {
-    let limits = limits.width(iced::Length::Fixed(self.length as f32)).height(iced::Length::Fixed(self.length as f32));
+    let limits = limits.width(self.width()).height(self.height());
     layout::Node::new(limits.fill())
   }
 
   fn on_event(&mut self, tree: &mut tree::Tree, event: event::Event, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor,
     _renderer: &iced::Renderer, _clipboard: &mut dyn iced::advanced::Clipboard, shell: &mut iced::advanced::Shell<'_, Message>, _viewport: &iced::Rectangle,
   ) -> event::Status {
-    
+    let state = tree.state.downcast_mut::<State>();
+    let event = match event {
+      event::Event::Mouse(event) => event,
+      //The buttons can be let go in another window, where the release never reaches this one.
+      event::Event::Window(iced::window::Event::Unfocused) => {
+        state.tracker.reset();
+        return event::Status::Ignored;
+      },
+      _ => return event::Status::Ignored,
+    };
+    let over = cursor.is_over(layout.bounds());
     match event {
-      event::Event::Mouse(mouse::Event::ButtonPressed(button)) => {
-        if cursor.is_over(layout.bounds()) {
-          let state = tree.state.downcast_mut::<State>();
-          match button {
-            mouse::Button::Left => state.is_left_pressed = true,
-            mouse::Button::Right => state.is_right_pressed = true,
-            _ => {state.is_left_pressed = true; state.is_right_pressed = true},
-          };
-          if let Some(on_press) = &self.on_press {
-            shell.publish(on_press.clone());
-            return event::Status::Captured;
-          }
+      mouse::Event::CursorMoved {..} if !over => state.hovered_since = None,
+      mouse::Event::CursorMoved {..} if state.hovered_since.is_none() => {
+        let now = time::Instant::now();
+        state.hovered_since = Some(now);
+        if self.tooltip.is_some() {
+          shell.request_redraw(iced::window::RedrawRequest::At(now + self.tooltip_delay));
+        }
+      },
+      //Clicking hides the tooltip until the cursor moves again.
+      mouse::Event::ButtonPressed(_) | mouse::Event::CursorLeft => state.hovered_since = None,
+      _ => {},
+    }
+    match state.tracker.on_event(&event, over) {
+      Some(Update::Pressed) => {
+        if let Some(on_press) = &self.on_press {
+          shell.publish(on_press.clone());
+          return event::Status::Captured;
         }
         event::Status::Ignored
       },
-      event::Event::Mouse(mouse::Event::ButtonReleased(_)) => {
-        let state = tree.state.downcast_mut::<State>();
-        
-        let on_click = if state.is_left_pressed && state.previous_click_time.elapsed().as_millis() <= 300 {
-          //Double clicked
-          &self.on_middle_click
-        } else {
-          match (state.is_left_pressed, state.is_right_pressed) {
-            (true, false) => &self.on_left_click,
-            (false, true) => &self.on_right_click,
-            (true, true) => &self.on_middle_click,
-            (false, false) => return event::Status::Ignored,
-          }
-        };
-        state.is_left_pressed = false;
-        state.is_right_pressed = false;
-        state.previous_click_time = time::Instant::now();
-
+      Some(Update::Joined) => event::Status::Captured,
+      Some(Update::Released(click)) => {
         if let Some(on_release) = &self.on_release {
           shell.publish(on_release.clone());
         }
+        let on_click = match click {
+          Some(Click::Left) => &self.on_left_click,
+          Some(Click::Right) => &self.on_right_click,
+          Some(Click::Middle | Click::Chord | Click::Double) => &self.on_middle_click,
+          None => &None,
+        };
         if let Some(on_click) = on_click.clone() {
-          if cursor.is_over(layout.bounds()) {
-            shell.publish(on_click);
-          }
+          shell.publish(on_click);
         }
         event::Status::Captured
       },
-      _ => event::Status::Ignored,
+      None => event::Status::Ignored,
     }
-    
   }
 
-  fn draw(&self, tree: &tree::Tree, renderer: &mut iced::Renderer, theme: &iced::Theme, _style: &renderer::Style, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor,_viewport: &iced::Rectangle) {
-    let bounds = layout.bounds();
-    
+  fn draw(&self, _tree: &tree::Tree, renderer: &mut iced::Renderer, theme: &iced::Theme, _style: &renderer::Style, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor,_viewport: &iced::Rectangle) {
+    self.paint(renderer, layout.bounds(), theme, cursor.is_over(layout.bounds()));
+  }
+
+  fn mouse_interaction(&self, _tree: &tree::Tree, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor, _viewport: &iced::Rectangle, _renderer: &iced::Renderer) -> mouse::Interaction {
+    let is_mouse_over = cursor.is_over(layout.bounds());
+    let is_enabled = self.on_left_click.is_some();
+    if is_mouse_over && is_enabled {
+      mouse::Interaction::Pointer
+    } else {
+      mouse::Interaction::default()
+    }
+  }
+
+  fn overlay<'b>(&'b mut self, tree: &'b mut tree::Tree, layout: iced::advanced::Layout<'_>, _renderer: &iced::Renderer) -> Option<overlay::Element<'b, Message, iced::Renderer>> {
+    let text = self.tooltip.clone()?;
+    let hovered_since = tree.state.downcast_ref::<State>().hovered_since?;
+    if hovered_since.elapsed() < self.tooltip_delay {
+      return None;
+    }
+    Some(overlay::Element::new(layout.position(), Box::new(Tooltip {text, cell: layout.bounds().size()})))
+  }
+
+}
+
+impl<Message> Cell<Message> {
+  /// Draws the cell in `bounds`, with the hovered look when `hovered`.
+  pub fn paint(&self, surface: &mut impl Surface, bounds: iced::Rectangle, theme: &iced::Theme, hovered: bool) {
     if !self.revealed {
       let style: iced::theme::Button = Default::default();
 
-      let styling = if !self.on_left_click.is_some() {
+      let styling = if self.on_left_click.is_none() {
         button::StyleSheet::disabled(theme, &style)
-      } else if cursor.is_over(bounds) {
-        let state = tree.state.downcast_ref::<State>();
-        match state.is_left_pressed || state.is_right_pressed {
-          true => button::StyleSheet::pressed(theme, &style),
-          false => button::StyleSheet::hovered(theme, &style),
-        }
+      } else if self.pressed {
+        button::StyleSheet::pressed(theme, &style)
+      } else if hovered {
+        button::StyleSheet::hovered(theme, &style)
       } else {
         button::StyleSheet::active(theme, &style)
       };
 
-      if styling.background.is_some() || styling.border_width > 0.0 {
-        iced::advanced::Renderer::fill_quad(renderer,
-          renderer::Quad {
-            bounds,
-            border_radius: styling.border_radius,
-            border_width: styling.border_width,
-            border_color: styling.border_color,
-          },
-          styling.background.unwrap_or(iced::Background::Color(iced::Color::TRANSPARENT)),
-        );
+      //Themes only give buttons solid colors.
+      let background = match styling.background {
+        Some(iced::Background::Color(color)) => color,
+        _ => iced::Color::TRANSPARENT,
+      };
+      if background.a > 0.0 || self.style.border_width > 0.0 {
+        let [radius, ..]: [f32; 4] = styling.border_radius.into();
+        surface.quad(bounds, radius, (self.style.border_width, self.style.border_color), background);
       }
-    } else if self.revealed {
-      iced::advanced::Renderer::fill_quad(renderer,
-        renderer::Quad {
-          bounds,
-          border_radius: 0.0.into(),
-          border_width: 0.0.into(),
-          border_color: iced::Color::WHITE,
-        },
-        iced::Background::Color(iced::Color::WHITE)
-      );
+    } else {
+      surface.quad(bounds, 0.0, (self.style.border_width, self.style.border_color), self.background.unwrap_or(self.style.revealed));
     }
 
-    advanced_text::Renderer::fill_text(renderer, iced::advanced::Text {
+    if self.flash > 0.0 {
+      surface.quad(bounds, 0.0, (0.0, iced::Color::TRANSPARENT), iced::Color {a: self.flash * 0.7, ..theme.palette().danger});
+    }
+
+    if self.stripes {
+      let stripe = bounds.height / 8.0;
+      for index in 0..3 {
+        surface.quad(iced::Rectangle {y: bounds.y + stripe * (index * 2 + 1) as f32, height: stripe, ..bounds}, 0.0,
+          (0.0, iced::Color::TRANSPARENT), iced::Color {a: 0.25, ..theme.palette().text});
+      }
+    }
+
+    if self.underline {
+      let bar = iced::Rectangle {
+        x: bounds.x + bounds.width / 4.0,
+        y: bounds.y + bounds.height * 0.85,
+        width: bounds.width / 2.0,
+        height: (bounds.height / 12.0).max(1.0),
+      };
+      surface.quad(bar, 0.0, (0.0, iced::Color::TRANSPARENT), self.color);
+    }
+
+    if self.highlight {
+      surface.quad(bounds, 0.0, (2.0, theme.palette().primary), iced::Color::TRANSPARENT);
+    }
+
+    //Shrinking the content pulls it towards the middle of the cell.
+    let content_scale = self.scale * self.content_scale;
+    let shrink = 1.0 - self.content_scale;
+    surface.text(iced::advanced::Text {
         content: &self.content.to_string(),
-        size: self.size as f32,
+        size: self.size as f32 * content_scale,
         line_height: widget_text::LineHeight::default(),
         bounds: iced::Rectangle {
-          x: bounds.x + self.padding.left, 
-          y: bounds.y + self.padding.top,
+          x: bounds.x + self.padding.left * content_scale + bounds.width * shrink / 2.0,
+          y: bounds.y + self.padding.top * content_scale + bounds.height * shrink / 2.0,
           ..bounds
         },
         color: self.color,
-        font: iced::Font::MONOSPACE,
+        font: self.style.font,
         horizontal_alignment: alignment::Horizontal::Left,
         vertical_alignment: alignment::Vertical::Top,
         shaping: widget_text::Shaping::Advanced,
     });
-    
   }
+}
 
-  fn mouse_interaction(&self, _tree: &tree::Tree, layout: iced::advanced::Layout<'_>, cursor: mouse::Cursor, _viewport: &iced::Rectangle, _renderer: &iced::Renderer) -> mouse::Interaction {
-    let is_mouse_over = cursor.is_over(layout.bounds());
-    let is_enabled = self.on_left_click.is_some();
-    if is_mouse_over && is_enabled {
-      mouse::Interaction::Pointer
-    } else {
-      mouse::Interaction::default()
+/// Where cells are drawn: straight to the renderer, or on a canvas frame which can be cached.
+pub trait Surface {
+  /// A rectangle with rounded corners, and a border of the given width and color inside its edge.
+  fn quad(&mut self, bounds: iced::Rectangle, radius: f32, border: (f32, iced::Color), color: iced::Color);
+  fn text(&mut self, text: iced::advanced::Text<'_, iced::Font>);
+}
+
+impl Surface for iced::Renderer {
+  fn quad(&mut self, bounds: iced::Rectangle, radius: f32, (border_width, border_color): (f32, iced::Color), color: iced::Color) {
+    iced::advanced::Renderer::fill_quad(self, renderer::Quad {bounds, border_radius: radius.into(), border_width, border_color}, iced::Background::Color(color));
+  }
+
+  fn text(&mut self, text: iced::advanced::Text<'_, iced::Font>) {
+    advanced_text::Renderer::fill_text(self, text);
+  }
+}
+
+impl Surface for canvas::Frame {
+  fn quad(&mut self, bounds: iced::Rectangle, radius: f32, (border_width, border_color): (f32, iced::Color), color: iced::Color) {
+    if color.a > 0.0 {
+      self.fill(&rounded(bounds, radius), color);
+    }
+    if border_width > 0.0 {
+      //Strokes are centered on the path, so it is pulled in by half the width to keep the border inside.
+      let half = border_width / 2.0;
+      let inside = iced::Rectangle {x: bounds.x + half, y: bounds.y + half, width: bounds.width - border_width, height: bounds.height - border_width};
+      self.stroke(&rounded(inside, (radius - half).max(0.0)), canvas::Stroke::default().with_color(border_color).with_width(border_width));
     }
   }
 
+  fn text(&mut self, text: iced::advanced::Text<'_, iced::Font>) {
+    self.fill_text(canvas::Text {
+      content: text.content.to_string(),
+      position: text.bounds.position(),
+      color: text.color,
+      size: text.size,
+      line_height: text.line_height,
+      font: text.font,
+      horizontal_alignment: text.horizontal_alignment,
+      vertical_alignment: text.vertical_alignment,
+      shaping: text.shaping,
+    });
+  }
+}
+
+fn rounded(bounds: iced::Rectangle, radius: f32) -> canvas::Path {
+  if radius <= 0.0 {
+    return canvas::Path::rectangle(bounds.position(), bounds.size());
+  }
+  let (left, top, right, bottom) = (bounds.x, bounds.y, bounds.x + bounds.width, bounds.y + bounds.height);
+  let point = iced::Point::new;
+  canvas::Path::new(|path| {
+    path.move_to(point(left + radius, top));
+    path.arc_to(point(right, top), point(right, bottom), radius);
+    path.arc_to(point(right, bottom), point(left, bottom), radius);
+    path.arc_to(point(left, bottom), point(left, top), radius);
+    path.arc_to(point(left, top), point(right, top), radius);
+    path.close();
+  })
 }
 
 impl<'a, Message> From<Cell<Message>> for iced::Element<'a, Message>
@@ -188,21 +340,60 @@ where Message: Clone + 'a
   }
 }
 
-/// For middle press, both left and right buttons get set to true
-#[derive(Clone)]
-pub struct State {
-  is_left_pressed: bool,
-  is_right_pressed: bool,
-  previous_click_time: time::Instant,
+/// What a cell remembers between frames.
+#[derive(Default)]
+struct State {
+  tracker: Tracker,
+  /// When the cursor came onto the cell.
+  hovered_since: Option<time::Instant>,
 }
 
-impl State {
-  /// Creates a new [`State`].
-  pub fn new() -> State {
-    State {
-      is_left_pressed: false,
-      is_right_pressed: false,
-      previous_click_time: time::Instant::now(),  //Wish there were a way to initiate this to 0.
-    }
+/// A line of text floating above a cell.
+pub struct Tooltip {
+  pub text: String,
+  /// The size of the cell, which the tooltip is placed around.
+  pub cell: iced::Size,
+}
+
+impl<Message> overlay::Overlay<Message, iced::Renderer> for Tooltip {
+  fn layout(&self, renderer: &iced::Renderer, bounds: iced::Size, position: iced::Point) -> layout::Node {
+    let text = advanced_text::Renderer::measure(renderer, &self.text, TOOLTIP_SIZE, widget_text::LineHeight::default(),
+      iced::Font::default(), iced::Size::INFINITY, widget_text::Shaping::Advanced);
+    let size = iced::Size::new(text.width + TOOLTIP_PADDING * 2.0, text.height + TOOLTIP_PADDING * 2.0);
+    //Centered above the cell, or below it along the top of the window, and never off the sides.
+    let x = (position.x + (self.cell.width - size.width) / 2.0).clamp(0.0, (bounds.width - size.width).max(0.0));
+    let y = if position.y >= size.height { position.y - size.height } else { position.y + self.cell.height };
+    let mut node = layout::Node::new(size);
+    node.move_to(iced::Point::new(x, y));
+    node
+  }
+
+  fn draw(&self, renderer: &mut iced::Renderer, theme: &iced::Theme, _style: &renderer::Style, layout: iced::advanced::Layout<'_>, _cursor: mouse::Cursor) {
+    let bounds = layout.bounds();
+    //Inverted colors stand out from the board in both light and dark themes.
+    iced::advanced::Renderer::fill_quad(renderer,
+      renderer::Quad {
+        bounds,
+        border_radius: 4.0.into(),
+        border_width: 0.0,
+        border_color: iced::Color::TRANSPARENT,
+      },
+      iced::Background::Color(theme.palette().text),
+    );
+    advanced_text::Renderer::fill_text(renderer, iced::advanced::Text {
+      content: &self.text,
+      size: TOOLTIP_SIZE,
+      line_height: widget_text::LineHeight::default(),
+      bounds: iced::Rectangle {
+        x: bounds.x + TOOLTIP_PADDING,
+        y: bounds.y + TOOLTIP_PADDING,
+        ..bounds
+      },
+      color: theme.palette().background,
+      font: iced::Font::default(),
+      horizontal_alignment: alignment::Horizontal::Left,
+      vertical_alignment: alignment::Vertical::Top,
+      shaping: widget_text::Shaping::Advanced,
+    });
   }
 }
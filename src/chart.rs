@@ -0,0 +1,98 @@
+//! Charts of solve times for the statistics view, drawn on a canvas.
+use iced::widget::canvas;
+use iced::{mouse, Point, Rectangle, Size};
+
+//Room left for the labels.
+const MARGIN: f32 = 20.0;
+const BUCKETS: usize = 10;
+
+/// Solve times in the order they were played, as a line.
+pub struct Trend(pub Vec<f32>);
+
+/// How many solve times fall into each range.
+pub struct Histogram(pub Vec<f32>);
+
+fn range(times: &[f32]) -> (f32, f32) {
+  let min = times.iter().copied().fold(f32::INFINITY, f32::min);
+  let max = times.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+  //Keep a single time, or all the same times, from dividing by zero.
+  (min, max.max(min + 1.0))
+}
+
+fn label(frame: &mut canvas::Frame, content: String, position: Point, color: iced::Color) {
+  frame.fill_text(canvas::Text {content, position, color, size: 12.0, ..Default::default()});
+}
+
+fn frame(renderer: &iced::Renderer, theme: &iced::Theme, bounds: Rectangle, title: &str) -> canvas::Frame {
+  let mut frame = canvas::Frame::new(renderer, bounds.size());
+  let palette = theme.palette();
+  let axes = canvas::Path::new(|path| {
+    path.move_to(Point::new(MARGIN, 0.0));
+    path.line_to(Point::new(MARGIN, bounds.height - MARGIN));
+    path.line_to(Point::new(bounds.width, bounds.height - MARGIN));
+  });
+  frame.stroke(&axes, canvas::Stroke::default().with_color(palette.text));
+  label(&mut frame, String::from(title), Point::new(MARGIN + 4.0, 0.0), palette.text);
+  frame
+}
+
+impl<Message> canvas::Program<Message> for Trend {
+  type State = ();
+
+  fn draw(&self, _state: &(), renderer: &iced::Renderer, theme: &iced::Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<canvas::Geometry> {
+    let mut frame = frame(renderer, theme, bounds, "Seconds per game");
+    let (min, max) = range(&self.0);
+    let width = bounds.width - MARGIN;
+    let height = bounds.height - MARGIN;
+    let point = |index: usize, time: f32| Point::new(
+      MARGIN + width * index as f32 / (self.0.len().max(2) - 1) as f32,
+      height - height * (time - min) / (max - min),
+    );
+    let line = canvas::Path::new(|path| {
+      for (index, &time) in self.0.iter().enumerate() {
+        if index == 0 {
+          path.move_to(point(index, time));
+        } else {
+          path.line_to(point(index, time));
+        }
+      }
+    });
+    frame.stroke(&line, canvas::Stroke::default().with_color(theme.palette().primary).with_width(2.0));
+    if !self.0.is_empty() {
+      label(&mut frame, format!("{:.0}", max), Point::new(0.0, 0.0), theme.palette().text);
+      label(&mut frame, format!("{:.0}", min), Point::new(0.0, height - 12.0), theme.palette().text);
+    }
+    vec![frame.into_geometry()]
+  }
+}
+
+impl<Message> canvas::Program<Message> for Histogram {
+  type State = ();
+
+  fn draw(&self, _state: &(), renderer: &iced::Renderer, theme: &iced::Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<canvas::Geometry> {
+    let mut frame = frame(renderer, theme, bounds, "Games per time");
+    let (min, max) = range(&self.0);
+    let mut counts = [0usize; BUCKETS];
+    for &time in &self.0 {
+      let bucket = ((time - min) / (max - min) * BUCKETS as f32) as usize;
+      counts[bucket.min(BUCKETS - 1)] += 1;
+    }
+    let most = counts.iter().copied().max().unwrap_or_default().max(1);
+    let width = (bounds.width - MARGIN) / BUCKETS as f32;
+    let height = bounds.height - MARGIN;
+    for (bucket, &count) in counts.iter().enumerate() {
+      let bar = height * count as f32 / most as f32;
+      frame.fill_rectangle(
+        Point::new(MARGIN + width * bucket as f32 + 1.0, height - bar),
+        Size::new(width - 2.0, bar),
+        theme.palette().primary,
+      );
+    }
+    if !self.0.is_empty() {
+      label(&mut frame, format!("{:.0}", min), Point::new(MARGIN, height + 4.0), theme.palette().text);
+      label(&mut frame, format!("{:.0}", max), Point::new(bounds.width - 24.0, height + 4.0), theme.palette().text);
+      label(&mut frame, most.to_string(), Point::new(0.0, 0.0), theme.palette().text);
+    }
+    vec![frame.into_geometry()]
+  }
+}
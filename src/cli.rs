@@ -0,0 +1,315 @@
+//! Modes run from the command line instead of opening the window, like `minesweeper solve board.mbf`.
+use crate::narration::cell_name;
+use crate::settings;
+use minesweeper::action::Action;
+use minesweeper::config::GameConfig;
+use minesweeper::engine::{CellStatus, CellValue, Engine, GameStatus, Neighbors};
+use minesweeper::pack::{self, Pack, Results};
+use minesweeper::{config, plausibility, probability, schema, solver};
+use std::collections::BTreeMap;
+use std::{fs, path};
+
+const USAGE: &str = "Usage: minesweeper solve BOARD [X Y]\n       minesweeper analyze COUNT [beginner|intermediate] [fair] [gradient] [diagonal|orthogonal]\n       minesweeper solvers COUNT\n       minesweeper pack sign PACK KEY\n       minesweeper pack check PACK RESULTS";
+//Boards with 3BV this close together share a row of the analysis.
+const THREE_BV_BUCKET: usize = 10;
+
+/// Runs the mode named by the arguments, returning the exit code. `None` when there is no mode, so the game opens.
+pub fn run(args: &[String]) -> Option<i32> {
+  let mode: fn(&[String]) -> Result<(), String> = match args.first().map(String::as_str) {
+    Some("solve") => solve,
+    Some("analyze") => analyze,
+    Some("solvers") => solvers,
+    Some("pack") => pack,
+    Some("help" | "--help" | "-h") => help,
+    _ => return None,
+  };
+  attach_console();
+  match mode(&args[1..]) {
+    Ok(()) => Some(0),
+    Err(error) => {
+      eprintln!("{}", error);
+      Some(1)
+    },
+  }
+}
+
+/// Release builds on Windows are GUI programs, which start without a console, so output goes to the one they were run from.
+#[cfg(windows)]
+fn attach_console() {
+  const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+  #[link(name = "kernel32")]
+  extern "system" {
+    fn AttachConsole(process: u32) -> i32;
+  }
+  //Started from Explorer, there is no console to attach to, and nothing to print to either way.
+  unsafe {
+    AttachConsole(ATTACH_PARENT_PROCESS);
+  }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn help(_: &[String]) -> Result<(), String> {
+  println!("{}", USAGE);
+  Ok(())
+}
+
+/// The board in a board file: MBF, or text with `*` for mines and `.` for safe cells, one line per row.
+pub fn load_board(path: &path::Path) -> Result<Engine, String> {
+  let bytes = fs::read(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+  if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("mbf")) {
+    //Width, height, the mine count as two bytes big endian, then a byte each for the x and y of every mine.
+    let [width, height, high, low, positions @ ..] = &bytes[..] else { return Err(String::from("The MBF file is too short")) };
+    let count = u16::from_be_bytes([*high, *low]) as usize;
+    if positions.len() < count * 2 {
+      return Err(String::from("The MBF file is missing mines"));
+    }
+    let mines: Vec<(usize, usize)> = positions.chunks(2).take(count).map(|position| (position[0] as usize, position[1] as usize)).collect();
+    return Engine::from_mines(*width as usize, *height as usize, &mines);
+  }
+  let text = String::from_utf8(bytes).map_err(|error| error.to_string())?;
+  let rows: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+  let width = rows.first().map_or(0, |row| row.chars().count());
+  if rows.iter().any(|row| row.chars().count() != width) {
+    return Err(String::from("Every line of a text board needs as many cells"));
+  }
+  let mut mines = Vec::new();
+  for (y, row) in rows.iter().enumerate() {
+    for (x, letter) in row.chars().enumerate() {
+      match letter {
+        '*' | 'x' | 'X' => mines.push((x, y)),
+        '.' => (),
+        letter => return Err(format!("Unknown cell {} at {}", letter, cell_name(x, y))),
+      }
+    }
+  }
+  Engine::from_mines(width, rows.len(), &mines)
+}
+
+/// The first opening in reading order, or the middle when there is none.
+fn first_opening(engine: &Engine) -> (usize, usize) {
+  let board = &engine.board;
+  (0..board.height).flat_map(|y| (0..board.width).map(move |x| (x, y)))
+    .find(|&(x, y)| board[x][y].value == CellValue::Number(0))
+    .unwrap_or((board.width / 2, board.height / 2))
+}
+
+/// Prints what the solver picked in the settings can work out from the start, step by step, and whether that clears the board.
+fn solve(args: &[String]) -> Result<(), String> {
+  let path = args.first().ok_or(USAGE)?;
+  let mut engine = load_board(path::Path::new(path))?;
+  let (width, height) = (engine.board.width, engine.board.height);
+  let start = match (args.get(1), args.get(2)) {
+    (Some(x), Some(y)) => (x.parse().map_err(|_| USAGE)?, y.parse().map_err(|_| USAGE)?),
+    _ => first_opening(&engine),
+  };
+  if !engine.board.contains(start.0, start.1) {
+    return Err(format!("{} {} is off the board", start.0, start.1));
+  }
+  let solver = settings::Settings::load().solver.solver();
+  println!("Start: reveal {}", cell_name(start.0, start.1));
+  engine.act(Action::Reveal(start.0, start.1));
+  let mut mines_found = Vec::new();
+  let mut step = 0;
+  while engine.status == GameStatus::Playing {
+    let known = solver.known(&engine.board, engine.neighbors);
+    let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+    let safe: Vec<(usize, usize)> = cells.clone().filter(|&(x, y)| known[x][y] == Some(false) && engine.board[x][y].status == CellStatus::Covered).collect();
+    let mines: Vec<(usize, usize)> = cells.filter(|&(x, y)| known[x][y] == Some(true) && !mines_found.contains(&(x, y))).collect();
+    if safe.is_empty() {
+      break;
+    }
+    step += 1;
+    let names = |cells: &[(usize, usize)]| cells.iter().map(|&(x, y)| cell_name(x, y)).collect::<Vec<_>>().join(" ");
+    if mines.is_empty() {
+      println!("Step {}: reveal {}", step, names(&safe));
+    } else {
+      println!("Step {}: mines at {}, so reveal {}", step, names(&mines), names(&safe));
+    }
+    mines_found.extend(mines);
+    for (x, y) in safe {
+      engine.act(Action::Reveal(x, y));
+    }
+  }
+  match engine.status {
+    GameStatus::Won => println!("Solvable without guessing"),
+    GameStatus::Lost => println!("The start is a mine"),
+    GameStatus::Playing => println!("Needs a guess after step {}, with {} cells still covered", step, engine.board.iter().flatten().filter(|cell| cell.status != CellStatus::Revealed).count()),
+  }
+  println!("Fewest clicks (3BV): {}", engine.three_bv());
+  Ok(())
+}
+
+#[derive(Default)]
+struct Bucket {
+  boards: usize,
+  no_guess: usize,
+  guesses: usize,
+  won: usize,
+}
+
+impl Bucket {
+  fn add(&mut self, other: &Bucket) {
+    self.boards += other.boards;
+    self.no_guess += other.no_guess;
+    self.guesses += other.guesses;
+    self.won += other.won;
+  }
+
+  fn row(&self, name: &str) -> String {
+    let boards = self.boards.max(1) as f32;
+    format!("{},{},{:.3},{:.2},{:.3}", name, self.boards, self.no_guess as f32 / boards, self.guesses as f32 / boards, self.won as f32 / boards)
+  }
+}
+
+/// Plays the board from its first opening until it is over, revealing every cell `solver` finds safe,
+/// and when there are none, guessing the cell it thinks is least likely a mine. Returns how many guesses that took.
+fn play_out(engine: &mut Engine, solver: &dyn solver::Solver) -> usize {
+  let (x, y) = first_opening(engine);
+  engine.act(Action::Reveal(x, y));
+  let mut guesses = 0;
+  while engine.status == GameStatus::Playing {
+    let known = solver.known(&engine.board, engine.neighbors);
+    let covered = engine.board.positions().filter(|&(x, y)| engine.board[x][y].status == CellStatus::Covered);
+    let safe: Vec<(usize, usize)> = covered.filter(|&(x, y)| known[x][y] == Some(false)).collect();
+    if !safe.is_empty() {
+      for (x, y) in safe {
+        engine.act(Action::Reveal(x, y));
+      }
+      continue;
+    }
+    let danger = solver.danger(&engine.board, engine.neighbors);
+    let guess = engine.board.positions()
+      .filter(|&(x, y)| engine.board[x][y].status == CellStatus::Covered && known[x][y] != Some(true))
+      .min_by(|&(x, y), &(other_x, other_y)| danger[x][y].partial_cmp(&danger[other_x][other_y]).unwrap_or(std::cmp::Ordering::Equal));
+    let Some((x, y)) = guess else { break };
+    guesses += 1;
+    engine.act(Action::Reveal(x, y));
+  }
+  guesses
+}
+
+/// Plays the boards of seeds 0 to COUNT from their first opening, and prints as CSV how many there are by 3BV,
+/// how often they clear without guessing, how many guesses they take on average and how often they are won.
+/// Guesses go for the cell the solver thinks is least likely a mine.
+fn analyze(args: &[String]) -> Result<(), String> {
+  let count: u64 = args.first().and_then(|count| count.parse().ok()).ok_or(USAGE)?;
+  let mut fair = false;
+  let mut gradient = false;
+  let mut neighbors = Neighbors::All;
+  let (_, mut width, mut height, mut mines) = config::EXPERT;
+  for arg in &args[1..] {
+    match arg.as_str() {
+      "beginner" => (_, width, height, mines) = config::BEGINNER,
+      "intermediate" => (_, width, height, mines) = config::INTERMEDIATE,
+      "fair" => fair = true,
+      "gradient" => gradient = true,
+      "diagonal" => neighbors = Neighbors::Diagonal,
+      "orthogonal" => neighbors = Neighbors::Orthogonal,
+      _ => return Err(String::from(USAGE)),
+    }
+  }
+  let mut buckets: BTreeMap<usize, Bucket> = BTreeMap::new();
+  for seed in 0..count {
+    let mut engine = GameConfig::new(seed).with_size(width, height, mines).with_neighbors(neighbors).with_gradient(gradient).with_fair_layout(fair).build();
+    let bucket = buckets.entry(engine.three_bv() / THREE_BV_BUCKET).or_default();
+    let guesses = play_out(&mut engine, &probability::Exact);
+    bucket.boards += 1;
+    bucket.no_guess += (guesses == 0 && engine.status == GameStatus::Won) as usize;
+    bucket.guesses += guesses;
+    bucket.won += (engine.status == GameStatus::Won) as usize;
+  }
+  println!("three_bv,boards,no_guess_rate,average_guesses,win_rate");
+  let mut all = Bucket::default();
+  for (bucket, counts) in &buckets {
+    println!("{}", counts.row(&format!("{}-{}", bucket * THREE_BV_BUCKET, bucket * THREE_BV_BUCKET + THREE_BV_BUCKET - 1)));
+    all.add(counts);
+  }
+  println!("{}", all.row("all"));
+  Ok(())
+}
+
+/// Plays the boards of seeds 0 to COUNT with each solver, and prints as CSV how often each wins,
+/// how many guesses it takes on average and how long it takes per board.
+fn solvers(args: &[String]) -> Result<(), String> {
+  let count: u64 = args.first().and_then(|count| count.parse().ok()).ok_or(USAGE)?;
+  println!("solver,boards,win_rate,average_guesses,ms_per_board");
+  for kind in solver::Kind::ALL {
+    let solver = kind.solver();
+    let (mut won, mut guesses) = (0, 0);
+    let start = std::time::Instant::now();
+    for seed in 0..count {
+      let mut engine = GameConfig::new(seed).build();
+      guesses += play_out(&mut engine, solver);
+      won += (engine.status == GameStatus::Won) as usize;
+    }
+    let boards = count.max(1) as f32;
+    println!("{},{},{:.3},{:.2},{:.2}", solver.name(), count, won as f32 / boards, guesses as f32 / boards, start.elapsed().as_secs_f32() * 1000.0 / boards);
+  }
+  Ok(())
+}
+
+/// Tools for tournament organizers: signing a seed pack before handing it out, and checking the results sent back.
+fn pack(args: &[String]) -> Result<(), String> {
+  let read = |path: &String| fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error));
+  match (args.first().map(String::as_str), args.get(1), args.get(2)) {
+    (Some("sign"), Some(path), Some(key_path)) => {
+      //Read without checking the signature, which isn't there yet.
+      let mut pack: Pack = schema::PACK.from_json(&read(path)?)?;
+      let (key, new) = pack::load_or_create_key(path::Path::new(key_path))?;
+      if new {
+        println!("Made a new key in {}. Keep it secret, and sign all your packs with it", key_path);
+      }
+      pack.sign(&key);
+      fs::write(path, pack.to_json()).map_err(|error| format!("{}: {}", path, error))?;
+      println!("{} boards of {}, announce the key {}", pack.boards.len(), pack.name, pack.key);
+      Ok(())
+    },
+    (Some("check"), Some(pack), Some(results)) => {
+      let pack = Pack::from_json(&read(pack)?)?;
+      let results = Results::from_json(&read(results)?)?;
+      let engines = results.check(&pack)?;
+      println!("{} by {} with the key {}, pack signed by the key {}", results.describe(&pack), results.player, results.key, pack.key);
+      for (index, (game, engine)) in results.games.iter().zip(engines).enumerate() {
+        let seconds = game.times.last().copied().unwrap_or_default() as f32 / 1000.0;
+        let result = match engine.status {
+          GameStatus::Won => "won",
+          GameStatus::Lost => "lost",
+          GameStatus::Playing => "left",
+        };
+        println!("Board {}: {} after {:.1}s, 3BV {}", index + 1, result, seconds, engine.three_bv());
+        for suspicion in plausibility::check(game) {
+          println!("  Suspicious: {}", suspicion);
+        }
+      }
+      Ok(())
+    },
+    _ => Err(String::from(USAGE)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn text_and_mbf_boards_match() {
+    let engine = GameConfig::new(7).with_size(16, 9, 20).build();
+    let (width, height) = (engine.board.width, engine.board.height);
+    let mines: Vec<(usize, usize)> = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+      .filter(|&(x, y)| engine.board[x][y].value == CellValue::Mined).collect();
+    let text: String = (0..height).map(|y| (0..width).map(|x| if mines.contains(&(x, y)) { '*' } else { '.' }).collect::<String>() + "\n").collect();
+    let mut mbf = vec![width as u8, height as u8];
+    mbf.extend((mines.len() as u16).to_be_bytes());
+    mbf.extend(mines.iter().flat_map(|&(x, y)| [x as u8, y as u8]));
+    let dir = std::env::temp_dir();
+    fs::write(dir.join("minesweeper-test.txt"), text).unwrap();
+    fs::write(dir.join("minesweeper-test.mbf"), mbf).unwrap();
+    for file in ["minesweeper-test.txt", "minesweeper-test.mbf"] {
+      let loaded = load_board(&dir.join(file)).unwrap();
+      assert_eq!((loaded.board.width, loaded.board.height), (width, height));
+      assert_eq!(loaded.dump(), engine.dump());
+    }
+  }
+}
@@ -0,0 +1,194 @@
+//! Game timer. Starts on the first reveal, and can be paused.
+//!
+//! The clock keeps exact instants, so how often it is shown doesn't change the times recorded.
+//! A redraw coming far later than it was due means the computer was asleep, and the clock is paused from the redraw before.
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Shortest time between redraws of the clock.
+pub const MIN_TICK_MS: u64 = 50;
+/// How much later than due a redraw has to come for the computer to have been asleep.
+pub const SUSPEND_GAP: Duration = Duration::from_secs(5);
+
+/// How the clock is shown.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerFormat {
+  /// Whole seconds, like `75` or `1,250`.
+  #[default]
+  Seconds,
+  /// Minutes and seconds, like `1:15`, and hours too past the first one, like `1:02:05`.
+  Minutes,
+  /// Seconds with tenths, like `75.3`.
+  Tenths,
+}
+
+/// `number` with `separator` between each group of three digits, like `12,345`.
+pub fn group_digits(number: u64, separator: &str) -> String {
+  let digits = number.to_string();
+  let mut grouped = String::new();
+  for (index, digit) in digits.chars().enumerate() {
+    if index > 0 && (digits.len() - index).is_multiple_of(3) {
+      grouped += separator;
+    }
+    grouped.push(digit);
+  }
+  grouped
+}
+
+impl TimerFormat {
+  /// Seconds are grouped with `separator`. The clock is text, so it never runs out of digits.
+  pub fn format(self, time: Duration, separator: &str) -> String {
+    let seconds = time.as_secs();
+    match self {
+      TimerFormat::Seconds => group_digits(seconds, separator),
+      TimerFormat::Minutes if seconds >= 3600 => format!("{}:{:02}:{:02}", group_digits(seconds / 3600, separator), seconds / 60 % 60, seconds % 60),
+      TimerFormat::Minutes => format!("{}:{:02}", seconds / 60, seconds % 60),
+      TimerFormat::Tenths => format!("{}.{}", group_digits(seconds, separator), time.subsec_millis() / 100),
+    }
+  }
+
+  /// How often the clock has to be redrawn to show every change.
+  pub fn tick(self) -> Duration {
+    match self {
+      TimerFormat::Seconds | TimerFormat::Minutes => Duration::from_secs(1),
+      TimerFormat::Tenths => Duration::from_millis(100),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Clock {
+  started: Option<Instant>,
+  paused: Option<Instant>,
+  stopped: Option<Instant>,
+  /// When the running clock was last redrawn, by the monotonic clock and the wall clock.
+  last_tick: Option<(Instant, SystemTime)>,
+}
+
+impl Clock {
+  /// Does nothing if the clock was already started.
+  pub fn start(&mut self) {
+    if self.started.is_none() {
+      self.started = Some(Instant::now());
+    }
+  }
+
+  pub fn stop(&mut self) {
+    if self.stopped.is_none() {
+      self.stopped = Some(self.paused.unwrap_or_else(Instant::now));
+    }
+  }
+
+  pub fn is_running(&self) -> bool {
+    self.started.is_some() && self.paused.is_none() && self.stopped.is_none()
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused.is_some()
+  }
+
+  pub fn pause(&mut self) {
+    if self.is_running() {
+      self.paused = Some(Instant::now());
+    }
+  }
+
+  /// Time spent paused doesn't count. A stopped clock keeps its time, and only stops showing as paused.
+  pub fn resume(&mut self) {
+    self.last_tick = None;
+    if self.stopped.is_some() {
+      self.paused = None;
+      return;
+    }
+    if let (Some(started), Some(paused)) = (&mut self.started, self.paused.take()) {
+      *started += paused.elapsed();
+    }
+  }
+
+  /// A redraw due every `period`. When it comes more than [`SUSPEND_GAP`] late, the computer was suspended in between,
+  /// so the clock is paused from the redraw before and this returns true.
+  pub fn tick(&mut self, now: Instant, wall: SystemTime, period: Duration) -> bool {
+    let last = self.last_tick.replace((now, wall));
+    let Some((last, last_wall)) = last.filter(|_| self.is_running()) else { return false };
+    //Some platforms stop the monotonic clock while suspended, but the wall clock always goes on.
+    let gap = now.duration_since(last).max(wall.duration_since(last_wall).unwrap_or_default());
+    if gap < period + SUSPEND_GAP {
+      return false;
+    }
+    self.paused = Some(last);
+    true
+  }
+
+  /// Take time off the clock, down to no time at all.
+  pub fn bonus(&mut self, bonus: Duration) {
+    let latest = self.stopped.or(self.paused).unwrap_or_else(Instant::now);
+    if let Some(started) = &mut self.started {
+      *started = (*started + bonus).min(latest);
+    }
+  }
+
+  /// Add time to the clock.
+  pub fn penalty(&mut self, penalty: Duration) {
+    if let Some(started) = &mut self.started {
+      *started = started.checked_sub(penalty).unwrap_or(*started);
+    }
+  }
+
+  pub fn elapsed(&self) -> Duration {
+    match self.started {
+      Some(started) => self.stopped.or(self.paused).unwrap_or_else(Instant::now).duration_since(started),
+      None => Duration::ZERO,
+    }
+  }
+
+  /// Time left before `limit` runs out.
+  pub fn remaining(&self, limit: Duration) -> Duration {
+    limit.saturating_sub(self.elapsed())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn suspends_pause_the_clock() {
+    let mut clock = Clock::default();
+    clock.start();
+    let (start, wall) = (Instant::now(), SystemTime::now());
+    let period = Duration::from_secs(1);
+    assert!(!clock.tick(start, wall, period));
+    assert!(!clock.tick(start + period, wall + period, period));
+    //The monotonic clock stood still while the wall clock went on an hour.
+    assert!(clock.tick(start + period * 2, wall + Duration::from_secs(3600), period));
+    assert!(clock.is_paused());
+    assert!(clock.elapsed() < Duration::from_secs(5));
+  }
+
+  #[test]
+  fn resuming_after_the_end_keeps_the_time() {
+    let mut clock = Clock::default();
+    clock.start();
+    std::thread::sleep(Duration::from_millis(20));
+    clock.pause();
+    std::thread::sleep(Duration::from_millis(20));
+    clock.stop();
+    let elapsed = clock.elapsed();
+    clock.resume();
+    assert!(!clock.is_paused());
+    assert_eq!(clock.elapsed(), elapsed);
+    assert!(elapsed >= Duration::from_millis(20));
+  }
+
+  #[test]
+  fn long_games_keep_every_digit() {
+    let time = Duration::from_millis(1_234_500);
+    assert_eq!(TimerFormat::Seconds.format(time, ","), "1,234");
+    assert_eq!(TimerFormat::Tenths.format(time, " "), "1 234.5");
+    assert_eq!(TimerFormat::Minutes.format(time, ","), "20:34");
+    assert_eq!(TimerFormat::Minutes.format(Duration::from_secs(3725), ","), "1:02:05");
+    assert_eq!(group_digits(999, ","), "999");
+    assert_eq!(group_digits(1_000_000, "."), "1.000.000");
+  }
+}
@@ -0,0 +1,44 @@
+//! Combo meter for the arcade mode. Moves which open cells quickly one after another build it up.
+use minesweeper::scoring;
+use std::time::{Duration, Instant};
+
+//Taking longer than this between moves is hesitating, and drops the combo.
+const WINDOW: Duration = Duration::from_millis(1500);
+
+#[derive(Default)]
+pub struct Combo {
+  pub moves: u32,
+  pub best: u32,
+  last: Option<Instant>,
+}
+
+impl Combo {
+  /// A move which opened cells.
+  pub fn hit(&mut self) {
+    let now = Instant::now();
+    if self.last.is_some_and(|last| now.duration_since(last) > WINDOW) {
+      self.moves = 0;
+    }
+    self.moves += 1;
+    self.best = self.best.max(self.moves);
+    self.last = Some(now);
+  }
+
+  /// A click which did nothing, or took a flag back.
+  pub fn miss(&mut self) {
+    self.moves = 0;
+    self.last = None;
+  }
+
+  /// The current combo, which has already dropped if the player waited too long.
+  pub fn current(&self) -> u32 {
+    match self.last {
+      Some(last) if last.elapsed() <= WINDOW => self.moves,
+      _ => 0,
+    }
+  }
+
+  pub fn best_multiplier(&self) -> f64 {
+    scoring::combo_multiplier(self.best)
+  }
+}
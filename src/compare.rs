@@ -0,0 +1,163 @@
+//! Two replays of the same board played side by side on a shared timeline, for studying where they went different ways.
+use crate::narration::cell_name;
+use iced::widget::canvas;
+use iced::{mouse, Point, Rectangle, Size};
+use minesweeper::action::Action;
+use minesweeper::engine::{Cell, CellStatus, CellValue, Engine};
+use minesweeper::history::History;
+use minesweeper::replay::Replay;
+use minesweeper::solver;
+
+/// What the comparison screen asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  Seek(f64),
+  Play,
+  /// Moves the timeline along while playing.
+  Step,
+  /// Show how dangerous each covered cell was.
+  Heatmap(bool),
+  Close,
+}
+
+pub struct Run {
+  pub name: String,
+  pub replay: Replay,
+  /// The board at the current time on the timeline.
+  pub engine: History,
+}
+
+pub struct Comparison {
+  pub runs: [Run; 2],
+  /// Milliseconds into the timeline.
+  pub time: u64,
+  pub playing: bool,
+  /// The first move where the runs did something different.
+  pub diverged: Option<usize>,
+  /// Shades the covered cells by how likely the solver thinks they are mines.
+  pub heatmap: bool,
+}
+
+impl Comparison {
+  /// Fails unless both replays are of the same board.
+  pub fn new(first: (String, Replay), second: (String, Replay)) -> Result<Comparison, String> {
+    if first.1.start().state_hash() != second.1.start().state_hash() {
+      return Err(String::from("The replays are of different boards"));
+    }
+    let diverged = (0..first.1.moves.len().max(second.1.moves.len())).find(|&index| first.1.moves.get(index) != second.1.moves.get(index));
+    let run = |(name, replay): (String, Replay)| Run {name, engine: History::from_replay(&replay), replay};
+    let mut comparison = Comparison {runs: [run(first), run(second)], time: 0, playing: false, diverged, heatmap: false};
+    comparison.seek(0);
+    Ok(comparison)
+  }
+
+  /// Where the timeline ends, when the slower run finishes.
+  pub fn length(&self) -> u64 {
+    self.runs.iter().map(|run| run.replay.moves.len().checked_sub(1).map_or(0, |last| run.replay.time(last))).max().unwrap_or_default()
+  }
+
+  /// Move both boards to a time on the timeline.
+  pub fn seek(&mut self, time: u64) {
+    self.time = time.min(self.length());
+    for run in &mut self.runs {
+      let played = (0..run.replay.moves.len()).take_while(|&index| run.replay.time(index) <= self.time).count();
+      run.engine.seek(played);
+    }
+  }
+
+  /// What each run did at the first difference, in words.
+  pub fn describe(&self) -> String {
+    let Some(index) = self.diverged else {
+      return String::from("Both runs played the same moves");
+    };
+    let describe = |run: &Run| match run.replay.moves.get(index) {
+      Some(Action::Reveal(x, y)) => format!("{} revealed {}", run.name, cell_name(*x, *y)),
+      Some(Action::Flag(x, y)) => format!("{} flagged {}", run.name, cell_name(*x, *y)),
+      Some(Action::Chord(x, y)) => format!("{} chorded {}", run.name, cell_name(*x, *y)),
+      None => format!("{} had stopped", run.name),
+    };
+    format!("Diverged at move {}: {}, {}", index + 1, describe(&self.runs[0]), describe(&self.runs[1]))
+  }
+
+  /// The move a run plays next, with `solver`'s guess at how likely it was to hit a mine.
+  pub fn next_move(&self, run: &Run, solver: &dyn solver::Solver) -> String {
+    let Some(&action) = run.replay.moves.get(run.engine.position()) else {
+      return String::from("Finished");
+    };
+    let (x, y) = action.position();
+    match action {
+      Action::Flag(..) => format!("Next: flags {}", cell_name(x, y)),
+      //A chord is as risky as the riskiest cell it opens.
+      Action::Reveal(..) | Action::Chord(..) => {
+        let danger = solver.danger(&run.engine.board, run.engine.neighbors);
+        let cells: Vec<(usize, usize)> = match action {
+          Action::Chord(..) => run.engine.neighbors.of(&run.engine.board, x, y).filter(|&(x, y)| run.engine.board[x][y].status == CellStatus::Covered).collect(),
+          _ => vec![(x, y)],
+        };
+        let risk = cells.iter().filter_map(|&(x, y)| danger[x][y]).fold(0.0, f32::max);
+        format!("Next: {} {}, {:.0}% danger", if matches!(action, Action::Chord(..)) { "chords" } else { "reveals" }, cell_name(x, y), risk * 100.0)
+      },
+    }
+  }
+}
+
+fn mix(from: iced::Color, to: iced::Color, amount: f32) -> iced::Color {
+  iced::Color::from_rgb(from.r + (to.r - from.r) * amount, from.g + (to.g - from.g) * amount, from.b + (to.b - from.b) * amount)
+}
+
+/// A small drawing of a board, with one cell outlined.
+pub struct MiniBoard<'a> {
+  pub engine: &'a Engine,
+  pub highlight: Option<(usize, usize)>,
+  /// Colors of the numbers 1 to 8.
+  pub number_colors: [iced::Color; 8],
+  /// Shades covered cells from safe to dangerous, by the solver's danger.
+  pub heatmap: bool,
+  pub solver: &'a dyn solver::Solver,
+}
+
+impl<Message> canvas::Program<Message> for MiniBoard<'_> {
+  type State = ();
+
+  fn draw(&self, _state: &(), renderer: &iced::Renderer, theme: &iced::Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<canvas::Geometry> {
+    let mut frame = canvas::Frame::new(renderer, bounds.size());
+    let palette = theme.palette();
+    let board = &self.engine.board;
+    let length = (bounds.width / board.width as f32).min(bounds.height / board.height as f32);
+    let danger = self.heatmap.then(|| self.solver.danger(&self.engine.board, self.engine.neighbors));
+    for x in 0..board.width {
+      for y in 0..board.height {
+        let position = Point::new(x as f32 * length, y as f32 * length);
+        let size = Size::new(length - 1.0, length - 1.0);
+        match self.engine.board[x][y] {
+          Cell {status: CellStatus::Covered, ..} => {
+            let color = match danger.as_ref().and_then(|danger| danger[x][y]) {
+              Some(danger) => mix(palette.success, palette.danger, danger),
+              None => palette.primary,
+            };
+            frame.fill_rectangle(position, size, color);
+          },
+          Cell {status: CellStatus::Flagged | CellStatus::AntiFlagged, ..} => frame.fill_rectangle(position, size, palette.danger),
+          Cell {status: CellStatus::Revealed, value: CellValue::Mined | CellValue::AntiMine} => frame.fill_rectangle(position, size, palette.text),
+          Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} => {
+            frame.fill_rectangle(position, size, palette.background);
+            if number != 0 {
+              frame.fill_text(canvas::Text {
+                content: number.to_string(),
+                position: Point::new(position.x + length * 0.25, position.y),
+                color: self.number_colors[number.unsigned_abs() as usize - 1],
+                size: length,
+                ..Default::default()
+              });
+            }
+          },
+        }
+      }
+    }
+    if let Some((x, y)) = self.highlight {
+      let outline = canvas::Path::rectangle(Point::new(x as f32 * length, y as f32 * length), Size::new(length - 1.0, length - 1.0));
+      frame.stroke(&outline, canvas::Stroke::default().with_color(palette.success).with_width(2.0));
+    }
+    vec![frame.into_geometry()]
+  }
+}
@@ -0,0 +1,157 @@
+//! A game's seed, size and rules, which every mode builds its boards from, so a board is the same wherever it is played.
+//!
+//! A safe first click is part of it too. The mines are still placed from the seed before any click,
+//! and the first reveal moves them off the cells around it by the seed as well, so replays and links play the same.
+use crate::engine::{Engine, Neighbors, Reward, Transform};
+use serde::{Deserialize, Serialize};
+
+/// The classic sizes, as name, width, height and mines.
+pub const BEGINNER: (&str, usize, usize, usize) = ("Beginner", 9, 9, 10);
+pub const INTERMEDIATE: (&str, usize, usize, usize) = ("Intermediate", 16, 16, 40);
+/// The default, and the only size before sizes could be picked.
+pub const EXPERT: (&str, usize, usize, usize) = ("Expert", 30, 16, 99);
+pub const DIFFICULTIES: [(&str, usize, usize, usize); 3] = [BEGINNER, INTERMEDIATE, EXPERT];
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+  pub seed: u64,
+  pub width: usize,
+  pub height: usize,
+  pub mines: usize,
+  /// The first cell revealed is never a mine.
+  pub safe_start: bool,
+  pub transform: Transform,
+  pub neighbors: Neighbors,
+  pub treasures: usize,
+  pub treasure_reward: Reward,
+  pub flag_limit: Option<usize>,
+  pub flag_win: bool,
+  pub fair: bool,
+  pub gradient: bool,
+  pub antimines: usize,
+  /// Lives the game starts with.
+  pub lives: usize,
+}
+
+impl Default for GameConfig {
+  fn default() -> Self {
+    let (_, width, height, mines) = EXPERT;
+    GameConfig {
+      seed: 0,
+      width,
+      height,
+      mines,
+      safe_start: false,
+      transform: Transform::None,
+      neighbors: Neighbors::All,
+      treasures: 0,
+      treasure_reward: Reward::Time,
+      flag_limit: None,
+      flag_win: false,
+      fair: false,
+      gradient: false,
+      antimines: 0,
+      lives: 0,
+    }
+  }
+}
+
+impl GameConfig {
+  /// The expert board for `seed`, under the standard rules.
+  pub fn new(seed: u64) -> GameConfig {
+    GameConfig {seed, ..Default::default()}
+  }
+
+  /// The seed and rules `engine` was built from.
+  pub fn of(engine: &Engine) -> GameConfig {
+    GameConfig {
+      seed: engine.seed,
+      width: engine.board.width,
+      height: engine.board.height,
+      mines: engine.board.mines,
+      safe_start: engine.safe_start,
+      transform: engine.transform,
+      neighbors: engine.neighbors,
+      treasures: engine.treasures,
+      treasure_reward: engine.treasure_reward,
+      flag_limit: engine.flag_limit,
+      flag_win: engine.flag_win,
+      fair: engine.fair,
+      gradient: engine.gradient,
+      antimines: engine.antimines,
+      lives: engine.starting_lives,
+    }
+  }
+
+  pub fn with_size(self, width: usize, height: usize, mines: usize) -> GameConfig {
+    GameConfig {width, height, mines, ..self}
+  }
+
+  pub fn with_safe_start(self, safe_start: bool) -> GameConfig {
+    GameConfig {safe_start, ..self}
+  }
+
+  pub fn with_transform(self, transform: Transform) -> GameConfig {
+    GameConfig {transform, ..self}
+  }
+
+  pub fn with_neighbors(self, neighbors: Neighbors) -> GameConfig {
+    GameConfig {neighbors, ..self}
+  }
+
+  pub fn with_treasures(self, treasures: usize, treasure_reward: Reward) -> GameConfig {
+    GameConfig {treasures, treasure_reward, ..self}
+  }
+
+  pub fn with_flag_limit(self, flag_limit: Option<usize>) -> GameConfig {
+    GameConfig {flag_limit, ..self}
+  }
+
+  pub fn with_flag_win(self, flag_win: bool) -> GameConfig {
+    GameConfig {flag_win, ..self}
+  }
+
+  pub fn with_fair_layout(self, fair: bool) -> GameConfig {
+    GameConfig {fair, ..self}
+  }
+
+  pub fn with_gradient(self, gradient: bool) -> GameConfig {
+    GameConfig {gradient, ..self}
+  }
+
+  pub fn with_antimines(self, antimines: usize) -> GameConfig {
+    GameConfig {antimines, ..self}
+  }
+
+  pub fn with_lives(self, lives: usize) -> GameConfig {
+    GameConfig {lives, ..self}
+  }
+
+  /// Generates the board. The rules are applied in the one order which keeps boards the same from version to version.
+  pub fn build(&self) -> Engine {
+    Engine::with_size(self.seed, (self.width, self.height, self.mines), self.transform, self.neighbors)
+      .with_gradient(self.gradient)
+      .with_fair_layout(self.fair)
+      .with_antimines(self.antimines)
+      .with_treasures(self.treasures, self.treasure_reward)
+      .with_flag_limit(self.flag_limit)
+      .with_flag_win(self.flag_win)
+      .with_lives(self.lives)
+      .with_safe_start(self.safe_start)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn engines_give_back_their_config() {
+    let config = GameConfig::new(9).with_neighbors(Neighbors::Diagonal).with_fair_layout(true).with_antimines(5).with_treasures(3, Reward::Life).with_flag_limit(Some(20))
+      .with_size(16, 16, 40).with_safe_start(true);
+    let engine = config.build();
+    assert_eq!(GameConfig::of(&engine), config);
+    assert_eq!(engine.state_hash(), config.build().state_hash());
+  }
+}
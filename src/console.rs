@@ -0,0 +1,136 @@
+//! Drop-down developer console, toggled with the backtick key.
+use crate::{action::Action, engine::Transform};
+
+/// What the console asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  Input(String),
+  Submit,
+}
+
+//Older lines get dropped, so the log doesn't grow forever.
+const MAX_LINES: usize = 100;
+
+pub enum Command {
+  Act(Action),
+  /// A seed number or phrase.
+  Seed(String),
+  /// Start the current board over with its mines moved around.
+  Transform(Transform),
+  Solve,
+  Dump,
+  /// Play four boards at once.
+  Quad,
+  /// Practice common patterns on small boards.
+  Drill,
+  /// Solve as many small puzzles as possible in three minutes.
+  Rush,
+  /// Load a replay or bug report file and play it from the start.
+  Replay(String),
+  /// Play two replays of the same board side by side.
+  Compare(String, String),
+  /// Copy a link to the current position.
+  Link,
+  /// Open a position link, and keep playing from there.
+  Open(String),
+  /// Save the current game as an animated PNG.
+  Export(String),
+  /// Save the settings, stats, daily challenges and drills to one file.
+  ExportProfile(String),
+  /// Merge a file saved by `ExportProfile` in.
+  ImportProfile(String),
+  /// Play the boards of a tournament seed pack in order.
+  PlayPack(String),
+  /// Save the results of the seed pack, with a replay of each board, signed with the player's key.
+  SavePackResults(String),
+  Report,
+  Help,
+}
+
+#[derive(Default)]
+pub struct Console {
+  pub open: bool,
+  pub input: String,
+  pub output: Vec<String>,
+}
+
+impl Console {
+  pub fn print(&mut self, line: impl Into<String>) {
+    self.output.push(line.into());
+    if self.output.len() > MAX_LINES {
+      self.output.remove(0);
+    }
+  }
+}
+
+pub const HELP: &str = "Commands: seed N or PHRASE, transform none/mirror/flip/rotate, quad, drill, rush, replay FILE, compare FILE FILE, link, open LINK, export FILE, profile export/import FILE, pack play/results FILE, report, help";
+
+pub const DEBUG_HELP: &str = "Debug builds also have: reveal X Y, flag X Y, chord X Y, solve, dump";
+
+//Playing moves for the player or showing what's under covered cells is cheating, so like the F12 overlay only developers get these.
+const DEBUG_ONLY: [&str; 5] = ["reveal", "flag", "chord", "solve", "dump"];
+
+fn position<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<(usize, usize), String> {
+  let x: usize = words.next().and_then(|word| word.parse().ok()).ok_or("Expected X")?;
+  let y: usize = words.next().and_then(|word| word.parse().ok()).ok_or("Expected Y")?;
+  Ok((x, y))
+}
+
+pub fn parse(line: &str) -> Result<Command, String> {
+  let mut words = line.split_whitespace();
+  match words.next() {
+    Some(word) if !cfg!(debug_assertions) && DEBUG_ONLY.contains(&word) => Err(format!("{} only works in debug builds", word)),
+    Some("reveal") => position(words).map(|(x, y)| Command::Act(Action::Reveal(x, y))),
+    Some("flag") => position(words).map(|(x, y)| Command::Act(Action::Flag(x, y))),
+    Some("chord") => position(words).map(|(x, y)| Command::Act(Action::Chord(x, y))),
+    Some("seed") => match words.collect::<Vec<_>>().join(" ") {
+      seed if seed.is_empty() => Err(String::from("Expected a number or phrase")),
+      seed => Ok(Command::Seed(seed)),
+    },
+    Some("transform") => match words.next() {
+      Some("none") => Ok(Command::Transform(Transform::None)),
+      Some("mirror") => Ok(Command::Transform(Transform::Mirror)),
+      Some("flip") => Ok(Command::Transform(Transform::Flip)),
+      Some("rotate") => Ok(Command::Transform(Transform::Rotate)),
+      _ => Err(String::from("Expected none, mirror, flip or rotate")),
+    },
+    Some("solve") => Ok(Command::Solve),
+    Some("dump") => Ok(Command::Dump),
+    Some("quad") => Ok(Command::Quad),
+    Some("drill") => Ok(Command::Drill),
+    Some("rush") => Ok(Command::Rush),
+    Some("replay") => match words.collect::<Vec<_>>().join(" ") {
+      path if path.is_empty() => Err(String::from("Expected a file")),
+      path => Ok(Command::Replay(path)),
+    },
+    Some("compare") => match (words.next(), words.next()) {
+      (Some(first), Some(second)) => Ok(Command::Compare(String::from(first), String::from(second))),
+      _ => Err(String::from("Expected two files")),
+    },
+    Some("link") => Ok(Command::Link),
+    Some("open") => match words.next() {
+      Some(link) => Ok(Command::Open(String::from(link))),
+      None => Err(String::from("Expected a position link")),
+    },
+    Some("export") => match words.collect::<Vec<_>>().join(" ") {
+      path if path.is_empty() => Err(String::from("Expected a file")),
+      path => Ok(Command::Export(path)),
+    },
+    Some("profile") => match (words.next(), words.collect::<Vec<_>>().join(" ")) {
+      (_, path) if path.is_empty() => Err(String::from("Expected export or import, and a file")),
+      (Some("export"), path) => Ok(Command::ExportProfile(path)),
+      (Some("import"), path) => Ok(Command::ImportProfile(path)),
+      _ => Err(String::from("Expected export or import")),
+    },
+    Some("pack") => match (words.next(), words.collect::<Vec<_>>().join(" ")) {
+      (_, path) if path.is_empty() => Err(String::from("Expected play or results, and a file")),
+      (Some("play"), path) => Ok(Command::PlayPack(path)),
+      (Some("results"), path) => Ok(Command::SavePackResults(path)),
+      _ => Err(String::from("Expected play or results")),
+    },
+    Some("report") => Ok(Command::Report),
+    Some("help") => Ok(Command::Help),
+    Some(word) => Err(format!("Unknown command {}", word)),
+    None => Err(String::from(HELP)),
+  }
+}
@@ -0,0 +1,130 @@
+//! Daily challenges: everyone gets the same board each day, seeded from the date.
+//!
+//! Days are counted in UTC, from 1970-01-01. Won days are stored as `daily.toml` in the config directory.
+use crate::settings;
+use minesweeper::schema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::{fs, io, time};
+
+/// What the calendar of daily challenges asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  /// Go back or forward this many months.
+  Month(i32),
+  Play(i64),
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Daily {
+  /// Days whose challenge was won on the day.
+  pub completed: BTreeSet<i64>,
+}
+
+pub fn today() -> i64 {
+  let seconds = time::SystemTime::now().duration_since(time::UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or_default();
+  (seconds / 86400) as i64
+}
+
+/// The board for a day. The seed reads as the date, like 20261015.
+pub fn seed(day: i64) -> u64 {
+  let (year, month, day) = date(day);
+  (year * 10000 + month as i64 * 100 + day as i64) as u64
+}
+
+/// Year, month and day of the month.
+pub fn date(day: i64) -> (i64, u32, u32) {
+  //Howard Hinnant's civil_from_days. Years start in March, so leap days come last.
+  let z = day + 719468;
+  let era = z.div_euclid(146097);
+  let day_of_era = z.rem_euclid(146097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let march_month = (5 * day_of_year + 2) / 153;
+  let day_of_month = (day_of_year - (153 * march_month + 2) / 5 + 1) as u32;
+  let month = if march_month < 10 { march_month + 3 } else { march_month - 9 } as u32;
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  (year, month, day_of_month)
+}
+
+/// The opposite of [`date`].
+pub fn day(year: i64, month: u32, day_of_month: u32) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year.rem_euclid(400);
+  let march_month = if month > 2 { month - 3 } else { month + 9 } as i64;
+  let day_of_year = (153 * march_month + 2) / 5 + day_of_month as i64 - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  era * 146097 + day_of_era - 719468
+}
+
+/// 0 for Monday up to 6 for Sunday.
+pub fn weekday(day: i64) -> i64 {
+  //1970-01-01 was a Thursday.
+  (day + 3).rem_euclid(7)
+}
+
+pub fn month_name(month: u32) -> &'static str {
+  ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"][month as usize - 1]
+}
+
+impl Daily {
+  /// Missing or unreadable files start with nothing completed.
+  pub fn load() -> Daily {
+    settings::config_path("daily.toml")
+      .and_then(|path| fs::read_to_string(path).ok())
+      .and_then(|text| schema::DAILY.from_toml(&text).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let path = settings::config_path("daily.toml").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    let text = schema::DAILY.to_toml(self).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    fs::write(path, text)
+  }
+
+  /// Days in a row up to `today`. Today not being done yet doesn't break the streak.
+  pub fn streak(&self, today: i64) -> usize {
+    let last = if self.completed.contains(&today) { today } else { today - 1 };
+    (0..).take_while(|&back| self.completed.contains(&(last - back))).count()
+  }
+
+  pub fn best_streak(&self) -> usize {
+    let mut best = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for &day in &self.completed {
+      run = if previous == Some(day - 1) { run + 1 } else { 1 };
+      best = usize::max(best, run);
+      previous = Some(day);
+    }
+    best
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn dates() {
+    assert_eq!(date(0), (1970, 1, 1));
+    assert_eq!(date(day(2024, 2, 29)), (2024, 2, 29));
+    assert_eq!(day(2000, 3, 1) - day(2000, 2, 28), 2);
+    assert_eq!(weekday(day(2026, 10, 15)), 3);
+    assert_eq!(seed(day(2026, 10, 15)), 20261015);
+  }
+
+  #[test]
+  fn streaks() {
+    let daily = Daily {completed: [10, 11, 12, 20, 21].into()};
+    assert_eq!(daily.streak(21), 2);
+    assert_eq!(daily.streak(22), 2);
+    assert_eq!(daily.streak(23), 0);
+    assert_eq!(daily.best_streak(), 3);
+  }
+}
@@ -0,0 +1,214 @@
+//! Practice drills: common patterns on their own, as small boards.
+//!
+//! Every covered cell the numbers decide has to be left clicked if it is safe, or right clicked if it is a mine.
+//! Accuracy and speed are kept per pattern as `drills.toml` in the config directory.
+//! Patterns go up a level each time they are solved and back to 0 when they aren't, and each level lower comes up twice as often.
+use crate::settings;
+use minesweeper::schema;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::{fs, io, time};
+
+/// What the drill screen asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  /// Says a cell of the drill is a mine, or safe.
+  Answer(usize, usize, bool),
+  /// Go on to another pattern, or start drilling.
+  Next,
+  Close,
+}
+
+pub const TOP_LEVEL: u32 = 4;
+
+/// Patterns drawn row by row. A digit is a revealed number, `?` is a safe cell and `*` a mine to find,
+/// `-` and `x` are a safe cell and a mine the numbers don't decide, and `#` is off the board.
+pub const PATTERNS: [(&str, &[&str]); 6] = [
+  ("1-2-1", &["?*?*?", "#121#"]),
+  ("1-2-2-1", &["??**??", "#1221#"]),
+  ("1-1 on an edge", &["x-?", "11#"]),
+  ("1-2 on an edge", &["x-*", "12#"]),
+  ("2 in a corner", &["**", "2#"]),
+  ("3 on an edge", &["***", "#3#"]),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Spot {
+  Wall,
+  Number(u8),
+  Covered {mine: bool, asked: bool},
+}
+
+/// Reads a pattern's rows, as `spots[y][x]`.
+pub fn spots(rows: &[&str]) -> Vec<Vec<Spot>> {
+  rows.iter().map(|row| row.chars().map(|letter| match letter {
+    '?' => Spot::Covered {mine: false, asked: true},
+    '*' => Spot::Covered {mine: true, asked: true},
+    '-' => Spot::Covered {mine: false, asked: false},
+    'x' => Spot::Covered {mine: true, asked: false},
+    letter => letter.to_digit(10).map_or(Spot::Wall, |number| Spot::Number(number as u8)),
+  }).collect()).collect()
+}
+
+pub struct Drill {
+  /// Index into [`PATTERNS`].
+  pub pattern: usize,
+  pub spots: Vec<Vec<Spot>>,
+  /// What was said about each cell clicked, `true` for a mine.
+  pub answers: BTreeMap<(usize, usize), bool>,
+  pub started: time::Instant,
+  /// Whether it was solved, once it is over.
+  pub solved: Option<bool>,
+  /// How long it took, once it is over.
+  pub took: time::Duration,
+}
+
+impl Drill {
+  pub fn new(pattern: usize) -> Drill {
+    Drill {
+      pattern,
+      spots: spots(PATTERNS[pattern].1),
+      answers: BTreeMap::new(),
+      started: time::Instant::now(),
+      solved: None,
+      took: time::Duration::ZERO,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    PATTERNS[self.pattern].0
+  }
+
+  /// Says a covered cell is a mine or safe. Saying anything about a cell the numbers don't decide is wrong too.
+  pub fn answer(&mut self, x: usize, y: usize, mine: bool) {
+    let Spot::Covered {mine: actual, asked} = self.spots[y][x] else { return };
+    if self.solved.is_some() || self.answers.contains_key(&(x, y)) {
+      return;
+    }
+    self.answers.insert((x, y), mine);
+    if !asked || actual != mine {
+      self.solved = Some(false);
+    } else if self.spots.iter().flatten().filter(|spot| matches!(spot, Spot::Covered {asked: true, ..})).count() == self.answers.len() {
+      self.solved = Some(true);
+    }
+    if self.solved.is_some() {
+      self.took = self.started.elapsed();
+    }
+  }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Record {
+  pub attempts: u32,
+  pub solved: u32,
+  /// Milliseconds taken by the solved attempts, all together.
+  pub solved_ms: u64,
+  pub level: u32,
+}
+
+impl Record {
+  pub fn describe(&self) -> String {
+    if self.solved == 0 {
+      return format!("{}/{} solved", self.solved, self.attempts);
+    }
+    format!("{}/{} solved, {:.1}s on average, level {}", self.solved, self.attempts, self.solved_ms as f32 / self.solved as f32 / 1000.0, self.level)
+  }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Drills {
+  /// By pattern name.
+  pub records: BTreeMap<String, Record>,
+  /// The most puzzles solved in one puzzle rush.
+  pub best_rush: usize,
+}
+
+impl Drills {
+  /// Missing or unreadable files start with nothing practiced.
+  pub fn load() -> Drills {
+    settings::config_path("drills.toml")
+      .and_then(|path| fs::read_to_string(path).ok())
+      .and_then(|text| schema::DRILLS.from_toml(&text).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let path = settings::config_path("drills.toml").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    let text = schema::DRILLS.to_toml(self).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    fs::write(path, text)
+  }
+
+  pub fn record(&self, pattern: usize) -> Record {
+    self.records.get(PATTERNS[pattern].0).cloned().unwrap_or_default()
+  }
+
+  /// Keeps the result of a finished drill.
+  pub fn finish(&mut self, drill: &Drill) {
+    let record = self.records.entry(drill.name().to_string()).or_default();
+    record.attempts += 1;
+    if drill.solved == Some(true) {
+      record.solved += 1;
+      record.solved_ms += drill.took.as_millis() as u64;
+      record.level = (record.level + 1).min(TOP_LEVEL);
+    } else {
+      record.level = 0;
+    }
+  }
+
+  /// Picks the next pattern, favoring the ones at lower levels. The previous one isn't picked twice in a row.
+  pub fn next(&self, previous: Option<usize>) -> usize {
+    let weights: Vec<u32> = (0..PATTERNS.len())
+      .map(|pattern| if Some(pattern) == previous { 0 } else { 1 << (TOP_LEVEL - self.record(pattern).level.min(TOP_LEVEL)) })
+      .collect();
+    let mut pick = rand::thread_rng().gen_range(0..weights.iter().sum::<u32>());
+    for (pattern, weight) in weights.into_iter().enumerate() {
+      if pick < weight {
+        return pattern;
+      }
+      pick -= weight;
+    }
+    0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn patterns_have_one_answer() {
+    for (name, rows) in PATTERNS {
+      let spots = spots(rows);
+      let covered: Vec<(usize, usize)> = (0..spots.len()).flat_map(|y| (0..spots[y].len()).map(move |x| (x, y)))
+        .filter(|&(x, y)| matches!(spots[y][x], Spot::Covered {..})).collect();
+      //Every way of placing mines under the covered cells which fits the numbers.
+      let fits = |mines: &dyn Fn(usize, usize) -> bool| (0..spots.len()).all(|y| (0..spots[y].len()).all(|x| match spots[y][x] {
+        Spot::Number(number) => {
+          let around = (y.saturating_sub(1)..(y + 2).min(spots.len()))
+            .flat_map(|near_y| (x.saturating_sub(1)..(x + 2).min(spots[near_y].len())).map(move |near_x| (near_x, near_y)))
+            .filter(|&(near_x, near_y)| matches!(spots[near_y][near_x], Spot::Covered {..}) && mines(near_x, near_y)).count();
+          around == number as usize
+        },
+        _ => true,
+      }));
+      assert!(fits(&|x, y| matches!(spots[y][x], Spot::Covered {mine: true, ..})), "{} has wrong numbers", name);
+      for layout in 0..1u32 << covered.len() {
+        let mine = |x, y| covered.iter().position(|&cell| cell == (x, y)).is_some_and(|index| layout & 1 << index != 0);
+        if !fits(&mine) {
+          continue;
+        }
+        for &(x, y) in &covered {
+          if let Spot::Covered {mine: actual, asked: true} = spots[y][x] {
+            assert_eq!(mine(x, y), actual, "{} doesn't decide {} {}", name, x, y);
+          }
+        }
+      }
+    }
+  }
+}
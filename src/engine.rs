@@ -0,0 +1,943 @@
+//! The rules of the game, without any GUI.
+//!
+//! An [`Engine`] is fully determined by its seed and the actions played on it, so games can be replayed exactly.
+use crate::action::Action;
+use crate::config::GameConfig;
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+
+//Offsets of the eight neighbors. The order decides which cells get revealed first, so it stays the same for old replays.
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (0, -1), (1, 1), (1, 0), (0, 1), (-1, 1), (1, -1)];
+
+/// Mines moved at most by [`Engine::with_fair_layout`].
+const FAIR_LAYOUT_ATTEMPTS: usize = 20;
+/// How many times more likely a cell on the right edge of a gradient board is a mine than one on the left edge.
+const GRADIENT_RATIO: f64 = 4.0;
+/// Widest and tallest a board can be.
+pub const MAX_SIDE: usize = 255;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CellValue {
+  Mined,
+  /// Loses the game like a mine, but takes one off the numbers around it instead of adding one.
+  AntiMine,
+  /// A safe cell, and the number of mines around it less the antimines.
+  Number(i8),
+  /// A safe cell with a treasure, and its number.
+  Treasure(i8),
+}
+
+impl CellValue {
+  /// The number of a safe cell.
+  pub fn number(self) -> Option<i8> {
+    match self {
+      CellValue::Mined | CellValue::AntiMine => None,
+      CellValue::Number(number) | CellValue::Treasure(number) => Some(number),
+    }
+  }
+
+  /// Either kind of mine.
+  pub fn is_mine(self) -> bool {
+    matches!(self, CellValue::Mined | CellValue::AntiMine)
+  }
+
+  /// What the cell adds to the numbers around it.
+  pub fn charge(self) -> i8 {
+    match self {
+      CellValue::Mined => 1,
+      CellValue::AntiMine => -1,
+      _ => 0,
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CellStatus {
+  Covered,
+  Revealed,
+  Flagged,
+  /// Flagged as an antimine.
+  AntiFlagged,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Cell {
+  pub status: CellStatus,
+  pub value: CellValue,
+}
+
+impl Cell {
+  /// Flagged, or a mine revealed without losing, which only happens when a life was used up.
+  pub fn is_known_mine(&self) -> bool {
+    self.known_charge() != 0
+  }
+
+  /// What the player thinks the cell adds to the numbers around it, going by its flag or the mine showing.
+  pub fn known_charge(&self) -> i8 {
+    match self.status {
+      CellStatus::Flagged => 1,
+      CellStatus::AntiFlagged => -1,
+      CellStatus::Revealed => self.value.charge(),
+      CellStatus::Covered => 0,
+    }
+  }
+}
+
+/// The cells of a board, indexed by `[x][y]`, and how many mines are among them.
+#[derive(Clone)]
+pub struct Board {
+  pub width: usize,
+  pub height: usize,
+  /// Mines on the board, antimines included. Players know it, so solvers can count on it.
+  pub mines: usize,
+  columns: Vec<Vec<Cell>>,
+}
+
+impl Board {
+  /// A `width`×`height` board of covered zeros, to place `mines` on.
+  pub fn new(width: usize, height: usize, mines: usize) -> Board {
+    Board {width, height, mines, columns: vec![vec![Cell {status: CellStatus::Covered, value: CellValue::Number(0)}; height]; width]}
+  }
+
+  /// Every position touching a cell, corners included, that is on the board.
+  pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    let (width, height) = (self.width, self.height);
+    NEIGHBOR_OFFSETS.into_iter().filter_map(move |(dx, dy)| {
+      let new_x = x.checked_add_signed(dx)?;
+      let new_y = y.checked_add_signed(dy)?;
+      (new_x < width && new_y < height).then_some((new_x, new_y))
+    })
+  }
+
+  /// The cells touching a cell, corners included.
+  pub fn neighbor_cells(&self, x: usize, y: usize) -> impl Iterator<Item = &Cell> {
+    self.neighbors(x, y).map(|(x, y)| &self.columns[x][y])
+  }
+
+  /// Every position on the board, column by column.
+  pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+    let height = self.height;
+    (0..self.width).flat_map(move |x| (0..height).map(move |y| (x, y)))
+  }
+
+  /// `value` for every cell, indexed the same way.
+  pub fn grid<T: Clone>(&self, value: T) -> Vec<Vec<T>> {
+    vec![vec![value; self.height]; self.width]
+  }
+
+  pub fn contains(&self, x: usize, y: usize) -> bool {
+    x < self.width && y < self.height
+  }
+}
+
+impl Deref for Board {
+  type Target = [Vec<Cell>];
+
+  fn deref(&self) -> &Self::Target {
+    &self.columns
+  }
+}
+
+impl DerefMut for Board {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.columns
+  }
+}
+
+impl<'a> IntoIterator for &'a Board {
+  type Item = &'a Vec<Cell>;
+  type IntoIter = std::slice::Iter<'a, Vec<Cell>>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.columns.iter()
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameStatus {
+  Playing,
+  Lost,
+  Won,
+}
+
+/// A way of moving the mines of a seed's board around, for practicing a board without playing the exact same layout.
+/// The numbers come out the same, just in different places.
+/// Quarter turns aren't possible, since they would need the board's width and height swapped.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Transform {
+  #[default]
+  None,
+  /// Left to right.
+  Mirror,
+  /// Top to bottom.
+  Flip,
+  /// Half a turn.
+  Rotate,
+}
+
+impl Transform {
+  pub fn apply(self, board: &Board, x: usize, y: usize) -> (usize, usize) {
+    match self {
+      Transform::None => (x, y),
+      Transform::Mirror => (board.width - 1 - x, y),
+      Transform::Flip => (x, board.height - 1 - y),
+      Transform::Rotate => (board.width - 1 - x, board.height - 1 - y),
+    }
+  }
+}
+
+/// Which cells around a cell count as its neighbors, for its number, for openings spreading from it and for chording it.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Neighbors {
+  /// All eight, the standard rules.
+  #[default]
+  All,
+  /// Only the four corners, for Diagonal Minesweeper.
+  Diagonal,
+  /// Only the four sides.
+  Orthogonal,
+}
+
+impl Neighbors {
+  pub fn name(self) -> &'static str {
+    match self {
+      Neighbors::All => "All neighbors",
+      Neighbors::Diagonal => "Diagonal",
+      Neighbors::Orthogonal => "Orthogonal",
+    }
+  }
+
+  /// Every neighbor of a cell on `board` under these rules.
+  pub fn of(self, board: &Board, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    board.neighbors(x, y).filter(move |&(new_x, new_y)| {
+      let diagonal = new_x != x && new_y != y;
+      matches!((self, diagonal), (Neighbors::All, _) | (Neighbors::Diagonal, true) | (Neighbors::Orthogonal, false))
+    })
+  }
+}
+
+/// What revealing a treasure gives.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reward {
+  /// Time off the clock. The engine has no clock, so this is up to the game.
+  #[default]
+  Time,
+  /// A life, which is used up instead of losing when a mine is revealed.
+  Life,
+}
+
+#[derive(Clone)]
+pub struct Engine {
+  pub board: Board,
+  pub status: GameStatus,
+  pub revealed_count: usize,
+  pub flag_count: usize,
+  pub seed: u64,
+  pub transform: Transform,
+  pub neighbors: Neighbors,
+  /// Treasures hidden on the board.
+  pub treasures: usize,
+  pub treasure_reward: Reward,
+  pub treasures_found: usize,
+  pub lives: usize,
+  /// Lives the game started with, before any were used up or found.
+  pub starting_lives: usize,
+  /// Fewer flags than mines, for the limited flags challenge.
+  pub flag_limit: Option<usize>,
+  /// Also win by flagging every mine, with no flags anywhere else.
+  pub flag_win: bool,
+  /// Mines were moved around to get rid of coin flips no number can settle.
+  pub fair: bool,
+  /// Mines which are antimines instead.
+  pub antimines: usize,
+  /// Mines get denser from the left of the board to the right.
+  pub gradient: bool,
+  /// The first cell revealed is never a mine, and opens up when there is room, since mines there are moved away first.
+  pub safe_start: bool,
+  /// Every action played since the board was generated.
+  pub moves: Vec<Action>,
+  //Counted up as cells are revealed, so the pace can be shown live without going over the whole board.
+  cleared_three_bv: usize,
+  //Cells the last action changed, so drawing can skip the rest.
+  changed: Vec<(usize, usize)>,
+}
+
+impl Engine {
+  /// Generate a board of `width`×`height` with `mines` for `seed`, with its mines moved around by `transform` and numbers counting `neighbors`.
+  /// Sides are kept between 1 and [`MAX_SIDE`], and at least one cell is left safe.
+  pub fn with_size(seed: u64, (width, height, mines): (usize, usize, usize), transform: Transform, neighbors: Neighbors) -> Engine {
+    let (width, height) = (width.clamp(1, MAX_SIDE), height.clamp(1, MAX_SIDE));
+    let mut engine = Engine {
+      board: Board::new(width, height, mines.min(width * height - 1)),
+      status: GameStatus::Playing,
+      revealed_count: 0,
+      flag_count: 0,
+      seed,
+      transform,
+      neighbors,
+      treasures: 0,
+      treasure_reward: Reward::Time,
+      treasures_found: 0,
+      lives: 0,
+      starting_lives: 0,
+      flag_limit: None,
+      flag_win: false,
+      fair: false,
+      antimines: 0,
+      gradient: false,
+      safe_start: false,
+      moves: Vec::new(),
+      cleared_three_bv: 0,
+      changed: Vec::new(),
+    };
+    engine.add_mines();
+    engine.add_numbers();
+    engine
+  }
+
+  /// A `width`×`height` board with its mines exactly where given, like one loaded from a file, instead of placed by the seed.
+  /// The seed is 0, so replays and links of it won't open the same board.
+  pub fn from_mines(width: usize, height: usize, mines: &[(usize, usize)]) -> Result<Engine, String> {
+    if !(1..=MAX_SIDE).contains(&width) || !(1..=MAX_SIDE).contains(&height) || mines.len() >= width * height {
+      return Err(format!("Can't have {} mines on {}x{}", mines.len(), width, height));
+    }
+    let mut engine = GameConfig::new(0).with_size(width, height, mines.len()).build();
+    for cell in engine.board.iter_mut().flatten() {
+      cell.value = CellValue::Number(0);
+    }
+    for &(x, y) in mines {
+      let cell = engine.board.get_mut(x).and_then(|column| column.get_mut(y)).ok_or_else(|| format!("The mine at {} {} is off the board", x, y))?;
+      cell.value = CellValue::Mined;
+    }
+    if engine.board.iter().flatten().filter(|cell| cell.value == CellValue::Mined).count() != mines.len() {
+      return Err(String::from("Some mines are in the same place"));
+    }
+    engine.add_numbers();
+    Ok(engine)
+  }
+
+  /// Places the mines again, few on the left and more and more towards the right, for an easy start and a hard finish.
+  /// They are placed by the seed too. Call this before the fair layout and hiding treasures, since it replaces the mines.
+  pub fn with_gradient(mut self, gradient: bool) -> Engine {
+    use rand::{seq::SliceRandom, SeedableRng};
+    if !gradient {
+      return self;
+    }
+    let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed ^ 0x6772616469656e74);
+    let positions = self.reading_order();
+    let width = self.board.width;
+    let weight = |&(x, _): &(usize, usize)| 1.0 + (GRADIENT_RATIO - 1.0) * x as f64 / (width - 1).max(1) as f64;
+    let Ok(mines) = positions.choose_multiple_weighted(&mut rng, self.board.mines, weight) else { return self };
+    let mines: Vec<(usize, usize)> = mines.copied().collect();
+    for cell in self.board.iter_mut().flatten() {
+      cell.value = CellValue::Number(0);
+    }
+    //Weighting before transforming keeps mirrored boards mirrored, so the gradient turns with them.
+    for (x, y) in mines {
+      let (x, y) = self.transform.apply(&self.board, x, y);
+      self.board[x][y].value = CellValue::Mined;
+    }
+    self.add_numbers();
+    self.gradient = true;
+    self
+  }
+
+  /// Moves mines out of coin flips no number can settle, found by [`Engine::fifty_fifties`]. They are moved by the seed too.
+  /// Call this before hiding treasures, since it works the numbers out again.
+  pub fn with_fair_layout(mut self, fair: bool) -> Engine {
+    use rand::{seq::SliceRandom, SeedableRng};
+    if !fair {
+      return self;
+    }
+    let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed ^ 0x666169726e657373);
+    //Moving a mine can make a new coin flip where it lands, so give up eventually rather than going on forever.
+    for _ in 0..FAIR_LAYOUT_ATTEMPTS {
+      //Picking in untransformed order keeps mirrored boards mirrored.
+      let Some((first, second)) = self.fifty_fifties().into_iter().min_by_key(|&((x, y), _)| {
+        let (x, y) = self.transform.apply(&self.board, x, y);
+        (y, x)
+      }) else { break };
+      let mine = if self.board[first.0][first.1].value == CellValue::Mined { first } else { second };
+      let mut spots = self.reading_order();
+      spots.shuffle(&mut rng);
+      let Some((x, y)) = spots.into_iter().map(|(x, y)| self.transform.apply(&self.board, x, y))
+        .find(|&spot| spot != first && spot != second && self.board[spot.0][spot.1].value != CellValue::Mined) else { break };
+      self.board[mine.0][mine.1].value = CellValue::Number(0);
+      self.board[x][y].value = CellValue::Mined;
+      self.add_numbers();
+    }
+    self.fair = true;
+    self
+  }
+
+  /// Pairs of neighboring cells with one mine between them which no number can ever tell apart,
+  /// because every other cell next to just one of them is a mine too. One of the two has to be guessed.
+  pub fn fifty_fifties(&self) -> Vec<((usize, usize), (usize, usize))> {
+    let mined = |(x, y): (usize, usize)| self.board[x][y].value == CellValue::Mined;
+    let mut pairs = Vec::new();
+    for x in 0..self.board.width {
+      for y in 0..self.board.height {
+        let first = (x, y);
+        for second in self.board.neighbors(x, y).filter(|&second| second > first && mined(first) != mined(second)) {
+          let near_first: Vec<(usize, usize)> = self.neighbors.of(&self.board, first.0, first.1).collect();
+          let near_second: Vec<(usize, usize)> = self.neighbors.of(&self.board, second.0, second.1).collect();
+          let telling = near_first.iter().filter(|cell| !near_second.contains(cell))
+            .chain(near_second.iter().filter(|cell| !near_first.contains(cell)))
+            .filter(|&&cell| cell != first && cell != second);
+          if telling.copied().all(mined) {
+            pairs.push((first, second));
+          }
+        }
+      }
+    }
+    pairs
+  }
+
+  /// Turn `count` of the mines into antimines, picked by the seed. Call this before hiding treasures, since it works the numbers out again.
+  pub fn with_antimines(mut self, count: usize) -> Engine {
+    use rand::{seq::SliceRandom, SeedableRng};
+    if count == 0 {
+      return self;
+    }
+    let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed ^ 0x616e74696d696e65);
+    //Picking in untransformed order keeps mirrored boards mirrored.
+    let mut mines: Vec<(usize, usize)> = self.reading_order().into_iter()
+      .map(|(x, y)| self.transform.apply(&self.board, x, y))
+      .filter(|&(x, y)| self.board[x][y].value == CellValue::Mined)
+      .collect();
+    mines.shuffle(&mut rng);
+    for &(x, y) in mines.iter().take(count) {
+      self.board[x][y].value = CellValue::AntiMine;
+    }
+    self.antimines = count.min(mines.len());
+    self.add_numbers();
+    self
+  }
+
+  /// Hide `count` treasures among the safe cells of a freshly generated board. They are placed by the seed too.
+  pub fn with_treasures(mut self, count: usize, reward: Reward) -> Engine {
+    use rand::{seq::SliceRandom, SeedableRng};
+    //A different stream than the mines, so boards without treasures stay the same.
+    let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed ^ 0x7472656173757265);
+    let mut positions = Vec::new();
+    for (x, y) in self.reading_order() {
+      //Picking before transforming keeps the treasures in the same place relative to the mines.
+      let (x, y) = self.transform.apply(&self.board, x, y);
+      if let CellValue::Number(number) = self.board[x][y].value {
+        positions.push((x, y, number));
+      }
+    }
+    positions.shuffle(&mut rng);
+    for &(x, y, number) in positions.iter().take(count) {
+      self.board[x][y].value = CellValue::Treasure(number);
+    }
+    self.treasures = count.min(positions.len());
+    self.treasure_reward = reward;
+    self
+  }
+
+  /// Allow only `limit` flags at once, instead of one for every mine.
+  pub fn with_flag_limit(mut self, limit: Option<usize>) -> Engine {
+    self.flag_limit = limit;
+    self
+  }
+
+  /// Also win once every mine is flagged and nothing else is, like some other versions of the game.
+  pub fn with_flag_win(mut self, flag_win: bool) -> Engine {
+    self.flag_win = flag_win;
+    self
+  }
+
+  /// Start with `lives`, each taking the hit of one mine instead of losing.
+  pub fn with_lives(mut self, lives: usize) -> Engine {
+    self.lives = lives;
+    self.starting_lives = lives;
+    self
+  }
+
+  /// Move mines away from the first cell revealed, and the cells around it when there is room, so the game starts with an opening.
+  pub fn with_safe_start(mut self, safe_start: bool) -> Engine {
+    self.safe_start = safe_start;
+    self
+  }
+
+  /// How many flags can be placed at once.
+  pub fn flag_budget(&self) -> usize {
+    self.flag_limit.map_or(self.board.mines, |limit| limit.min(self.board.mines))
+  }
+
+  /// Every position, row by row, the order the seed places things in so boards stay the same from version to version.
+  fn reading_order(&self) -> Vec<(usize, usize)> {
+    let width = self.board.width;
+    (0..self.board.height).flat_map(|y| (0..width).map(move |x| (x, y))).collect()
+  }
+
+  /// Nothing has been clicked on the current board yet.
+  pub fn is_fresh(&self) -> bool {
+    self.status == GameStatus::Playing && self.revealed_count == 0 && self.flag_count == 0
+  }
+
+  pub fn is_over(&self) -> bool {
+    self.status == GameStatus::Won || self.status == GameStatus::Lost
+  }
+
+  /// The flags around a revealed number when there are more than it needs, so they can't all be right.
+  /// With antimines around, flags can't be told to be too many, so there are none.
+  pub fn excess_flags(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+    let cell = self.board[x][y];
+    if self.antimines > 0 {
+      return Vec::new();
+    }
+    let Some(number) = cell.value.number().filter(|_| cell.status == CellStatus::Revealed) else { return Vec::new() };
+    let flags: Vec<(usize, usize)> = self.neighbors.of(&self.board, x, y).filter(|&(new_x, new_y)| self.board[new_x][new_y].status == CellStatus::Flagged).collect();
+    if flags.len() > number as usize { flags } else { Vec::new() }
+  }
+
+  /// A revealed 0 opens the cells around it, unless it is a 0 because mines and antimines around it cancel out.
+  pub fn is_blank(&self, x: usize, y: usize) -> bool {
+    self.board[x][y].value.number() == Some(0) && (self.antimines == 0 || self.neighbors.of(&self.board, x, y).all(|(x, y)| !self.board[x][y].value.is_mine()))
+  }
+
+  /// End the game as lost for a reason outside the board, like running out of time.
+  /// It isn't a move, so it isn't logged.
+  pub fn lose(&mut self) {
+    if !self.is_over() {
+      self.status = GameStatus::Lost;
+      self.changed = self.board.positions().collect();
+    }
+  }
+
+  /// Do something to the board, keeping it in the move log.
+  /// Positions off the board, and anything done after the game is over, are ignored.
+  pub fn act(&mut self, action: Action) {
+    self.changed.clear();
+    let (x, y) = action.position();
+    if !self.board.contains(x, y) || self.is_over() {
+      return;
+    }
+    //Only the first cell actually revealed is cleared, not a flagged one clicked before it.
+    let cleared = self.safe_start && self.revealed_count == 0 && matches!(action, Action::Reveal(..)) && self.board[x][y].status == CellStatus::Covered;
+    if cleared {
+      self.clear_start(x, y);
+    }
+    self.moves.push(action);
+    match action {
+      Action::Reveal(x, y) => self.reveal_multiple(x, y),
+      Action::Flag(x, y) => self.toggle_flag(x, y),
+      Action::Chord(x, y) => self.reveal_special(x, y),
+    }
+    //Moving mines changes the numbers around them, and the end of the game shows the mines left.
+    if cleared || self.is_over() {
+      self.changed = self.board.positions().collect();
+    }
+  }
+
+  /// Marks as changed every cell which differs from `before`, or the whole board when the game's status does,
+  /// for jumping to a position without playing the actions between.
+  pub(crate) fn changed_since(&mut self, before: &Engine) {
+    self.changed = if self.status != before.status || self.board.width != before.board.width || self.board.height != before.board.height {
+      self.board.positions().collect()
+    } else {
+      self.board.positions().filter(|&(x, y)| self.board[x][y] != before.board[x][y]).collect()
+    };
+  }
+
+  /// Cells whose status the last action changed, so only they need drawing again. Empty when it changed nothing.
+  /// An action which ended the game, or moved mines for a safe start, changes the whole board.
+  pub fn changed(&self) -> &[(usize, usize)] {
+    &self.changed
+  }
+
+  fn toggle_flag(&mut self, x: usize, y: usize) {
+    match self.board[x][y].status {
+      CellStatus::Covered => {
+        if self.flag_budget() == self.flag_count {
+          //Too many flags! Don't add an extra flag. (Else MNE_COUNT - self.flag_count < 0, which will cause an exception because they are unsigned.)
+          return;
+        }
+        self.board[x][y].status = CellStatus::Flagged;
+        self.flag_count += 1;
+        self.changed.push((x, y));
+        if self.flag_win && self.all_mines_flagged() {
+          self.status = GameStatus::Won;
+        }
+      },
+      //A second flag marks an antimine, on boards which have them.
+      CellStatus::Flagged if self.antimines > 0 => {
+        self.board[x][y].status = CellStatus::AntiFlagged;
+        self.changed.push((x, y));
+      },
+      CellStatus::Flagged | CellStatus::AntiFlagged => {
+        self.board[x][y].status = CellStatus::Covered;
+        self.flag_count -= 1;
+        self.changed.push((x, y));
+      },
+      CellStatus::Revealed => (), //If it's already revealed, it can't be flagged.
+    };
+  }
+
+  /// Every mine is flagged as the right kind, or revealed and survived, and every flag is on a mine.
+  fn all_mines_flagged(&self) -> bool {
+    self.board.iter().flatten().all(|cell| cell.value.charge() == cell.known_charge())
+  }
+
+  /// Moves the mines off `(x, y)` and its neighbors, or just off `(x, y)` when the rest of the board can't take them.
+  /// They are moved by the seed, so a replay of the game moves them the same way.
+  fn clear_start(&mut self, x: usize, y: usize) {
+    use rand::{seq::SliceRandom, SeedableRng};
+    let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed ^ 0x7361666573746172);
+    let mut start: Vec<(usize, usize)> = std::iter::once((x, y)).chain(self.neighbors.of(&self.board, x, y)).collect();
+    let spots = |start: &[(usize, usize)]| -> Vec<(usize, usize)> {
+      self.reading_order().into_iter().map(|(x, y)| self.transform.apply(&self.board, x, y))
+        .filter(|&(x, y)| !start.contains(&(x, y)) && !self.board[x][y].value.is_mine())
+        .collect()
+    };
+    let mut free = spots(&start);
+    if free.len() < start.iter().filter(|&&(x, y)| self.board[x][y].value.is_mine()).count() {
+      start.truncate(1);
+      free = spots(&start);
+    }
+    free.shuffle(&mut rng);
+    for (x, y) in start {
+      if self.board[x][y].value.is_mine() {
+        let Some((new_x, new_y)) = free.pop() else { break };
+        //Swapped, so a treasure where the mine lands moves too.
+        let mine = self.board[x][y].value;
+        self.board[x][y].value = self.board[new_x][new_y].value;
+        self.board[new_x][new_y].value = mine;
+      }
+    }
+    self.add_numbers();
+  }
+
+  /// The board as text, with what the player sees on the left and what is underneath on the right.
+  pub fn dump(&self) -> Vec<String> {
+    (0..self.board.height).map(|y| {
+      let visible: String = (0..self.board.width).map(|x| match self.board[x][y] {
+        Cell {status: CellStatus::Covered, ..} => '#',
+        Cell {status: CellStatus::Flagged, ..} => 'F',
+        Cell {status: CellStatus::AntiFlagged, ..} => 'A',
+        Cell {value, ..} => dump_value(value),
+      }).collect();
+      let actual: String = (0..self.board.width).map(|x| dump_value(self.board[x][y].value)).collect();
+      format!("{} {}", visible, actual)
+    }).collect()
+  }
+
+  /// The board's 3BV: the fewest clicks which clear it. Every opening counts once, plus every number not touching an opening.
+  pub fn three_bv(&self) -> usize {
+    self.count_three_bv()
+  }
+
+  /// How much of the 3BV has been cleared so far.
+  pub fn solved_three_bv(&self) -> usize {
+    self.cleared_three_bv
+  }
+
+  fn count_three_bv(&self) -> usize {
+    let mut counted = self.board.grid(false);
+    let mut count = 0;
+    //Openings. Revealing one zero reveals all of its opening, so checking the first one is enough.
+    for (x, column) in self.board.iter().enumerate() {
+      for y in 0..column.len() {
+        if counted[x][y] || !self.is_blank(x, y) {
+          continue;
+        }
+        count += 1;
+        let mut opening = vec![(x, y)];
+        counted[x][y] = true;
+        while let Some((x, y)) = opening.pop() {
+          for (new_x, new_y) in self.neighbors.of(&self.board, x, y) {
+            if !counted[new_x][new_y] {
+              counted[new_x][new_y] = true;
+              if self.is_blank(new_x, new_y) {
+                opening.push((new_x, new_y));
+              }
+            }
+          }
+        }
+      }
+    }
+    //Numbers which no opening reveals need a click each.
+    for (column, counted_column) in self.board.iter().zip(counted) {
+      for (cell, counted) in column.iter().zip(counted_column) {
+        if !counted && !cell.value.is_mine() {
+          count += 1;
+        }
+      }
+    }
+    count
+  }
+
+  /// A hash of everything about the position, stable across platforms and Rust versions.
+  /// Replays compare it to make sure a recorded game still ends up the same way.
+  pub fn state_hash(&self) -> u64 {
+    //FNV-1a. The standard library's hasher isn't guaranteed to stay the same between releases.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |byte: u8| {
+      hash ^= byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    };
+    for column in &self.board {
+      for cell in column {
+        feed(match cell.status {
+          CellStatus::Covered => 0,
+          CellStatus::Revealed => 1,
+          CellStatus::Flagged => 2,
+          CellStatus::AntiFlagged => 3,
+        });
+        feed(match cell.value {
+          CellValue::Mined => 9,
+          CellValue::AntiMine => 19,
+          //Negative numbers come out as 248 to 255.
+          CellValue::Number(number) => number as u8,
+          CellValue::Treasure(number) if number < 0 => 20 + number.unsigned_abs(),
+          CellValue::Treasure(number) => 10 + number as u8,
+        });
+      }
+    }
+    feed(match self.status {
+      GameStatus::Playing => 0,
+      GameStatus::Lost => 1,
+      GameStatus::Won => 2,
+    });
+    for byte in (self.revealed_count as u64).to_le_bytes().into_iter().chain((self.flag_count as u64).to_le_bytes()) {
+      feed(byte);
+    }
+    hash
+  }
+
+  /// A hash of only what the player can see, so two people can check they're looking at the same position.
+  /// Covered cells all hash the same, whatever is under them.
+  pub fn position_hash(&self) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |byte: u8| {
+      hash ^= byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    };
+    for column in &self.board {
+      for cell in column {
+        feed(match (cell.status, cell.value) {
+          (CellStatus::Covered, _) => 0,
+          (CellStatus::Flagged, _) => 1,
+          (CellStatus::Revealed, CellValue::Mined) => 2,
+          (CellStatus::Revealed, CellValue::Number(number)) if number < 0 => 30 + number.unsigned_abs(),
+          (CellStatus::Revealed, CellValue::Treasure(number)) if number < 0 => 40 + number.unsigned_abs(),
+          (CellStatus::Revealed, CellValue::Number(number)) => 3 + number as u8,
+          (CellStatus::Revealed, CellValue::Treasure(number)) => 12 + number as u8,
+          (CellStatus::AntiFlagged, _) => 21,
+          (CellStatus::Revealed, CellValue::AntiMine) => 22,
+        });
+      }
+    }
+    hash
+  }
+
+  fn add_mines(&mut self) {
+    use rand::{seq::SliceRandom, SeedableRng};
+    let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed);
+    
+    // Create a Vec of all possible positions.
+    let mut positions = self.reading_order();
+    
+    // Shuffle the Vec of positions.
+    positions.shuffle(&mut rng);
+    
+    // Mine some positions.
+    for &(x, y) in positions.iter().take(self.board.mines) {
+      let (x, y) = self.transform.apply(&self.board, x, y);
+      self.board[x][y].value = CellValue::Mined;
+    }
+  }
+  
+  fn add_numbers(&mut self) {
+    for (x, y) in self.board.positions() {
+      //Count up all bombs at sides and corners, less the antimines
+      let count = self.neighbors.of(&self.board, x, y).map(|(new_x, new_y)| self.board[new_x][new_y].value.charge()).sum();
+      self.board[x][y].value = match self.board[x][y].value {
+        CellValue::Treasure(_) => CellValue::Treasure(count),
+        CellValue::Number(_) => CellValue::Number(count),
+        mine => mine,
+      };
+    }
+  }
+  
+  fn reveal_multiple(&mut self, x: usize, y: usize) {
+    let start = (x, y);
+    let mut reveal_vec = vec![(x, y)];
+    
+    while let Some(cell) = reveal_vec.pop() {
+      let x = cell.0;
+      let y = cell.1;
+
+      //Only reveal cells which haven't been revealed. Else we will be counting too many.
+      if self.board[x][y].status != CellStatus::Covered {
+        continue;
+      }
+
+      self.board[x][y].status = CellStatus::Revealed;
+      self.changed.push((x, y));
+
+      if self.board[x][y].value.is_mine() {
+        //A life takes the hit. The mine stays showing, and counts like a flag from then on.
+        if self.lives > 0 {
+          self.lives -= 1;
+          continue;
+        }
+        self.status = GameStatus::Lost;
+        return;
+      }
+
+      if let CellValue::Treasure(_) = self.board[x][y].value {
+        self.treasures_found += 1;
+        if self.treasure_reward == Reward::Life {
+          self.lives += 1;
+        }
+      }
+
+      //The opening a zero starts counts once, and so does each number no opening reveals.
+      self.cleared_three_bv += if self.is_blank(x, y) {
+        ((x, y) == start) as usize
+      } else {
+        !self.neighbors.of(&self.board, x, y).any(|(new_x, new_y)| self.is_blank(new_x, new_y)) as usize
+      };
+      self.revealed_count += 1;
+      if self.revealed_count >= self.board.width * self.board.height - self.board.mines {
+        //All numbers were revealed
+        self.status = GameStatus::Won;
+        return;
+      }
+      
+      //Clicked on a blank piece? Reveal all sides and corners.
+      if self.is_blank(x, y) {
+        reveal_vec.extend(self.neighbors.of(&self.board, x, y).filter(|&(new_x, new_y)| self.board[new_x][new_y].status == CellStatus::Covered));
+      }
+    }
+  }
+  
+  fn reveal_special(&mut self, x: usize, y: usize) {
+    //This feature should only work if the current cell is already revealed. Otherwise the user is cheating.
+    if self.board[x][y].status != CellStatus::Revealed {
+      return;
+    }
+
+    if let Some(cell_number) = self.board[x][y].value.number() {
+      let flag_count: i8 = self.neighbors.of(&self.board, x, y).map(|(new_x, new_y)| self.board[new_x][new_y].known_charge()).sum();
+      
+      //Flag count matches the cell number. Reveal the neighbors.
+      //With too many flags nothing happens here, and the game points them out with excess_flags instead.
+      if flag_count == cell_number {
+        for (new_x, new_y) in self.neighbors.of(&self.board, x, y) {
+          if self.board[new_x][new_y].status == CellStatus::Covered {
+            self.reveal_multiple(new_x, new_y);
+          }
+        }
+      }
+    }
+
+  }
+}
+
+/// One character for what is under a cell, as [`Engine::dump`] shows it.
+fn dump_value(value: CellValue) -> char {
+  match value {
+    CellValue::Mined => '*',
+    CellValue::AntiMine => 'a',
+    CellValue::Treasure(_) => '$',
+    CellValue::Number(0) => '.',
+    //Only the sign of negative numbers fits in one character.
+    CellValue::Number(number) if number < 0 => '-',
+    CellValue::Number(number) => (number as u8 + b'0') as char,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn neighbors_stay_on_the_board() {
+    let board = Board::new(30, 16, 99);
+    assert_eq!(board.neighbors(0, 0).count(), 3);
+    assert_eq!(board.neighbors(5, 0).count(), 5);
+    assert_eq!(board.neighbors(29, 15).count(), 3);
+    assert_eq!(board.neighbors(5, 5).count(), 8);
+    assert_eq!(Neighbors::Diagonal.of(&board, 5, 5).count(), 4);
+    assert_eq!(Neighbors::Orthogonal.of(&board, 0, 0).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+  }
+
+  #[test]
+  fn fair_layouts_have_no_coin_flips() {
+    let seed = (0..100).find(|&seed| !GameConfig::new(seed).build().fifty_fifties().is_empty()).unwrap();
+    let fair = GameConfig::new(seed).with_fair_layout(true).build();
+    assert!(fair.fifty_fifties().is_empty());
+    assert_eq!(fair.board.iter().flatten().filter(|cell| cell.value == CellValue::Mined).count(), fair.board.mines);
+    let mirrored = GameConfig::new(seed).with_transform(Transform::Mirror).with_fair_layout(true).build();
+    for (x, y) in fair.board.positions() {
+      let (mirrored_x, mirrored_y) = Transform::Mirror.apply(&fair.board, x, y);
+      assert!(fair.board[x][y].value == mirrored.board[mirrored_x][mirrored_y].value);
+    }
+  }
+
+  #[test]
+  fn gradient_boards_are_denser_on_the_right() {
+    let engine = GameConfig::new(5).with_gradient(true).build();
+    let mines = |columns: std::ops::Range<usize>| columns.flat_map(|x| (0..engine.board.height).map(move |y| (x, y)))
+      .filter(|&(x, y)| engine.board[x][y].value == CellValue::Mined).count();
+    let width = engine.board.width;
+    assert_eq!(mines(0..width), engine.board.mines);
+    assert!(mines(0..width / 3) * 2 < mines(width * 2 / 3..width));
+  }
+
+  #[test]
+  fn antimines_take_one_off_their_numbers() {
+    let mut engine = GameConfig::new(5).with_antimines(10).build();
+    assert_eq!(engine.board.iter().flatten().filter(|cell| cell.value == CellValue::AntiMine).count(), 10);
+    for (x, y) in engine.board.positions() {
+      if let Some(number) = engine.board[x][y].value.number() {
+        assert_eq!(number, engine.board.neighbors(x, y).map(|(x, y)| engine.board[x][y].value.charge()).sum::<i8>());
+      }
+    }
+    //Flags go mine, antimine, then off again.
+    for status in [CellStatus::Flagged, CellStatus::AntiFlagged, CellStatus::Covered] {
+      engine.act(Action::Flag(0, 0));
+      assert!(engine.board[0][0].status == status);
+    }
+    assert_eq!(engine.flag_count, 0);
+  }
+
+  #[test]
+  fn clearing_the_board_clears_its_three_bv() {
+    let mut engine = GameConfig::new(3).build();
+    //Cell by cell in reading order, so numbers beside an opening are often revealed before it.
+    for (x, y) in engine.reading_order() {
+      if engine.board[x][y].value != CellValue::Mined {
+        engine.act(Action::Reveal(x, y));
+      }
+    }
+    assert_eq!(engine.status, GameStatus::Won);
+    assert_eq!(engine.solved_three_bv(), engine.three_bv());
+  }
+
+  #[test]
+  fn safe_starts_open_up() {
+    let config = GameConfig::new(4).with_size(9, 9, 10).with_safe_start(true);
+    let board = config.build().board;
+    let (x, y) = board.positions().find(|&(x, y)| board[x][y].value.is_mine()).unwrap();
+    let mut engine = config.build();
+    engine.act(Action::Reveal(x, y));
+    assert_eq!(engine.status, GameStatus::Playing);
+    assert!(engine.is_blank(x, y));
+    assert_eq!(engine.board.iter().flatten().filter(|cell| cell.value.is_mine()).count(), 10);
+    //Only the first reveal moves mines, and the same way every time, so replays still work.
+    let mut again = config.build();
+    again.act(Action::Reveal(x, y));
+    assert_eq!(again.state_hash(), engine.state_hash());
+    //Too full for a whole opening, so only the cell clicked is safe.
+    let mut crowded = GameConfig::new(4).with_size(3, 3, 8).with_safe_start(true).build();
+    crowded.act(Action::Reveal(1, 1));
+    assert_eq!(crowded.status, GameStatus::Won);
+  }
+}
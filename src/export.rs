@@ -0,0 +1,149 @@
+//! Exports a game as an animated PNG, one frame per move, for sharing.
+//!
+//! Frames are drawn straight into a pixel buffer, with numbers from a tiny built in font.
+//! Each frame starts from the one before, with only the cells the move changed drawn again.
+use crate::{settings, theme};
+use minesweeper::engine::{Board, Cell, CellStatus, CellValue, Engine};
+use minesweeper::replay::Replay;
+use std::{fs, io, path};
+
+//How long the finished board stays on screen before the animation loops.
+const END_DELAY_MS: u16 = 2000;
+
+//Numbers 1 to 8, 3 pixels wide and 5 tall.
+const DIGITS: [[&str; 5]; 8] = [
+  [".#.", "##.", ".#.", ".#.", "###"],
+  ["##.", "..#", ".#.", "#..", "###"],
+  ["##.", "..#", ".#.", "..#", "##."],
+  ["#.#", "#.#", "###", "..#", "..#"],
+  ["###", "#..", "##.", "..#", "##."],
+  [".##", "#..", "###", "#.#", "###"],
+  ["###", "..#", ".#.", ".#.", ".#."],
+  ["###", "#.#", "###", "#.#", "###"],
+];
+
+struct Canvas {
+  width: usize,
+  pixels: Vec<u8>,
+}
+
+impl Canvas {
+  fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, [r, g, b]: [u8; 3]) {
+    for row in y..y + height {
+      for column in x..x + width {
+        let index = (row * self.width + column) * 3;
+        self.pixels[index..index + 3].copy_from_slice(&[r, g, b]);
+      }
+    }
+  }
+}
+
+fn rgb(color: iced::Color) -> [u8; 3] {
+  let [r, g, b, _] = color.into_rgba8();
+  [r, g, b]
+}
+
+fn draw_cell(canvas: &mut Canvas, engine: &Engine, (x, y): (usize, usize), cell_size: usize, theme: &theme::Theme, settings: &settings::Settings) {
+  //The font is drawn on a 7 by 7 grid, so it scales with the cells.
+  let scale = (cell_size / 7).max(1);
+  let inset = (cell_size - 3 * scale) / 2;
+  let (left, top) = (1 + x * (cell_size + 1), 1 + y * (cell_size + 1));
+  let middle = (cell_size - 5 * scale) / 2;
+  match engine.board[x][y] {
+    Cell {status: CellStatus::Covered, ..} => canvas.fill(left, top, cell_size, cell_size, theme.primary),
+    Cell {status: CellStatus::Flagged | CellStatus::AntiFlagged, ..} => {
+      canvas.fill(left, top, cell_size, cell_size, theme.primary);
+      canvas.fill(left + inset, top + middle, 3 * scale, 3 * scale, theme.danger);
+    },
+    Cell {status: CellStatus::Revealed, value: CellValue::Mined | CellValue::AntiMine} => {
+      canvas.fill(left, top, cell_size, cell_size, theme.danger);
+      canvas.fill(left + inset, top + inset, 3 * scale, 3 * scale, theme.text);
+    },
+    Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} => {
+      canvas.fill(left, top, cell_size, cell_size, theme.revealed);
+      //The pixel digits have no minus, so numbers from antimines are drawn without their sign.
+      if let Some(digit) = DIGITS.get((number.unsigned_abs() as usize).wrapping_sub(1)) {
+        let color = rgb(theme.number_color(settings, number.unsigned_abs()));
+        for (row, line) in digit.iter().enumerate() {
+          for (column, _) in line.char_indices().filter(|&(_, pixel)| pixel == '#') {
+            canvas.fill(left + inset + column * scale, top + middle + row * scale, scale, scale, color);
+          }
+        }
+      }
+    },
+  }
+}
+
+/// Width and height in pixels of a picture of `board`, with a line between cells and around the edge.
+fn image_size(board: &Board, cell_size: usize) -> (usize, usize) {
+  (board.width * (cell_size + 1) + 1, board.height * (cell_size + 1) + 1)
+}
+
+fn render(engine: &Engine, cell_size: usize, theme: &theme::Theme, settings: &settings::Settings) -> Canvas {
+  let (width, height) = image_size(&engine.board, cell_size);
+  let mut canvas = Canvas {width, pixels: Vec::with_capacity(width * height * 3)};
+  for _ in 0..width * height {
+    canvas.pixels.extend_from_slice(&theme.background);
+  }
+  for position in engine.board.positions() {
+    draw_cell(&mut canvas, engine, position, cell_size, theme, settings);
+  }
+  canvas
+}
+
+/// Every frame of `replay`, the board before any move and after each one.
+fn frames(replay: &Replay, cell_size: usize, theme: &theme::Theme, settings: &settings::Settings) -> Vec<Vec<u8>> {
+  let mut engine = replay.start();
+  let mut canvas = render(&engine, cell_size, theme, settings);
+  let mut frames = vec![canvas.pixels.clone()];
+  for &action in &replay.moves {
+    engine.act(action);
+    for &position in engine.changed() {
+      draw_cell(&mut canvas, &engine, position, cell_size, theme, settings);
+    }
+    frames.push(canvas.pixels.clone());
+  }
+  frames
+}
+
+/// Write `replay` as an animated PNG, with `cell_size` pixel cells and `frame_ms` between moves.
+pub fn save(replay: &Replay, path: &path::Path, cell_size: u32, frame_ms: u16, theme: &theme::Theme, settings: &settings::Settings) -> io::Result<()> {
+  let cell_size = cell_size.max(7) as usize;
+  let frames = frames(replay, cell_size, theme, settings);
+  let (width, height) = image_size(&replay.start().board, cell_size);
+  let mut encoder = png::Encoder::new(io::BufWriter::new(fs::File::create(path)?), width as u32, height as u32);
+  encoder.set_color(png::ColorType::Rgb);
+  encoder.set_depth(png::BitDepth::Eight);
+  encoder.set_compression(png::Compression::Best);
+  encoder.set_animated(frames.len() as u32, 0)?;
+  encoder.set_frame_delay(frame_ms, 1000)?;
+  let mut writer = encoder.write_header()?;
+  let last = frames.len() - 1;
+  for (index, frame) in frames.iter().enumerate() {
+    if index == last {
+      writer.set_frame_delay(END_DELAY_MS, 1000)?;
+    }
+    writer.write_image_data(frame)?;
+  }
+  writer.finish()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn frames_match_whole_redraws() {
+    let (theme, settings) = (theme::Theme::default(), settings::Settings::default());
+    for json in [include_str!("../tests/replays/win.json"), include_str!("../tests/replays/reveal_mine.json")] {
+      let replay = Replay::from_json(json).unwrap();
+      let frames = frames(&replay, 7, &theme, &settings);
+      let mut engine = replay.start();
+      for (index, frame) in frames.iter().enumerate().skip(1) {
+        engine.act(replay.moves[index - 1]);
+        assert!(*frame == render(&engine, 7, &theme, &settings).pixels, "frame {}", index);
+      }
+    }
+  }
+}
@@ -0,0 +1,182 @@
+//! What shows how the game is going in the header. The emoji face by default, since that's the classic look,
+//! but some font stacks draw it poorly, so the theme can pick words, a lamp, a ring filling up with the board, or images of its own.
+use iced::widget::canvas;
+use iced::{mouse, Point, Rectangle, Size};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path};
+
+/// How the game is going.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mood {
+  Playing,
+  /// A mouse button is held down on a cell.
+  Pressed,
+  Won,
+  Lost,
+}
+
+impl Mood {
+  pub fn emoji(self) -> char {
+    match self {
+      Mood::Playing => '😀',
+      Mood::Pressed => '😮',
+      Mood::Won => '😎',
+      Mood::Lost => '☹',
+    }
+  }
+
+  pub fn text(self) -> &'static str {
+    match self {
+      Mood::Playing => "Playing",
+      Mood::Pressed => "…",
+      Mood::Won => "Won!",
+      Mood::Lost => "Lost",
+    }
+  }
+
+  /// Name of the image file for the mood, in `face_images`.
+  fn file_name(self) -> &'static str {
+    match self {
+      Mood::Playing => "playing.png",
+      Mood::Pressed => "pressed.png",
+      Mood::Won => "won.png",
+      Mood::Lost => "lost.png",
+    }
+  }
+}
+
+/// What the theme shows in place of the face.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Indicator {
+  #[default]
+  Emoji,
+  /// A word, like "Won!".
+  Text,
+  /// A round lamp, blue while playing, green once won and red once lost.
+  Lamp,
+  /// A ring which fills up as safe cells are revealed, colored like the lamp.
+  Ring,
+}
+
+/// A picture decoded from a PNG file, as rows of RGBA pixels.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Image {
+  pub width: u32,
+  pub height: u32,
+  pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+  pub fn load(path: &path::Path) -> io::Result<Image> {
+    let mut decoder = png::Decoder::new(fs::File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8() | png::Transformations::ALPHA);
+    let mut reader = decoder.read_info().map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let pixels = match info.color_type {
+      png::ColorType::Rgba => buffer[..info.buffer_size()].chunks(4).map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]).collect(),
+      //Gray with alpha is the only other kind left after the transformations.
+      _ => buffer[..info.buffer_size()].chunks(2).map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]]).collect(),
+    };
+    Ok(Image {width: info.width, height: info.height, pixels})
+  }
+
+  pub fn save(&self, path: &path::Path) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(fs::File::create(path)?, self.width, self.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let pixels: Vec<u8> = self.pixels.iter().flatten().copied().collect();
+    encoder.write_header()?.write_image_data(&pixels)?;
+    Ok(())
+  }
+}
+
+/// Images for each mood, loaded from a folder holding `playing.png`, `pressed.png`, `won.png` and `lost.png`.
+/// Moods without an image fall back to the theme's indicator.
+#[derive(Clone, Default)]
+pub struct Images {
+  images: Vec<(Mood, Image)>,
+}
+
+impl Images {
+  pub fn load(folder: &path::Path) -> Images {
+    let images = [Mood::Playing, Mood::Pressed, Mood::Won, Mood::Lost].into_iter()
+      .filter_map(|mood| Image::load(&folder.join(mood.file_name())).ok().map(|image| (mood, image)))
+      .collect();
+    Images {images}
+  }
+
+  pub fn get(&self, mood: Mood) -> Option<&Image> {
+    self.images.iter().find(|(image_mood, _)| *image_mood == mood).map(|(_, image)| image)
+  }
+}
+
+/// The lamp, ring or image, drawn to fit the square it is given.
+pub struct Drawing<'a> {
+  pub mood: Mood,
+  pub indicator: Indicator,
+  /// How much of the board is cleared, from 0 to 1, for the ring.
+  pub progress: f32,
+  pub image: Option<&'a Image>,
+}
+
+impl<Message> canvas::Program<Message> for Drawing<'_> {
+  type State = ();
+
+  fn draw(&self, _state: &(), renderer: &iced::Renderer, theme: &iced::Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<canvas::Geometry> {
+    let mut frame = canvas::Frame::new(renderer, bounds.size());
+    let palette = theme.palette();
+    let color = match self.mood {
+      Mood::Playing | Mood::Pressed => palette.primary,
+      Mood::Won => palette.success,
+      Mood::Lost => palette.danger,
+    };
+    let center = frame.center();
+    let radius = bounds.width.min(bounds.height) / 2.0 - 2.0;
+    match (self.image, self.indicator) {
+      (Some(image), _) => {
+        let pixel = (bounds.width / image.width as f32).min(bounds.height / image.height as f32);
+        for (index, &[r, g, b, a]) in image.pixels.iter().enumerate() {
+          if a > 0 {
+            let (x, y) = (index as u32 % image.width, index as u32 / image.width);
+            frame.fill_rectangle(Point::new(x as f32 * pixel, y as f32 * pixel), Size::new(pixel, pixel), iced::Color::from_rgba8(r, g, b, a as f32 / 255.0));
+          }
+        }
+      },
+      (None, Indicator::Ring) => {
+        frame.stroke(&canvas::Path::circle(center, radius), canvas::Stroke::default().with_color(iced::Color {a: 0.3, ..color}).with_width(4.0));
+        let arc = canvas::Path::new(|builder| builder.arc(canvas::path::Arc {
+          center,
+          radius,
+          start_angle: -std::f32::consts::FRAC_PI_2,
+          end_angle: -std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * self.progress.clamp(0.0, 1.0),
+        }));
+        frame.stroke(&arc, canvas::Stroke::default().with_color(color).with_width(4.0));
+      },
+      //A press dims the lamp, the way the face gasps.
+      (None, _) => frame.fill(&canvas::Path::circle(center, radius), if self.mood == Mood::Pressed { iced::Color {a: 0.5, ..color} } else { color }),
+    }
+    vec![frame.into_geometry()]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn images_load_by_mood() {
+    let folder = std::env::temp_dir().join("minesweeper-faces");
+    fs::create_dir_all(&folder).unwrap();
+    let mut encoder = png::Encoder::new(fs::File::create(folder.join("won.png")).unwrap(), 2, 1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header().unwrap().write_image_data(&[255, 0, 0, 0, 255, 0]).unwrap();
+    let images = Images::load(&folder);
+    let won = images.get(Mood::Won).unwrap();
+    assert_eq!((won.width, won.height), (2, 1));
+    assert_eq!(won.pixels, [[255, 0, 0, 255], [0, 255, 0, 255]]);
+    assert!(images.get(Mood::Lost).is_none());
+  }
+}
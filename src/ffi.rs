@@ -0,0 +1,153 @@
+//! C API for embedding the engine in other languages. Enabled with the `ffi` feature.
+//!
+//! The matching header is `include/minesweeper.h`, generated by cbindgen. Only add to this API, so the ABI stays stable.
+use crate::{action::Action, config::GameConfig, engine};
+
+pub const MS_ACTION_REVEAL: u32 = 0;
+pub const MS_ACTION_FLAG: u32 = 1;
+pub const MS_ACTION_CHORD: u32 = 2;
+
+pub const MS_STATUS_PLAYING: u32 = 0;
+pub const MS_STATUS_WON: u32 = 1;
+pub const MS_STATUS_LOST: u32 = 2;
+
+pub const MS_CELL_COVERED: i8 = -1;
+pub const MS_CELL_FLAGGED: i8 = -2;
+pub const MS_CELL_MINE: i8 = -3;
+pub const MS_CELL_OFF_BOARD: i8 = -4;
+
+/// Opaque handle to a game.
+pub struct MsEngine(engine::Engine);
+
+/// Width of the board `ms_engine_new` makes. Kept for older callers, since a game's own size comes from `ms_engine_width`.
+#[deprecated(note = "use ms_engine_width")]
+#[no_mangle]
+pub extern "C" fn ms_board_width() -> u32 {
+  GameConfig::default().width as u32
+}
+
+/// Kept for older callers, since a game's own size comes from `ms_engine_height`.
+#[deprecated(note = "use ms_engine_height")]
+#[no_mangle]
+pub extern "C" fn ms_board_height() -> u32 {
+  GameConfig::default().height as u32
+}
+
+/// Kept for older callers, since a game's own mine count comes from `ms_engine_mines`.
+#[deprecated(note = "use ms_engine_mines")]
+#[no_mangle]
+pub extern "C" fn ms_board_mines() -> u32 {
+  GameConfig::default().mines as u32
+}
+
+/// Generate the expert board for `seed`. Free it with `ms_engine_free`.
+#[no_mangle]
+pub extern "C" fn ms_engine_new(seed: u64) -> *mut MsEngine {
+  Box::into_raw(Box::new(MsEngine(GameConfig::new(seed).build())))
+}
+
+/// Generate a `width`×`height` board with `mines` for `seed`, where the first reveal is never a mine if `safe_start` is set.
+/// Free it with `ms_engine_free`.
+#[no_mangle]
+pub extern "C" fn ms_engine_new_sized(seed: u64, width: u32, height: u32, mines: u32, safe_start: bool) -> *mut MsEngine {
+  let config = GameConfig::new(seed).with_size(width as usize, height as usize, mines as usize).with_safe_start(safe_start);
+  Box::into_raw(Box::new(MsEngine(config.build())))
+}
+
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_width(engine: *const MsEngine) -> u32 {
+  engine.as_ref().map_or(0, |MsEngine(engine)| engine.board.width as u32)
+}
+
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_height(engine: *const MsEngine) -> u32 {
+  engine.as_ref().map_or(0, |MsEngine(engine)| engine.board.height as u32)
+}
+
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_mines(engine: *const MsEngine) -> u32 {
+  engine.as_ref().map_or(0, |MsEngine(engine)| engine.board.mines as u32)
+}
+
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized` and not be used afterwards. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_free(engine: *mut MsEngine) {
+  if !engine.is_null() {
+    drop(Box::from_raw(engine));
+  }
+}
+
+/// Play one of the `MS_ACTION_` values at a position. Unknown actions and positions off the board are ignored.
+///
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_act(engine: *mut MsEngine, action: u32, x: u32, y: u32) {
+  let Some(MsEngine(engine)) = engine.as_mut() else { return };
+  let (x, y) = (x as usize, y as usize);
+  match action {
+    MS_ACTION_REVEAL => engine.act(Action::Reveal(x, y)),
+    MS_ACTION_FLAG => engine.act(Action::Flag(x, y)),
+    MS_ACTION_CHORD => engine.act(Action::Chord(x, y)),
+    _ => (),
+  }
+}
+
+/// One of the `MS_STATUS_` values.
+///
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_status(engine: *const MsEngine) -> u32 {
+  match engine.as_ref().map(|MsEngine(engine)| &engine.status) {
+    Some(engine::GameStatus::Won) => MS_STATUS_WON,
+    Some(engine::GameStatus::Lost) => MS_STATUS_LOST,
+    _ => MS_STATUS_PLAYING,
+  }
+}
+
+/// What the player sees at a position: a number from 0 to 8, or one of the `MS_CELL_` values.
+///
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_cell(engine: *const MsEngine, x: u32, y: u32) -> i8 {
+  let Some(MsEngine(engine)) = engine.as_ref() else { return MS_CELL_OFF_BOARD };
+  let Some(cell) = engine.board.get(x as usize).and_then(|column| column.get(y as usize)) else { return MS_CELL_OFF_BOARD };
+  match (cell.status, cell.value) {
+    (engine::CellStatus::Covered, _) => MS_CELL_COVERED,
+    (engine::CellStatus::Flagged | engine::CellStatus::AntiFlagged, _) => MS_CELL_FLAGGED,
+    (engine::CellStatus::Revealed, engine::CellValue::Mined | engine::CellValue::AntiMine) => MS_CELL_MINE,
+    (engine::CellStatus::Revealed, engine::CellValue::Number(number) | engine::CellValue::Treasure(number)) => number,
+  }
+}
+
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_revealed_count(engine: *const MsEngine) -> u32 {
+  engine.as_ref().map_or(0, |MsEngine(engine)| engine.revealed_count as u32)
+}
+
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_flag_count(engine: *const MsEngine) -> u32 {
+  engine.as_ref().map_or(0, |MsEngine(engine)| engine.flag_count as u32)
+}
+
+/// Stable hash of the whole position, matching the game's replays.
+///
+/// # Safety
+/// `engine` must come from `ms_engine_new` or `ms_engine_new_sized`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ms_engine_state_hash(engine: *const MsEngine) -> u64 {
+  engine.as_ref().map_or(0, |MsEngine(engine)| engine.state_hash())
+}
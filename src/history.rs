@@ -0,0 +1,157 @@
+//! A game kept as its fresh board plus the list of actions played on it.
+//!
+//! Every position is worked out from those by playing the actions again, starting from the nearest checkpoint.
+//! Undo, replays and jumping around in time are all just picking how many of the actions to play.
+use crate::action::Action;
+use crate::engine::Engine;
+use crate::replay::Replay;
+use std::ops::Deref;
+
+/// Actions between checkpoints. No position is more than this many actions away from being worked out.
+const CHECKPOINT_EVERY: usize = 32;
+
+#[derive(Clone)]
+pub struct History {
+  /// Every action, including ones stepped back over, until a new action replaces them.
+  actions: Vec<Action>,
+  /// The positions after 0, `CHECKPOINT_EVERY`, 2 * `CHECKPOINT_EVERY`... actions.
+  checkpoints: Vec<Engine>,
+  /// The position being played, after the first `current.moves.len()` actions.
+  current: Engine,
+}
+
+impl History {
+  /// A history starting from `start`, which should be a fresh board.
+  pub fn new(start: Engine) -> History {
+    History {actions: start.moves.clone(), checkpoints: vec![start.clone()], current: start}
+  }
+
+  /// A replay's board with all of its moves played.
+  pub fn from_replay(replay: &Replay) -> History {
+    let mut history = History::new(replay.start());
+    for &action in &replay.moves {
+      history.act(action);
+    }
+    history
+  }
+
+  /// How many actions have been played to reach the current position.
+  pub fn position(&self) -> usize {
+    self.current.moves.len()
+  }
+
+  /// How many actions there are, counting ones stepped back over.
+  pub fn len(&self) -> usize {
+    self.actions.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.actions.is_empty()
+  }
+
+  /// Plays an action on the current position. Any actions stepped back over are dropped.
+  /// Actions the rules ignore, like ones after the game is over, aren't kept.
+  pub fn act(&mut self, action: Action) {
+    let position = self.position();
+    self.current.act(action);
+    if self.position() == position {
+      return;
+    }
+    self.actions.truncate(position);
+    self.actions.push(action);
+    self.checkpoints.truncate(position / CHECKPOINT_EVERY + 1);
+    if self.position() == self.checkpoints.len() * CHECKPOINT_EVERY {
+      self.checkpoints.push(self.current.clone());
+    }
+  }
+
+  /// Loses the game without an action, like when the countdown runs out.
+  /// This isn't kept, so stepping back and forward again undoes it.
+  pub fn lose(&mut self) {
+    self.current.lose();
+  }
+
+  /// The position after the first `index` actions.
+  pub fn at(&self, index: usize) -> Engine {
+    let index = index.min(self.len());
+    if index == self.position() {
+      return self.current.clone();
+    }
+    let mut engine = self.checkpoints[(index / CHECKPOINT_EVERY).min(self.checkpoints.len() - 1)].clone();
+    for &action in &self.actions[engine.moves.len()..index] {
+      engine.act(action);
+    }
+    engine
+  }
+
+  /// Moves to the position after the first `index` actions, keeping the rest to step forward to again.
+  pub fn seek(&mut self, index: usize) {
+    let position = self.at(index);
+    let before = std::mem::replace(&mut self.current, position);
+    self.current.changed_since(&before);
+  }
+
+  /// Steps back over the last action played, returning it.
+  pub fn undo(&mut self) -> Option<Action> {
+    let action = *self.current.moves.last()?;
+    self.seek(self.position() - 1);
+    Some(action)
+  }
+}
+
+impl Deref for History {
+  type Target = Engine;
+
+  fn deref(&self) -> &Engine {
+    &self.current
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::GameConfig;
+
+  #[test]
+  fn every_position_matches_playing_from_the_start() {
+    let mut history = History::new(GameConfig::new(7).build());
+    let (width, height) = (history.board.width, history.board.height);
+    let mut flags = Vec::new();
+    for x in 0..width {
+      for y in 0..2 {
+        history.act(Action::Flag(x, y));
+        flags.push(Action::Flag(x, y));
+      }
+    }
+    history.seek(50);
+    history.act(Action::Reveal(width - 1, height - 1));
+    assert_eq!(history.len(), 51);
+    for index in 0..=history.len() {
+      let mut engine = GameConfig::new(7).build();
+      for &action in flags.iter().take(index.min(50)) {
+        engine.act(action);
+      }
+      if index > 50 {
+        engine.act(Action::Reveal(width - 1, height - 1));
+      }
+      assert_eq!(history.at(index).state_hash(), engine.state_hash());
+    }
+  }
+
+  #[test]
+  fn jumps_mark_what_they_changed() {
+    let mut history = History::new(GameConfig::new(7).build());
+    let cells = history.board.width * history.board.height;
+    history.act(Action::Flag(0, 0));
+    history.act(Action::Flag(1, 0));
+    history.seek(0);
+    assert_eq!(history.changed(), [(0, 0), (1, 0)]);
+    history.seek(2);
+    history.undo();
+    assert_eq!(history.changed(), [(1, 0)]);
+    history.lose();
+    assert_eq!(history.changed().len(), cells);
+    history.act(Action::Flag(2, 0));
+    assert!(history.changed().is_empty());
+  }
+}
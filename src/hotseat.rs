@@ -0,0 +1,133 @@
+//! Hot seat: players taking turns at one board on one computer, passing the mouse along after every move.
+//!
+//! In co-op they play together, sharing the board's result, and at the end each player's part is listed.
+//! In versus every cell opened scores a point for whoever opened it and every mine set off costs [`MINE_PENALTY`],
+//! but doesn't end the game, which goes on until the board is cleared.
+//! Players can be given a handicap, points they start versus with, so a stronger player has to win by more.
+//! A move which changes nothing, like chording a number without its flags, doesn't use up the turn.
+
+/// Points lost for setting off a mine in versus.
+pub const MINE_PENALTY: i64 = 10;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mode {
+  CoOp,
+  Versus,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Player {
+  pub name: String,
+  pub moves: usize,
+  /// Cells their moves opened.
+  pub opened: usize,
+  /// Flags placed, changed or taken back.
+  pub flags: usize,
+  /// Mines they set off.
+  pub mines: usize,
+  /// Points to start versus with.
+  pub handicap: i64,
+}
+
+impl Player {
+  /// Points in versus.
+  pub fn score(&self) -> i64 {
+    self.handicap + self.opened as i64 - self.mines as i64 * MINE_PENALTY
+  }
+}
+
+pub struct HotSeat {
+  pub mode: Mode,
+  pub players: Vec<Player>,
+  /// Index of the player whose move it is.
+  pub turn: usize,
+}
+
+impl HotSeat {
+  /// Players with these names and handicaps in the same order, or made up ones when there are fewer than two names.
+  /// Missing handicaps are 0.
+  pub fn new(mode: Mode, names: &[String], handicaps: &[i64]) -> HotSeat {
+    let mut names: Vec<String> = names.iter().map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect();
+    if names.len() < 2 {
+      names = vec![String::from("Player 1"), String::from("Player 2")];
+    }
+    let players = names.into_iter().enumerate()
+      .map(|(index, name)| Player {name, handicap: handicaps.get(index).copied().unwrap_or_default(), ..Default::default()})
+      .collect();
+    HotSeat {mode, players, turn: 0}
+  }
+
+  /// The same players on a new board.
+  pub fn restart(&self) -> HotSeat {
+    HotSeat {mode: self.mode, players: self.players.iter().map(|player| Player {name: player.name.clone(), handicap: player.handicap, ..Default::default()}).collect(), turn: 0}
+  }
+
+  pub fn current(&self) -> &Player {
+    &self.players[self.turn]
+  }
+
+  /// A move by the player whose turn it is, which opened `opened` cells, changed a flag or not and set off `mines`.
+  /// The turn goes to the next player.
+  pub fn played(&mut self, opened: usize, flagged: bool, mines: usize) {
+    let player = &mut self.players[self.turn];
+    player.moves += 1;
+    player.opened += opened;
+    player.flags += flagged as usize;
+    player.mines += mines;
+    self.turn = (self.turn + 1) % self.players.len();
+  }
+
+  /// The players in versus, best first, in words.
+  pub fn scores(&self) -> String {
+    let mut players: Vec<&Player> = self.players.iter().collect();
+    players.sort_by_key(|player| -player.score());
+    players.iter().map(|player| format!("{} {}", player.name, player.score())).collect::<Vec<_>>().join("  ")
+  }
+
+  /// Each player's part of the game, in words. Versus starts with who won.
+  pub fn summary(&self) -> Vec<String> {
+    let mut lines = Vec::new();
+    if self.mode == Mode::Versus {
+      let best = self.players.iter().map(Player::score).max().unwrap_or_default();
+      let winners: Vec<&str> = self.players.iter().filter(|player| player.score() == best).map(|player| player.name.as_str()).collect();
+      lines.push(match winners[..] {
+        [winner] => format!("🏆 {} wins with {} points", winner, best),
+        _ => format!("🏆 A tie between {} with {} points", winners.join(" and "), best),
+      });
+    }
+    lines.extend(self.players.iter().map(|player| {
+      let mut line = format!("{}: {} moves, {} cells opened, {} flags", player.name, player.moves, player.opened, player.flags);
+      if player.mines > 0 {
+        line += &format!(", {} mines set off", player.mines);
+      }
+      match (self.mode, player.handicap) {
+        (Mode::Versus, 0) => line += &format!(", {} points", player.score()),
+        (Mode::Versus, handicap) => line += &format!(", {} points with a handicap of {:+}", player.score(), handicap),
+        (Mode::CoOp, _) => (),
+      }
+      line
+    }));
+    lines
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn turns_go_round() {
+    let mut seat = HotSeat::new(Mode::Versus, &[String::from("Ann"), String::from(" "), String::from("Bo")], &[0, 10]);
+    assert_eq!(seat.players.len(), 2);
+    seat.played(30, false, 0);
+    seat.played(0, true, 0);
+    seat.played(1, false, 0);
+    seat.played(2, false, 1);
+    assert_eq!(seat.current().name, "Ann");
+    assert_eq!(seat.summary()[0], "🏆 Ann wins with 31 points");
+    assert_eq!(seat.players[0], Player {name: String::from("Ann"), moves: 2, opened: 31, flags: 0, mines: 0, handicap: 0});
+    assert_eq!(seat.players[1].score(), 10 + 2 - MINE_PENALTY);
+    assert_eq!(seat.restart().players[0].moves, 0);
+    assert_eq!(HotSeat::new(Mode::CoOp, &[], &[]).players.len(), 2);
+  }
+}
@@ -0,0 +1,18 @@
+//! The rules of Minesweeper, usable without the GUI.
+//!
+//! The game binary is built on top of this, and so are the fuzz targets in `fuzz/`.
+pub mod action;
+pub mod config;
+pub mod engine;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod history;
+pub mod link;
+pub mod pack;
+pub mod plausibility;
+pub mod probability;
+pub mod replay;
+pub mod schema;
+pub mod scoring;
+pub mod seed;
+pub mod solver;
@@ -0,0 +1,158 @@
+//! Position links, like `minesweeper:12345/r3.4,f5.6,c4.4#9c1f0e2ab3d4c5e6`, for talking about a game part way through.
+//!
+//! A link is the seed, the moves played on it and [`Engine::position_hash`] after them,
+//! so whoever opens it can be sure they're looking at the same position.
+//! Boards with other sizes or rules list them after a `?`, like `?size=16x16&mines=40&neighbors=diagonal&flags=20`.
+use crate::action::Action;
+use crate::engine::{Engine, Neighbors, Reward, Transform};
+use crate::config::GameConfig;
+use crate::replay::Replay;
+use crate::seed;
+
+const SCHEME: &str = "minesweeper:";
+
+fn action_text(action: Action) -> String {
+  let letter = match action {
+    Action::Reveal(..) => 'r',
+    Action::Flag(..) => 'f',
+    Action::Chord(..) => 'c',
+  };
+  let (x, y) = action.position();
+  format!("{}{}.{}", letter, x, y)
+}
+
+fn parse_action(text: &str) -> Result<Action, String> {
+  let error = || format!("Can't read the move {}", text);
+  let mut chars = text.chars();
+  let letter = chars.next().ok_or_else(error)?;
+  let (x, y) = chars.as_str().split_once('.').ok_or_else(error)?;
+  let x: usize = x.parse().map_err(|_| error())?;
+  let y: usize = y.parse().map_err(|_| error())?;
+  match letter {
+    'r' => Ok(Action::Reveal(x, y)),
+    'f' => Ok(Action::Flag(x, y)),
+    'c' => Ok(Action::Chord(x, y)),
+    _ => Err(error()),
+  }
+}
+
+/// A link to the position `engine` is in.
+pub fn link(engine: &Engine) -> String {
+  let moves: Vec<String> = engine.moves.iter().map(|&action| action_text(action)).collect();
+  let mut rules = Vec::new();
+  let standard = GameConfig::default();
+  if (engine.board.width, engine.board.height) != (standard.width, standard.height) {
+    rules.push(format!("size={}x{}", engine.board.width, engine.board.height));
+  }
+  if engine.board.mines != standard.mines {
+    rules.push(format!("mines={}", engine.board.mines));
+  }
+  if engine.safe_start {
+    rules.push(String::from("start=safe"));
+  }
+  match engine.transform {
+    Transform::None => {},
+    Transform::Mirror => rules.push(String::from("transform=mirror")),
+    Transform::Flip => rules.push(String::from("transform=flip")),
+    Transform::Rotate => rules.push(String::from("transform=rotate")),
+  }
+  if engine.neighbors != Neighbors::All {
+    rules.push(format!("neighbors={}", engine.neighbors.name().to_lowercase()));
+  }
+  if engine.fair {
+    rules.push(String::from("layout=fair"));
+  }
+  if engine.gradient {
+    rules.push(String::from("density=gradient"));
+  }
+  if engine.antimines > 0 {
+    rules.push(format!("antimines={}", engine.antimines));
+  }
+  if engine.starting_lives > 0 {
+    rules.push(format!("lives={}", engine.starting_lives));
+  }
+  if engine.treasures > 0 {
+    rules.push(format!("treasures={}", engine.treasures));
+    if engine.treasure_reward == Reward::Life {
+      rules.push(String::from("reward=life"));
+    }
+  }
+  if let Some(limit) = engine.flag_limit {
+    rules.push(format!("flags={}", limit));
+  }
+  if engine.flag_win {
+    rules.push(String::from("win=flags"));
+  }
+  let rules = if rules.is_empty() { String::new() } else { format!("?{}", rules.join("&")) };
+  format!("{}{}/{}{}#{:016x}", SCHEME, engine.seed, moves.join(","), rules, engine.position_hash())
+}
+
+/// Plays a link's moves on its board. Fails if they don't lead to the position it was made from.
+pub fn open(link: &str) -> Result<Engine, String> {
+  let rest = link.trim().strip_prefix(SCHEME).ok_or_else(|| format!("Position links start with {}", SCHEME))?;
+  let (rest, hash) = rest.split_once('#').ok_or("The link is missing its hash")?;
+  let hash = u64::from_str_radix(hash, 16).map_err(|_| format!("Can't read the hash {}", hash))?;
+  let (rest, rules) = rest.split_once('?').unwrap_or((rest, ""));
+  let (seed, moves) = rest.split_once('/').unwrap_or((rest, ""));
+
+  let moves: Vec<Action> = moves.split(',').filter(|text| !text.is_empty()).map(parse_action).collect::<Result<_, _>>()?;
+  let mut config = GameConfig::new(seed::parse(seed));
+  for rule in rules.split('&').filter(|rule| !rule.is_empty()) {
+    match rule.split_once('=') {
+      Some(("size", size)) => {
+        let (width, height) = size.split_once('x').ok_or_else(|| format!("Can't read the rule {}", rule))?;
+        config.width = width.parse().map_err(|_| format!("Can't read the rule {}", rule))?;
+        config.height = height.parse().map_err(|_| format!("Can't read the rule {}", rule))?;
+      },
+      Some(("mines", count)) => config.mines = count.parse().map_err(|_| format!("Can't read the rule {}", rule))?,
+      Some(("start", "safe")) => config.safe_start = true,
+      Some(("transform", "mirror")) => config.transform = Transform::Mirror,
+      Some(("transform", "flip")) => config.transform = Transform::Flip,
+      Some(("transform", "rotate")) => config.transform = Transform::Rotate,
+      Some(("neighbors", "diagonal")) => config.neighbors = Neighbors::Diagonal,
+      Some(("neighbors", "orthogonal")) => config.neighbors = Neighbors::Orthogonal,
+      Some(("layout", "fair")) => config.fair = true,
+      Some(("density", "gradient")) => config.gradient = true,
+      Some(("lives", count)) => config.lives = count.parse().map_err(|_| format!("Can't read the rule {}", rule))?,
+      Some(("antimines", count)) => config.antimines = count.parse().map_err(|_| format!("Can't read the rule {}", rule))?,
+      Some(("treasures", count)) => config.treasures = count.parse().map_err(|_| format!("Can't read the rule {}", rule))?,
+      Some(("reward", "life")) => config.treasure_reward = Reward::Life,
+      Some(("flags", limit)) => config.flag_limit = Some(limit.parse().map_err(|_| format!("Can't read the rule {}", rule))?),
+      Some(("win", "flags")) => config.flag_win = true,
+      _ => return Err(format!("Unknown rule {}", rule)),
+    }
+  }
+
+  if let Some(&action) = moves.iter().find(|action| action.position().0 >= config.width || action.position().1 >= config.height) {
+    return Err(format!("The move {} is off the board", action_text(action)));
+  }
+  let engine = Replay {config, moves, times: Vec::new(), hash: None}.run()?;
+  if engine.position_hash() != hash {
+    return Err(format!("The moves lead to position {:016x}, not {:016x}", engine.position_hash(), hash));
+  }
+  Ok(engine)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn links_open_the_same_position() {
+    let mut engine = GameConfig::new(42).with_transform(Transform::Flip).with_neighbors(Neighbors::Diagonal).with_flag_limit(Some(20)).build();
+    engine.act(Action::Reveal(10, 8));
+    engine.act(Action::Flag(0, 0));
+    assert_eq!(engine.flag_count, 1);
+    let opened = open(&link(&engine)).unwrap();
+    assert_eq!(opened.state_hash(), engine.state_hash());
+
+    let tampered = link(&engine).replace("f0.0", "f1.0");
+    assert!(open(&tampered).is_err());
+
+    let mut small = GameConfig::new(42).with_size(9, 9, 10).with_safe_start(true).build();
+    small.act(Action::Reveal(4, 4));
+    assert!(link(&small).contains("?size=9x9&mines=10&start=safe"));
+    assert_eq!(open(&link(&small)).unwrap().state_hash(), small.state_hash());
+    assert!(open(&link(&small).replace("r4.4", "r9.4")).is_err());
+  }
+}
@@ -1,339 +1,2675 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod animation;
+mod audio;
+mod board;
+mod camera;
 mod cell;
+mod chart;
+mod cli;
+mod clock;
+mod combo;
+mod compare;
+mod console;
+mod daily;
+mod drill;
+mod export;
+mod face;
+mod hotseat;
+mod modal;
+mod narration;
+mod notify;
+mod overlay;
+mod profile;
+mod quad;
+mod report;
+mod rush;
+mod scanner;
+mod screen;
+mod session;
+mod settings;
+mod stats;
+mod theme;
+mod toast;
 
-use iced::{theme, widget, window};
+use iced::{keyboard, mouse, widget, window};
+use iced::widget::scrollable;
+use minesweeper::{action, config, engine, history, link, pack, plausibility, replay, schema, scoring, seed, solver};
+use action::Action;
+use engine::{Cell, CellStatus, CellValue, GameStatus};
+use config::GameConfig;
+use std::time;
 
-const CELL_ROWS: usize = 16;
-const CELL_COLUMNS: usize = 30;
-const MINE_COUNT: usize = 99;
+const CELL_LENGTH: f32 = 20.0;
+//Largest the window is made to fit the board, so big boards don't open off the screen.
+const MAX_WINDOW: (f32, f32) = (1600.0, 900.0);
+const HEADER_HEIGHT: f32 = 33.0;
+const UI_SCALE_STEP: f32 = 0.25;
+/// Delay for each cell further away from the winning move, as the remaining mines are flagged.
+const FLAG_THE_REST_STEP: time::Duration = time::Duration::from_millis(25);
+const DEMO_STEP: time::Duration = time::Duration::from_millis(400);
+//How many of the latest wins the statistics view charts.
+const STATS_GAMES: usize = 50;
+//How often a playing comparison moves along its timeline.
+const COMPARE_STEP: time::Duration = time::Duration::from_millis(50);
+//Drill boards are small, so they are drawn bigger.
+const DRILL_SCALE: f32 = 2.0;
+const TREASURE_COLOR: iced::Color = iced::Color {r: 0.85, g: 0.65, b: 0.13, a: 1.0};
+//Steps the demo leaves a finished board on screen before starting over.
+const DEMO_END_PAUSE: u8 = 5;
+//The player's secret key, in the config directory, which seed pack results are signed with.
+const PLAYER_KEY: &str = "player.key";
 
 pub fn main() -> iced::Result {
+  if let Some(code) = cli::run(&std::env::args().skip(1).collect::<Vec<_>>()) {
+    std::process::exit(code);
+  }
+  let game_settings = settings::Settings::load();
+  let cells = (game_settings.board_width, game_settings.board_height);
+  let size = window_size(cells, theme::Theme::load(game_settings.is_dark()).cell_spacing(), game_settings.ui_scale());
   let settings = iced::Settings {
     window: window::Settings {
-      size: (21 * CELL_COLUMNS as u32, 33 + 21 * CELL_ROWS as u32),
+      size: (size.width, size.height),
       resizable: false,
       ..Default::default()
     },
+    //Closing can be held back to ask first, with `confirm_abandon`.
+    exit_on_close_request: false,
     ..Default::default()
   };
-  <Game as iced::Sandbox>::run(settings)
+  <Game as iced::Application>::run(settings)
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum CellValue {
-  Mined,
-  Number(u8),
+/// Pixel size of a whole board of `columns`×`rows` at a given zoom, including the spacing after every cell.
+fn board_size((columns, rows): (usize, usize), zoom: f32, spacing: f32) -> iced::Size {
+  iced::Size::new(
+    (CELL_LENGTH * zoom + spacing) * columns as f32,
+    (CELL_LENGTH * zoom + spacing) * rows as f32,
+  )
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum CellStatus {
-  Covered,
-  Revealed,
-  Flagged,
+/// Size of the window in the system's units, fitting the board unzoomed and the header, scaled by the settings' `ui_scale`.
+/// Boards too big for [`MAX_WINDOW`] scroll instead.
+fn window_size(cells: (usize, usize), spacing: f32, ui_scale: f32) -> iced::Size<u32> {
+  let board = board_size(cells, 1.0, spacing);
+  let (width, height) = ((board.width * ui_scale).min(MAX_WINDOW.0), ((board.height + HEADER_HEIGHT) * ui_scale).min(MAX_WINDOW.1));
+  iced::Size::new(width as u32, height as u32)
 }
 
-#[derive(Clone, Copy)]
-struct Cell {
-  status: CellStatus,
-  value: CellValue,
+/// Mines not flagged yet. Won boards have all of theirs flagged.
+fn mines_left(engine: &engine::Engine) -> usize {
+  match engine.status {
+    GameStatus::Won => 0,
+    _ => engine.board.mines.saturating_sub(engine.flag_count),
+  }
 }
 
-#[derive(PartialEq)]
-enum GameStatus {
-  Playing,
-  Pressing,
-  Lost,
-  Won,
+/// Every key binding, as shown by the help overlay.
+const KEYS: [(&str, &str); 21] = [
+  ("Arrows", "Move the keyboard cursor"),
+  ("Space / Enter", "Reveal, or chord a number"),
+  ("F", "Flag or unflag"),
+  ("C", "Chord"),
+  ("H", "Hint: point at a safe move"),
+  ("Ctrl + Z", "Take back the last move"),
+  ("P", "Pause or resume"),
+  ("N / F2", "New game"),
+  ("L", "Show or hide the move log"),
+  ("S", "Arm the scanner, when it is on"),
+  ("Ctrl + wheel", "Zoom"),
+  ("Ctrl + = / - / 0", "Make everything bigger, smaller or normal"),
+  ("Middle drag", "Pan"),
+  ("F1", "Show or hide this help"),
+  ("Esc", "Dismiss a question, or go back to the board"),
+  ("F11", "Fullscreen"),
+  ("F12", "Debug overlay, in debug builds"),
+  ("`", "Console"),
+  ("Left click", "Reveal"),
+  ("Right click", "Flag"),
+  ("Middle click", "Chord"),
+];
+
+/// What each name in `mouse_bindings` does, as shown by the help overlay.
+const BINDINGS: [(&str, &str); 5] = [
+  ("hint", "Hint: point at a safe move"),
+  ("pause", "Pause or resume"),
+  ("new_game", "New game"),
+  ("log", "Show or hide the move log"),
+  ("help", "Show or hide this help"),
+];
+
+/// Shown for revealed antimines.
+const ANTIMINE: char = '⊖';
+
+/// A number as one character. Negative numbers, from antimines, are digits in a filled circle.
+fn number_content(number: i8) -> char {
+  match number {
+    -8..=-1 => char::from_u32(0x2775 + number.unsigned_abs() as u32).unwrap_or('-'),
+    _ => char::from_digit(number as u32, 10).unwrap_or('?'),
+  }
+}
+
+/// Keys which play the game, when nothing else like the console took them.
+fn board_key(key_code: keyboard::KeyCode) -> Option<Message> {
+  match key_code {
+    keyboard::KeyCode::Left => Some(Message::MoveCursor(-1, 0)),
+    keyboard::KeyCode::Right => Some(Message::MoveCursor(1, 0)),
+    keyboard::KeyCode::Up => Some(Message::MoveCursor(0, -1)),
+    keyboard::KeyCode::Down => Some(Message::MoveCursor(0, 1)),
+    keyboard::KeyCode::Space | keyboard::KeyCode::Enter => Some(Message::CursorReveal),
+    keyboard::KeyCode::F => Some(Message::CursorFlag),
+    keyboard::KeyCode::C => Some(Message::CursorChord),
+    keyboard::KeyCode::H => Some(Message::Hint),
+    keyboard::KeyCode::P => Some(Message::TogglePause),
+    keyboard::KeyCode::L => Some(Message::ToggleNarration),
+    keyboard::KeyCode::S => Some(Message::ArmScanner),
+    keyboard::KeyCode::N | keyboard::KeyCode::F2 => Some(Message::NewGame),
+    _ => None,
+  }
+}
+
+/// What a name in `mouse_bindings` does.
+fn binding(name: &str) -> Option<Message> {
+  match name {
+    "hint" => Some(Message::Hint),
+    "pause" => Some(Message::TogglePause),
+    "new_game" => Some(Message::NewGame),
+    "log" => Some(Message::ToggleNarration),
+    "help" => Some(Message::ToggleHelp),
+    _ => None,
+  }
+}
+
+fn load_replay(path: &str) -> Result<replay::Replay, String> {
+  let json = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+  replay::Replay::from_json(&json)
+}
+
+fn board_scrollable_id() -> scrollable::Id {
+  scrollable::Id::new("board")
+}
+
+fn console_scrollable_id() -> scrollable::Id {
+  scrollable::Id::new("console")
+}
+
+fn console_input_id() -> widget::text_input::Id {
+  widget::text_input::Id::new("console")
 }
 
 struct Game {
-  board: [[Cell; CELL_ROWS]; CELL_COLUMNS],
-  status: GameStatus,
-  revealed_count: usize,
-  flag_count: usize,
+  /// The board and every action played on it. Reads go to the position being played.
+  engine: history::History,
+  /// The phrase the board's seed came from, if it came from one.
+  seed_phrase: Option<String>,
+  camera: camera::Camera,
+  animations: animation::Animations,
+  audio: audio::Audio,
+  cursor: iced::Point,
+  /// Cell picked with the arrow keys, once they've been used.
+  keyboard_cursor: Option<(usize, usize)>,
+  /// A safe move the solver pointed out, until the next move.
+  hint: Option<solver::Move>,
+  hints_used: usize,
+  /// A question waiting on an answer.
+  modal: Option<modal::Modal>,
+  toasts: toast::Toasts,
+  /// Moves which asked to be confirmed.
+  warnings: usize,
+  /// Reveals which weren't certainly safe, when something else was.
+  guesses: usize,
+  /// Reveals made when nothing was certainly safe.
+  forced_guesses: usize,
+  /// The mine which lost the game.
+  fatal: Option<(usize, usize)>,
+  /// Moves taken back, counting time travel in debug builds.
+  undos: usize,
+  last_hint: Option<time::Instant>,
+  /// The cell a mouse button is held down on, and the quad board it is on, or `None` for the main board.
+  pressed: Option<(Option<usize>, (usize, usize))>,
+  /// Cached drawings of the main board, drawn again only where it changes.
+  tiles: board::Tiles,
+  /// What the main board was last drawn with besides its cells.
+  board_look: Option<BoardLook>,
+  /// Four boards being played instead of the main one.
+  quad: Option<quad::Quad>,
+  drills: drill::Drills,
+  screen: screen::Screen,
+  modifiers: keyboard::Modifiers,
+  settings: settings::Settings,
+  /// The avatar's picture, when the avatar is an image.
+  avatar: Option<face::Image>,
+  last_input: time::Instant,
+  demo: Option<Demo>,
+  theme: theme::Theme,
+  theme_modified: Option<time::SystemTime>,
+  /// Whether the built in dark theme is wanted, when no theme file pins one.
+  dark: bool,
+  debug_overlay: bool,
+  console: console::Console,
+  narration: narration::Narration,
+  clock: clock::Clock,
+  /// Game clock milliseconds when each move in the engine's log was played.
+  move_times: Vec<u64>,
+  /// Countdown mode's limit for the current board.
+  time_limit: Option<time::Duration>,
+  streak: u32,
+  /// Best time in the game's category when it started or last finished, so redraws don't go through every record.
+  best_time: Option<f32>,
+  /// Score of the last finished game, and all of this session's scores added up.
+  score: Option<u64>,
+  total_score: u64,
+  combo: combo::Combo,
+  /// Whether the computer was suspended during the game, pausing it.
+  suspended: bool,
+  /// Whether the clock was paused by the window losing focus, so getting it back only undoes that pause.
+  paused_by_focus: bool,
+  scanner: scanner::Scanner,
+  /// Players taking turns, in hot seat games.
+  hot_seat: Option<hotseat::HotSeat>,
+  session: session::Session,
+  /// The tournament seed pack being played, and the games played on its boards so far.
+  pack: Option<(pack::Pack, pack::Results)>,
+  /// Whether the board is the pack's next one, so its game goes in the results.
+  pack_board: bool,
+  daily: daily::Daily,
+  /// Which day's challenge is being played, if any.
+  daily_day: Option<i64>,
+  /// The day it was when last checked, to notice a new daily challenge.
+  today: i64,
+  stats: stats::Stats,
+  /// Only games with a tag containing this are listed under the charts.
+  history_filter: String,
+  /// Index into the stats of the game whose tags and note are being edited.
+  history_game: Option<usize>,
+  tag_input: String,
+  overlay: Option<overlay::Overlay>,
 }
 
-fn with_surrounding_cells<F>(x: usize, y: usize, mut f: F) where F: FnMut(usize, usize) {
-  let first_y = y == 0;
-  let last_y = y == CELL_ROWS - 1;
-  let first_x = x == 0;
-  let last_x = x == CELL_COLUMNS - 1;
-  
-  if !first_x && !first_y { f(x - 1, y - 1) }
-  if !first_x { f(x - 1, y) }
-  if !first_y { f(x, y - 1) }
-  if !last_x && !last_y { f(x + 1, y + 1) }
-  if !last_x { f(x + 1, y) }
-  if !last_y { f(x, y + 1) }
-  if !first_x && !last_y { f(x - 1, y + 1) }
-  if !last_x && !first_y { f(x + 1, y - 1) }
+/// Everything besides the cells themselves which changes how the main board is drawn.
+#[derive(PartialEq)]
+struct BoardLook {
+  palette: iced::theme::Palette,
+  style: cell::Style,
+  numbers: [iced::Color; 8],
+  dual_coding: bool,
+  debug_overlay: bool,
+  fatal: Option<(usize, usize)>,
+  scans: usize,
+}
+
+/// The solver playing by itself after the new game screen was left idle.
+struct Demo {
+  last_move: Option<solver::Move>,
+  end_pause: u8,
 }
 
 impl Game {
-  fn add_mines(&mut self) {
-    use rand::seq::SliceRandom;
-    let mut rng = rand::thread_rng();
-    
-    // Create a Vec of all possible positions.
-    let mut positions = Vec::new();
-    for y in 0..CELL_ROWS {
-      for x in 0..CELL_COLUMNS {
-        positions.push((x, y));
-      }
+  /// Start over on the board generated from `seed`.
+  fn reset(&mut self, seed: u64) {
+    self.reset_transformed(seed, engine::Transform::None);
+  }
+
+  /// Start over on the board generated from `seed`, with its mines moved around by `transform`.
+  fn reset_transformed(&mut self, seed: u64, transform: engine::Transform) {
+    self.abandon();
+    //Versus goes on until the board is cleared, so mines only cost points.
+    let versus = self.hot_seat.as_ref().is_some_and(|seat| seat.mode == hotseat::Mode::Versus);
+    let config = self.settings.game_config(seed).with_transform(transform);
+    self.engine = history::History::new(config.with_lives(if versus { config.mines } else { 0 }).build());
+    self.redraw_board();
+    self.seed_phrase = None;
+    self.quad = None;
+    self.hint = None;
+    self.hints_used = 0;
+    self.undos = 0;
+    self.modal = None;
+    self.warnings = 0;
+    self.guesses = 0;
+    self.forced_guesses = 0;
+    self.fatal = None;
+    self.last_hint = None;
+    self.narration.lines.clear();
+    self.clock = clock::Clock::default();
+    self.move_times.clear();
+    self.score = None;
+    self.combo = combo::Combo::default();
+    self.suspended = false;
+    self.paused_by_focus = false;
+    self.scanner = scanner::Scanner::default();
+    self.hot_seat = self.hot_seat.as_ref().map(hotseat::HotSeat::restart);
+    self.daily_day = None;
+    self.pack_board = false;
+    self.time_limit = self.settings.time_limit(self.engine.three_bv());
+    self.best_time = self.stats.best_time(&self.category());
+    self.update_overlay();
+  }
+
+  /// Start over on the seed pack's next board, the next pasted seed, or a random board with a phrase for its seed when none are left.
+  fn new_game(&mut self) {
+    if let Some(board) = self.pack.as_ref().and_then(|(pack, results)| pack.next(results)).cloned() {
+      self.reset(board.config.seed);
+      self.engine = history::History::from_replay(&board);
+      self.redraw_board();
+      self.time_limit = self.settings.time_limit(self.engine.three_bv());
+      self.best_time = self.stats.best_time(&self.category());
+      self.pack_board = true;
+      return;
     }
-    
-    // Shuffle the Vec of positions.
-    positions.shuffle(&mut rng);
-    
-    // Mine some positions.
-    for &(x, y) in positions.iter().take(MINE_COUNT) {
-      self.board[x][y].value = CellValue::Mined;
+    let text = self.session.queue.pop_front().unwrap_or_else(seed::random_phrase);
+    self.reset(seed::parse(&text));
+    self.seed_phrase = seed::phrase(&text);
+  }
+
+  fn play_daily(&mut self, day: i64) {
+    self.reset(daily::seed(day));
+    self.daily_day = Some(day);
+    self.screen = screen::Screen::Game;
+  }
+
+  /// A game which has been started and isn't over yet, played by the player.
+  fn in_progress(&self) -> bool {
+    self.demo.is_none() && self.quad.is_none() && !self.engine.is_fresh() && !self.engine.is_over()
+  }
+
+  /// Ends the game in progress without a result, keeping it in the stats as abandoned.
+  fn abandon(&mut self) {
+    if !self.in_progress() {
+      return;
+    }
+    //Before losing, which the replay wouldn't play back.
+    self.record_pack_game();
+    self.engine.lose();
+    self.tiles.clear(self.engine.changed());
+    self.clock.stop();
+    if self.settings.abandoned_are_losses {
+      self.streak = 0;
     }
+    self.stats.games.push(stats::Record {
+      category: self.category(),
+      seed: self.engine.seed,
+      won: false,
+      seconds: self.clock.elapsed().as_secs_f32(),
+      three_bv: self.engine.three_bv(),
+      guesses: self.guesses,
+      forced_guesses: self.forced_guesses,
+      tags: self.tags(),
+      note: String::new(),
+      abandoned: true,
+    });
+    self.stats.territory += scoring::territory(&self.engine);
+    let _ = self.stats.save();
   }
-  
-  fn add_numbers(&mut self) {
-    for y in 0..CELL_ROWS {
-      for x in 0..CELL_COLUMNS {
-        if self.board[x][y].value == CellValue::Mined {
-          continue;
-        }
-        //Count up all bombs at sides and corners
-        let mut count = 0;
-        with_surrounding_cells(x, y, |new_x, new_y| {
-          if self.board[new_x][new_y].value == CellValue::Mined {
-            count += 1;
-          }
-        });
-        self.board[x][y].value = CellValue::Number(count);
-      }
+
+  /// Leaves the current game, asking first when it is still going and the player wants to be asked.
+  fn leave(&mut self, abandon: modal::Abandon) -> iced::Command<Message> {
+    if self.settings.confirm_abandon && self.in_progress() {
+      self.modal = Some(modal::Modal::Abandon(abandon));
+      return iced::Command::none();
     }
+    self.go(abandon)
+  }
+
+  fn go(&mut self, abandon: modal::Abandon) -> iced::Command<Message> {
+    self.modal = None;
+    match abandon {
+      modal::Abandon::NewGame => self.new_game(),
+      modal::Abandon::Daily(day) => self.play_daily(day),
+      modal::Abandon::Retry(index) => {
+        let text = self.session.seeds[index].clone();
+        self.reset(seed::parse(&text));
+        self.seed_phrase = seed::phrase(&text);
+        self.screen = screen::Screen::Game;
+      },
+      modal::Abandon::Close => {
+        self.abandon();
+        return window::close();
+      },
+    }
+    iced::Command::none()
+  }
+
+  /// Seed of the current board, as its phrase when there is one.
+  fn seed_text(&self) -> String {
+    self.seed_phrase.clone().unwrap_or_else(|| self.engine.seed.to_string())
   }
-  
-  fn reveal_multiple(&mut self, x: usize, y: usize) {
-    let mut reveal_vec = vec![(x, y)];
-    
-    while let Some(cell) = reveal_vec.pop() {
-      let x = cell.0;
-      let y = cell.1;
-
-      //Only reveal cells which haven't been revealed. Else we will be counting too many.
-      if self.board[x][y].status != CellStatus::Covered {
-        continue;
-      }
 
-      self.board[x][y].status = CellStatus::Revealed;
+  /// A mine the numbers prove is under a cell `action` would reveal, when the player asked to be warned about those.
+  fn fatal_cell(&self, action: Action) -> Option<(usize, usize)> {
+    if !self.settings.confirm_fatal_moves || self.daily_day == Some(daily::today()) || self.quad.is_some() || self.engine.is_over() || self.engine.antimines > 0 {
+      return None;
+    }
+    let board = &self.engine.board;
+    let revealed: Vec<(usize, usize)> = match action {
+      Action::Reveal(x, y) if board[x][y].status == CellStatus::Covered => vec![(x, y)],
+      //Chording a number with all its flags reveals the rest of its neighbors.
+      Action::Chord(x, y) if board[x][y].status == CellStatus::Revealed && board[x][y].value.number().is_some_and(|number| {
+        self.engine.neighbors.of(board, x, y).filter(|&(x, y)| board[x][y].is_known_mine()).count() == number as usize
+      }) => self.engine.neighbors.of(board, x, y).filter(|&(x, y)| board[x][y].status == CellStatus::Covered).collect(),
+      _ => Vec::new(),
+    };
+    let known = self.settings.solver.solver().known(board, self.engine.neighbors);
+    revealed.into_iter().find(|&(x, y)| known[x][y] == Some(true))
+  }
+
+  /// Play a move from the player, unless it needs confirming first.
+  fn play(&mut self, action: Action) {
+    match self.fatal_cell(action) {
+      Some(mine) => {
+        self.modal = Some(modal::Modal::FatalMove(action, mine));
+        self.warnings += 1;
+      },
+      None => self.act(action),
+    }
+  }
 
-      if self.board[x][y].value == CellValue::Mined {
-        self.board[x][y].status = CellStatus::Revealed;
-        self.status = GameStatus::Lost;
-        return;
+  /// Play an action, keeping track of the time and win streak. Finished games are left alone.
+  fn act(&mut self, action: Action) {
+    if matches!(self.modal, Some(modal::Modal::FatalMove(..))) {
+      self.modal = None;
+    }
+    //The main board is hidden behind the quad boards.
+    if self.quad.is_some() || self.engine.is_over() {
+      return;
+    }
+    let (revealed_count, flag_count, treasures_found, position) = (self.engine.revealed_count, self.engine.flag_count, self.engine.treasures_found, self.engine.position());
+    let before = self.engine.board.clone();
+    //The first click is always a guess, so only the ones after it are judged.
+    if let (Action::Reveal(x, y), true) = (action, revealed_count > 0 && self.engine.antimines == 0) {
+      if self.engine.board[x][y].status == CellStatus::Covered {
+        match solver::judge(&self.engine.board, self.engine.neighbors, x, y) {
+          solver::Judgement::Certain => (),
+          solver::Judgement::Guess => self.guesses += 1,
+          solver::Judgement::ForcedGuess => self.forced_guesses += 1,
+        }
+      }
+    }
+    self.engine.act(action);
+    self.tiles.clear(self.engine.changed());
+    if position == 0 && self.engine.position() > 0 {
+      self.session.played(self.seed_text());
+    }
+    if self.engine.status == GameStatus::Lost {
+      self.fatal = self.engine.board.positions()
+        .find(|&(x, y)| before[x][y].status != CellStatus::Revealed && self.engine.board[x][y].status == CellStatus::Revealed && self.engine.board[x][y].value.is_mine());
+    }
+    if let Action::Flag(x, y) = action {
+      match self.engine.flag_count.cmp(&flag_count) {
+        std::cmp::Ordering::Greater => self.animations.start(x, y, animation::Kind::FlagPlaced),
+        std::cmp::Ordering::Less => self.animations.start(x, y, animation::Kind::FlagRemoved),
+        std::cmp::Ordering::Equal => (),
+      }
+    }
+    if let Action::Chord(x, y) = action {
+      for (flag_x, flag_y) in self.engine.excess_flags(x, y) {
+        self.animations.start(flag_x, flag_y, animation::Kind::TooManyFlags);
+      }
+    }
+    self.hint = None;
+    self.narration.record(action, revealed_count, flag_count, &self.engine);
+    let opened = self.engine.revealed_count - revealed_count;
+    if let Some(seat) = &mut self.hot_seat {
+      let (x, y) = action.position();
+      let flagged = matches!(action, Action::Flag(..)) && before[x][y].status != self.engine.board[x][y].status;
+      let mines = self.engine.board.positions()
+        .filter(|&(x, y)| before[x][y].status != CellStatus::Revealed && self.engine.board[x][y].status == CellStatus::Revealed && self.engine.board[x][y].value.is_mine())
+        .count();
+      if opened > 0 || flagged || mines > 0 {
+        seat.played(opened, flagged, mines);
+      }
+    }
+    if opened > 0 && self.engine.status != GameStatus::Lost {
+      let (x, y) = action.position();
+      let sound = match self.engine.board[x][y].value {
+        _ if opened >= self.settings.opening_sound_cells => self.settings.opening_sounds.then_some(audio::Sound::Opening),
+        CellValue::Number(number) | CellValue::Treasure(number) if number != 0 => self.settings.reveal_sounds.then_some(audio::Sound::Number(number.unsigned_abs())),
+        _ => None,
+      };
+      if let Some(sound) = sound {
+        self.audio.play(sound, self.settings.effects_volume());
+      }
+    }
+    if self.settings.scanner {
+      self.scanner.opened(opened);
+    }
+    if self.settings.combo {
+      if self.engine.revealed_count > revealed_count {
+        self.combo.hit();
+      } else if !matches!(action, Action::Flag(..)) || self.engine.flag_count < flag_count {
+        self.combo.miss();
       }
+    }
+    if self.engine.revealed_count > 0 {
+      self.clock.start();
+    }
+    if self.engine.treasure_reward == engine::Reward::Time {
+      let found = (self.engine.treasures_found - treasures_found) as u64;
+      self.clock.bonus(time::Duration::from_secs(found * self.settings.treasure_seconds));
+    }
+    if self.engine.position() > position {
+      //Times of moves stepped back over go with them.
+      self.move_times.truncate(position);
+      self.move_times.push(self.clock.elapsed().as_millis() as u64);
+    }
+    if self.engine.status == GameStatus::Won {
+      self.flag_the_rest(action.position());
+    }
+    if self.engine.is_over() {
+      self.finish();
+    }
+    self.update_overlay();
+  }
 
-      self.revealed_count += 1;
-      if self.revealed_count >= CELL_ROWS * CELL_COLUMNS - MINE_COUNT {
-        //All numbers were revealed
-        self.status = GameStatus::Won;
-        return;
+  /// Pop flags onto the mines left covered after a win, spreading out from the winning move.
+  fn flag_the_rest(&mut self, (from_x, from_y): (usize, usize)) {
+    for (x, y) in self.engine.board.positions() {
+      if self.engine.board[x][y].status == CellStatus::Covered && self.engine.board[x][y].value.is_mine() {
+        let distance = x.abs_diff(from_x).max(y.abs_diff(from_y)) as u32;
+        self.animations.start_after(x, y, animation::Kind::FlagPlaced, FLAG_THE_REST_STEP * distance);
       }
-      
-      //Clicked on a blank piece? Reveal all sides and corners.
-      if self.board[x][y].value == CellValue::Number(0) {
-        with_surrounding_cells(x, y, |new_x, new_y| {
-          if self.board[new_x][new_y].status == CellStatus::Covered {
-            reveal_vec.push((new_x, new_y));
-          }
-        });
+    }
+  }
+
+  /// Stop the clock and tally up the game which just ended.
+  fn finish(&mut self) {
+    self.clock.stop();
+    //The demo playing doesn't count towards the streak or score.
+    if self.demo.is_some() {
+      return;
+    }
+    self.streak = if self.engine.status == GameStatus::Won { self.streak + 1 } else { 0 };
+    self.record_pack_game();
+    let seconds = self.clock.elapsed().as_secs_f32();
+    let category = self.category();
+    //The first win of a kind is its first record too.
+    let best = self.stats.best_time(&category);
+    if self.engine.status == GameStatus::Won && best.is_none_or(|best| seconds < best) {
+      self.toasts.push(format!("New best time! {}", self.settings.time(self.clock.elapsed())));
+      if self.settings.notifications {
+        notify::send(String::from("New personal best"), format!("{:.1} seconds on {}", seconds, category));
       }
     }
+    self.stats.games.push(stats::Record {
+      category,
+      seed: self.engine.seed,
+      won: self.engine.status == GameStatus::Won,
+      seconds,
+      three_bv: self.engine.three_bv(),
+      guesses: self.guesses,
+      forced_guesses: self.forced_guesses,
+      tags: self.tags(),
+      note: String::new(),
+      abandoned: false,
+    });
+    self.best_time = self.stats.best_time(&self.category());
+    self.stats.territory += scoring::territory(&self.engine);
+    self.narration.print(self.guess_summary());
+    let _ = self.stats.save();
+    //Past days can be replayed, but only winning on the day counts.
+    if self.engine.status == GameStatus::Won && self.daily_day == Some(daily::today()) {
+      self.daily.completed.insert(daily::today());
+      let _ = self.daily.save();
+      self.toasts.push("Daily challenge won");
+    }
+    let combo_multiplier = if self.settings.combo { self.combo.best_multiplier() } else { 1.0 };
+    let score = scoring::score(&self.engine, self.clock.elapsed(), combo_multiplier);
+    self.score = Some(score);
+    self.total_score += score;
   }
-  
-  fn reveal_special(&mut self, x: usize, y: usize) {
-    //This feature should only work if the current cell is already revealed. Otherwise the user is cheating.
-    if self.board[x][y].status != CellStatus::Revealed {
+
+  /// Adds the game to the seed pack's results, when it was played on the pack's next board.
+  fn record_pack_game(&mut self) {
+    if !std::mem::take(&mut self.pack_board) {
       return;
     }
+    let game = self.record();
+    let Some((pack, results)) = &mut self.pack else { return };
+    results.add(game);
+    let line = match pack.next(results) {
+      Some(_) => format!("Board {} of {} played, a new game starts the next", results.games.len(), pack.boards.len()),
+      None => format!("Every board of {} is played. pack results FILE in the console saves the results to send in", pack.name),
+    };
+    self.console.print(line);
+    self.toasts.push(format!("Pack board {} of {} played", results.games.len(), pack.boards.len()));
+  }
 
-    if let CellValue::Number(cell_number) = self.board[x][y].value {
-      let mut flag_count = 0;
-      with_surrounding_cells(x, y, |new_x, new_y| {
-        if self.board[new_x][new_y].status == CellStatus::Flagged {
-          flag_count += 1;
-        }
+  /// Tags the game starts with in the stats.
+  fn tags(&self) -> Vec<String> {
+    if self.suspended { vec![String::from("suspended")] } else { Vec::new() }
+  }
+
+  /// The category the game goes under in the stats.
+  fn category(&self) -> stats::Category {
+    let assists = stats::Assists {hints: self.hints_used, undos: self.undos, warnings: self.warnings};
+    stats::Category::of(&self.engine, &self.settings, assists, self.hot_seat.as_ref().map(|seat| seat.mode))
+  }
+
+  /// How many reveals of the game were guesses, and how many of those had to be.
+  fn guess_summary(&self) -> String {
+    match (self.guesses, self.forced_guesses) {
+      (0, 0) => String::from("No guesses after the first click"),
+      (guesses, 0) => format!("{} guesses, none of them forced", guesses),
+      (0, forced) => format!("{} forced guesses, and no others", forced),
+      (guesses, forced) => format!("{} guesses, and {} forced guesses", guesses, forced),
+    }
+  }
+
+  fn update_overlay(&self) {
+    if let Some(overlay) = &self.overlay {
+      overlay.update(overlay::Stats {
+        clock: self.clock,
+        mines_left: mines_left(&self.engine),
+        solved_three_bv: self.engine.solved_three_bv(),
+        streak: self.streak,
+        board: overlay::visible_rows(&self.engine.board),
       });
-      
-      //Flag count matches the cell number. Reveal the neighbors.
-      if flag_count == cell_number {
-        with_surrounding_cells(x, y, |new_x, new_y| {
-          if self.board[new_x][new_y].status == CellStatus::Covered {
-            self.reveal_multiple(new_x, new_y);
-          }
-        })
+    }
+  }
+
+  fn demo_step(&mut self) {
+    let Some(demo) = &mut self.demo else { return };
+    if self.engine.is_over() {
+      demo.end_pause += 1;
+      if demo.end_pause >= DEMO_END_PAUSE {
+        self.demo = Some(Demo {last_move: None, end_pause: 0});
+        self.new_game();
       }
+      return;
+    }
+    let Some(step) = self.settings.solver.solver().next_move(&self.engine.board, self.engine.neighbors) else { return };
+    demo.last_move = Some(step);
+    let before = self.engine.state_hash();
+    self.act(step.action);
+    //Out of flags in the limited flags challenge, the solver has nothing else to try.
+    if self.engine.state_hash() == before {
+      self.new_game();
     }
+  }
 
+  /// Keeps the best score once a puzzle rush runs out of time or strikes.
+  fn end_rush(&mut self) {
+    let screen::Screen::Rush(rush) = &mut self.screen else { return };
+    if rush.end() && rush.solved > self.drills.best_rush {
+      self.drills.best_rush = rush.solved;
+      let _ = self.drills.save();
+    }
   }
-}
 
-fn text_color(number: u8) -> iced::Color {
-  match number {
-    1 => iced::Color::new(0.0, 0.0, 1.0, 0.0),  //Blue
-    2 => iced::Color::new(0.0, 0.5, 0.0, 0.0),  //Green
-    3 => iced::Color::new(1.0, 0.0, 0.0, 0.0),  //Red
-    4 => iced::Color::new(0.0, 0.0, 0.5, 0.0),  //Dark blue
-    5 => iced::Color::new(0.5, 0.0, 0.0, 0.0),  //Dark red
-    6 => iced::Color::new(0.0, 0.5, 0.5, 0.0),  //Cyan
-    7 => iced::Color::new(0.0, 0.0, 0.0, 0.0),  //Black
-    8 => iced::Color::new(0.5, 0.5, 0.5, 0.0),  //Grey
-    _ => iced::Color::new(1.0, 1.0, 1.0, 0.0),  //White
+  /// Columns and rows of the board being played.
+  fn board_cells(&self) -> (usize, usize) {
+    (self.engine.board.width, self.engine.board.height)
   }
-}
 
-#[derive(Clone, Copy, Debug)]
-enum Message {
-  NewGame,
-  Pressing(bool),
-  Reveal(usize, usize),
-  SpecialReveal(usize, usize),
-  Flag(usize, usize),
-}
+  /// Resizes the window to fit boards of the size picked in the settings, at the current spacing and UI scale. Fullscreen is left alone.
+  /// Boards of other sizes, like from links and packs, scroll or sit in the middle instead.
+  fn fit_window(&self) -> iced::Command<Message> {
+    if self.settings.fullscreen {
+      return iced::Command::none();
+    }
+    window::resize(window_size((self.settings.board_width, self.settings.board_height), self.theme.cell_spacing(), self.settings.ui_scale()))
+  }
 
-impl iced::Sandbox for Game {
-  type Message = Message;
+  /// Play every move the solver is sure about. Returns how many moves were played.
+  fn solve(&mut self) -> usize {
+    let mut moves = 0;
+    //The solver can't read numbers with antimines around them.
+    while self.engine.status == GameStatus::Playing && self.engine.antimines == 0 {
+      match self.settings.solver.solver().next_move(&self.engine.board, self.engine.neighbors) {
+        Some(step) if step.reason != solver::Reason::Guess => {
+          let before = self.engine.state_hash();
+          self.act(step.action);
+          //Out of flags in the limited flags challenge, the solver has nothing else to try.
+          if self.engine.state_hash() == before {
+            break;
+          }
+        },
+        _ => break,
+      }
+      moves += 1;
+    }
+    moves
+  }
 
-  fn new() -> Self {
-    let mut game = Game {
-      board: [[Cell {status: CellStatus::Covered, value: CellValue::Number(0)}; CELL_ROWS]; CELL_COLUMNS],
-      status: GameStatus::Playing,
-      revealed_count: 0,
-      flag_count: 0,
-    };
-    game.add_mines();
-    game.add_numbers();
-    
-    game
+  /// The current game as a replay, with the timing of its moves.
+  fn record(&self) -> replay::Replay {
+    replay::Replay {times: self.move_times.iter().take(self.engine.position()).copied().collect(), ..replay::Replay::record(&self.engine)}
   }
 
-  fn title(&self) -> String {
-    match self.status {
-      GameStatus::Won => String::from("Minesweeper - You Won"),
-      GameStatus::Lost => String::from("Minesweeper - You Lost"),
-      _ => String::from("Minesweeper"),
+  fn bug_report(&self) -> report::Report<'_> {
+    report::Report {
+      version: env!("CARGO_PKG_VERSION"),
+      status: format!("{:?}", self.engine.status),
+      game: self.record(),
+      settings: &self.settings,
     }
   }
-  
-  fn theme(&self) -> theme::Theme {
-    theme::Theme::custom(theme::Palette {
-      background: iced::Color::from_rgb(0.9, 0.9, 0.9),
-      text: iced::Color::BLACK,
-      primary: iced::Color::from_rgb(0.36, 0.48, 0.88),
-      success: iced::Color::from_rgb(0.07, 0.4, 0.31),
-      danger: iced::Color::from_rgb(0.76, 0.26, 0.25),
-    })
+
+  /// Save a bug report, say where it went in the console, and copy it to the clipboard.
+  fn report(&mut self) -> iced::Command<Message> {
+    let report = self.bug_report();
+    let json = report.to_json();
+    let line = match report.save() {
+      Ok(path) => format!("Bug report saved to {} and copied to the clipboard", path.display()),
+      Err(error) => format!("Bug report copied to the clipboard, but couldn't be saved: {}", error),
+    };
+    self.console.print(line);
+    self.toasts.push("Bug report copied");
+    iced::clipboard::write(json)
   }
 
-  fn update(&mut self, message: Message) {
-    match message {
-      Message::NewGame => *self = Game::new(),
-      Message::Pressing(true) => self.status = GameStatus::Pressing,
-      Message::Pressing(false) => self.status = GameStatus::Playing,
-      Message::Reveal(x, y) => {
-        self.reveal_multiple(x, y);
+  /// Copy a link to the current position, and show it in the console.
+  fn copy_link(&mut self) -> iced::Command<Message> {
+    let link = link::link(&self.engine);
+    self.console.print(format!("Position {:016x} copied to the clipboard: {}", self.engine.position_hash(), link));
+    self.toasts.push("Link copied");
+    iced::clipboard::write(link)
+  }
+
+  fn run_command(&mut self, line: &str) -> iced::Command<Message> {
+    self.console.print(format!("> {}", line));
+    match console::parse(line) {
+      //Boards come in different sizes, so moves are only checked against the one being played.
+      Ok(console::Command::Act(action)) if !self.engine.board.contains(action.position().0, action.position().1) => {
+        self.console.print(format!("{} {} is off the board", action.position().0, action.position().1));
       },
-      Message::SpecialReveal(x, y) => {
-        self.reveal_special(x, y);
+      Ok(console::Command::Act(action)) => self.act(action),
+      Ok(console::Command::Seed(text)) => {
+        self.reset(seed::parse(&text));
+        self.seed_phrase = seed::phrase(&text);
       },
-      Message::Flag(x, y) => {
-        if self.status != GameStatus::Playing {
-          return;
-        }
-        
-        match self.board[x][y].status {
-          CellStatus::Covered => {
-            if MINE_COUNT == self.flag_count {
-              //Too many flags! Don't add an extra flag. (Else MNE_COUNT - self.flag_count < 0, which will cause an exception because they are unsigned.)
-              return;
+      Ok(console::Command::Transform(transform)) => {
+        let phrase = self.seed_phrase.take();
+        self.reset_transformed(self.engine.seed, transform);
+        self.seed_phrase = phrase;
+        self.console.print(format!("Started {} over with {:?}", self.seed_text(), transform));
+      },
+      Ok(console::Command::Solve) => {
+        let moves = self.solve();
+        self.console.print(format!("Played {} moves", moves));
+      },
+      Ok(console::Command::Quad) => {
+        self.new_game();
+        self.quad = Some(quad::Quad::new(self.engine.seed));
+      },
+      Ok(console::Command::Drill) => self.screen = screen::Screen::Drill(drill::Drill::new(self.drills.next(None))),
+      Ok(console::Command::Rush) => self.screen = screen::Screen::Rush(rush::Rush::new()),
+      Ok(console::Command::Dump) => {
+        self.console.print(format!("Seed {} ({}) {:?} revealed {} flags {}", self.seed_text(), self.engine.seed, self.engine.status, self.engine.revealed_count, self.engine.flag_count));
+        for line in self.engine.dump() {
+          self.console.print(line);
+        }
+        self.console.print(format!("Coin flips: {}", self.engine.fifty_fifties().len()));
+      },
+      Ok(console::Command::Replay(path)) => {
+        let result = load_replay(&path).and_then(|replay| replay.run().map(|_| replay));
+        match result {
+          Ok(replay) => {
+            self.console.print(format!("Replayed {} moves, the final state matches", replay.moves.len()));
+            for suspicion in plausibility::check(&replay) {
+              self.console.print(format!("Suspicious: {}", suspicion));
             }
-            self.board[x][y].status = CellStatus::Flagged;
-            self.flag_count += 1;
+            self.abandon();
+            self.engine = history::History::from_replay(&replay);
+            self.redraw_board();
+            self.move_times = replay.times;
+            self.best_time = self.stats.best_time(&self.category());
           },
-          CellStatus::Flagged => {
-            self.board[x][y].status = CellStatus::Covered;
-            self.flag_count -= 1;
+          Err(error) => self.console.print(error),
+        }
+      },
+      Ok(console::Command::Compare(first, second)) => {
+        let result = load_replay(&first).and_then(|first_replay| {
+          let second_replay = load_replay(&second)?;
+          compare::Comparison::new((first, first_replay), (second, second_replay))
+        });
+        match result {
+          Ok(comparison) => {
+            self.console.print(comparison.describe());
+            for run in &comparison.runs {
+              for suspicion in plausibility::check(&run.replay) {
+                self.console.print(format!("Suspicious {}: {}", run.name, suspicion));
+              }
+            }
+            self.screen = screen::Screen::Comparison(Box::new(comparison));
           },
-          CellStatus::Revealed => (), //If it's already revealed, it can't be flagged.
+          Err(error) => self.console.print(error),
+        }
+      },
+      Ok(console::Command::Link) => return self.copy_link(),
+      Ok(console::Command::Open(text)) => match link::open(&text) {
+        Ok(engine) => {
+          self.reset(engine.seed);
+          self.time_limit = self.settings.time_limit(engine.three_bv());
+          self.console.print(format!("Opened the position after {} moves", engine.moves.len()));
+          self.engine = history::History::from_replay(&replay::Replay::record(&engine));
+          self.redraw_board();
+          self.best_time = self.stats.best_time(&self.category());
+        },
+        Err(error) => self.console.print(error),
+      },
+      Ok(console::Command::PlayPack(path)) => match std::fs::read_to_string(&path).map_err(|error| error.to_string()).and_then(|json| pack::Pack::from_json(&json)) {
+        Ok(pack) => {
+          self.console.print(format!("{}, {} boards, signed by the key {}. Check it is the one the organizer announced", pack.name, pack.boards.len(), pack.key));
+          let results = pack::Results::new(&pack, &self.settings.player_name);
+          self.pack = Some((pack, results));
+          self.new_game();
+        },
+        Err(error) => self.console.print(format!("Couldn't load the pack: {}", error)),
+      },
+      Ok(console::Command::SavePackResults(path)) => match &mut self.pack {
+        Some((pack, results)) => {
+          let key = settings::config_path(PLAYER_KEY).ok_or_else(|| String::from("There is no config directory for the player's key")).and_then(|key| pack::load_or_create_key(&key));
+          let saved = key.and_then(|(key, _)| {
+            results.sign(&key);
+            std::fs::write(&path, results.to_json()).map_err(|error| error.to_string())
+          });
+          match saved {
+            Ok(()) => self.console.print(format!("Saved {} to {}, signed by your key {}. Give the organizer this key, so they can tell your results are yours", results.describe(pack), path, results.key)),
+            Err(error) => self.console.print(format!("Couldn't save the results: {}", error)),
+          }
+        },
+        None => self.console.print("No seed pack is being played"),
+      },
+      Ok(console::Command::Export(path)) => {
+        let replay = self.record();
+        match export::save(&replay, std::path::Path::new(&path), self.settings.export_cell_size, self.settings.export_frame_ms, &self.theme, &self.settings) {
+          Ok(()) => self.console.print(format!("Exported {} moves to {}", replay.moves.len(), path)),
+          Err(error) => self.console.print(format!("Couldn't export: {}", error)),
+        }
+      },
+      Ok(console::Command::ExportProfile(path)) => {
+        let profile = profile::Profile {
+          settings: settings::Settings::load(),
+          stats: stats::Stats::load(),
+          daily: daily::Daily::load(),
+          drills: drill::Drills::load(),
+          avatar_image: self.avatar.clone(),
         };
-        
+        match profile.save(std::path::Path::new(&path)) {
+          Ok(()) => self.console.print(format!("Exported {} games to {}", profile.stats.games.len(), path)),
+          Err(error) => self.console.print(format!("Couldn't export: {}", error)),
+        }
+      },
+      Ok(console::Command::ImportProfile(path)) => match profile::Profile::load(std::path::Path::new(&path)) {
+        Ok(imported) => {
+          let mut profile = profile::Profile {
+            settings: std::mem::take(&mut self.settings),
+            stats: std::mem::take(&mut self.stats),
+            daily: std::mem::take(&mut self.daily),
+            drills: std::mem::take(&mut self.drills),
+            avatar_image: None,
+          };
+          let merged = profile.merge(imported);
+          (self.settings, self.stats, self.daily, self.drills) = (profile.settings, profile.stats, profile.daily, profile.drills);
+          //The picture goes next to the settings, under its own name only, wherever it was on the other machine.
+          let picture = profile.avatar_image.and_then(|image| {
+            let name = std::path::Path::new(&self.settings.avatar).file_name()?.to_str()?.to_string();
+            Some((settings::config_path(&name)?, name, image))
+          });
+          if let Some((_, name, image)) = &picture {
+            self.settings.avatar = name.clone();
+            self.avatar = Some(image.clone());
+          }
+          //After the settings, which make the config folder when there isn't one yet.
+          let saved = self.settings.save().and(self.stats.save()).and(self.daily.save()).and(self.drills.save())
+            .and_then(|()| picture.map_or(Ok(()), |(path, _, image)| image.save(&path)));
+          self.console.print(match saved {
+            Ok(()) => format!("Added {} games, {} daily challenges and {} drill records", merged.games, merged.days, merged.drills),
+            Err(error) => format!("Merged, but couldn't save: {}", error),
+          });
+        },
+        Err(error) => self.console.print(format!("Couldn't import: {}", error)),
+      },
+      Ok(console::Command::Report) => return self.report(),
+      Ok(console::Command::Help) => {
+        self.console.print(console::HELP);
+        if cfg!(debug_assertions) {
+          self.console.print(console::DEBUG_HELP);
+        }
       },
+      Err(error) => self.console.print(error),
     }
+    iced::Command::none()
   }
+}
 
-  fn view(&self) -> iced::Element<Message> {
-    let mut column = widget::Column::new().spacing(1);
-    let face = match self.status {
-      GameStatus::Playing => '😀',
-      GameStatus::Pressing => '😮',
-      GameStatus::Lost => '☹',
-      GameStatus::Won => '😎',
-    };
-    let mut top_row = widget::Row::new().padding(2);
-    top_row = top_row.push(widget::Text::new(format!("Mines: {}", MINE_COUNT - self.flag_count)).size(20));
-    top_row = top_row.push(widget::Space::with_width(iced::Length::Fill));
-    top_row = top_row.push(cell::Cell {
-      content: face,
-      padding: [5,2].into(),
-      size: 18,
-      length: 28,
-      on_left_click: Some(Message::NewGame),
-      ..Default::default()
-    });
-    top_row = top_row.push(widget::Space::with_width(iced::Length::Fill));
-    top_row = top_row.push(widget::Text::new("No clock").size(20));
-    column = column.push(top_row);
-    for y in 0..CELL_ROWS {
-      let mut row = widget::Row::new().spacing(1);
-      for x in 0..CELL_COLUMNS {
-        let cell: iced::Element<_> = match self.board[x][y] {
-          Cell {status: CellStatus::Flagged, .. } => cell::Cell {
-            content: '🚩',
-            size: 14,
-            padding: 2.into(),
-            on_right_click: Some(Message::Flag(x, y)),
-            ..Default::default()
-          }.into(),
-          Cell {status: CellStatus::Covered, .. } => match self.status {
-            GameStatus::Playing | GameStatus::Pressing => {
-              cell::Cell {
-                on_press: Some(Message::Pressing(true)),
-                on_release: Some(Message::Pressing(false)),
-                on_left_click: Some(Message::Reveal(x, y)),
-                on_right_click: Some(Message::Flag(x, y)),
-                ..Default::default()
-              }.into()
-            },
-            GameStatus::Won | GameStatus::Lost => if self.board[x][y].value == CellValue::Mined {
-              cell::Cell {content: '💣', ..Default::default()}.into()
-            } else {
-              cell::Cell {..Default::default()}.into()  //Removing on_press disables the buttons
-            },
+#[derive(Clone, Debug)]
+enum Message {
+  NewGame,
+  /// An extra mouse button or wheel tilt, by its name in `mouse_bindings`.
+  MouseBinding(String),
+  /// A mouse button went down on a cell of the main board or a quad board, or was let go.
+  Press(Option<(Option<usize>, (usize, usize))>),
+  Reveal(usize, usize),
+  SpecialReveal(usize, usize),
+  Flag(usize, usize),
+  /// An action on one of the quad boards.
+  Quad(usize, Action),
+  Drill(drill::Message),
+  Rush(rush::Message),
+  ToggleHotSeat(hotseat::Mode),
+  ModifiersChanged(keyboard::Modifiers),
+  CursorMoved(iced::Point),
+  WheelScrolled(f32),
+  Pan(bool),
+  Scrolled(scrollable::AbsoluteOffset),
+  Frame,
+  ToggleFullscreen,
+  Resized(u32, u32),
+  Focused(bool),
+  Input,
+  Tick(time::Instant),
+  DemoStep,
+  ToggleColors,
+  NumberColor(u8, usize, u8),
+  SaveSettings,
+  CheckTheme,
+  CheckDay,
+  ToastTick(time::Instant),
+  ToggleDebugOverlay,
+  ToggleConsole,
+  Console(console::Message),
+  ReportIssue,
+  Modal(modal::Message),
+  /// The window's close button.
+  CloseRequested,
+  /// Switch new boards to one of [`config::DIFFICULTIES`], and start one.
+  Difficulty(usize),
+  /// Turn the safe first click on or off, and start a new board.
+  ToggleSafeStart,
+  /// Take back the last move.
+  Undo,
+  /// Jump to the position after this many moves, in debug builds.
+  TimeTravel(u32),
+  /// Grow or shrink everything by a step, or go back to normal size with 0.
+  UiScale(f32),
+  CopyLink,
+  ToggleSession,
+  Session(session::Message),
+  ToggleCalendar,
+  Calendar(daily::Message),
+  ToggleStats,
+  Stats(stats::Message),
+  ToggleAudio,
+  Audio(audio::Message),
+  MoveCursor(i32, i32),
+  CursorReveal,
+  CursorFlag,
+  CursorChord,
+  Hint,
+  TogglePause,
+  ToggleHelp,
+  ToggleMenu,
+  /// A menu entry was picked. The menu closes, along with any other panel, and the message is handled.
+  Menu(Box<Message>),
+  ToggleNarration,
+  ArmScanner,
+  Comparison(compare::Message),
+}
+
+impl Message {
+  /// Messages which come from the user doing something, as opposed to timers and the window.
+  fn is_input(&self) -> bool {
+    !matches!(self, Message::ModifiersChanged(_) | Message::Scrolled(_) | Message::Frame | Message::Resized(..) | Message::CloseRequested | Message::Focused(_) | Message::Tick(_) | Message::DemoStep | Message::Comparison(compare::Message::Step) | Message::Rush(rush::Message::Tick) | Message::CheckTheme | Message::CheckDay | Message::ToastTick(_))
+  }
+}
+
+impl Game {
+  fn view_console(&self) -> iced::Element<'_, Message> {
+    let mut lines = widget::Column::new().padding([0, 4]);
+    for line in &self.console.output {
+      lines = lines.push(widget::Text::new(line).size(12).font(iced::Font::MONOSPACE));
+    }
+    let log = widget::Scrollable::new(lines)
+      .id(console_scrollable_id())
+      .width(iced::Length::Fill)
+      .height(iced::Length::Fixed(120.0));
+    let input = widget::TextInput::new(console::HELP, &self.console.input)
+      .id(console_input_id())
+      .on_input(|input| Message::Console(console::Message::Input(input)))
+      .on_submit(Message::Console(console::Message::Submit))
+      .size(12)
+      .font(iced::Font::MONOSPACE);
+    widget::Column::new().push(log).push(input).into()
+  }
+
+  /// Both runs of a comparison, with the timeline under them.
+  fn view_comparison<'a>(&'a self, comparison: &'a compare::Comparison) -> iced::Element<'a, Message> {
+    let number_colors = [1, 2, 3, 4, 5, 6, 7, 8].map(|number| self.theme.number_color(&self.settings, number));
+    let mut boards = widget::Row::new().spacing(10);
+    for run in &comparison.runs {
+      let highlight = comparison.diverged.and_then(|index| run.replay.moves.get(index)).map(|action| action.position());
+      let finished = run.replay.moves.len().checked_sub(1).map_or(0, |last| run.replay.time(last));
+      boards = boards.push(widget::Column::new().spacing(4)
+        .push(widget::Text::new(format!("{}: {:.1}s", run.name, finished as f32 / 1000.0)).size(14))
+        .push(widget::Canvas::new(compare::MiniBoard {engine: &run.engine, highlight, number_colors, heatmap: comparison.heatmap, solver: self.settings.solver.solver()})
+          .width(iced::Length::Fixed(300.0))
+          .height(iced::Length::Fixed(160.0)))
+        .push(widget::Text::new(comparison.next_move(run, self.settings.solver.solver())).size(14)));
+    }
+    let timeline = widget::Row::new().spacing(10).align_items(iced::Alignment::Center)
+      .push(widget::Button::new(if comparison.playing { "Pause" } else { "Play" }).on_press(Message::Comparison(compare::Message::Play)))
+      .push(widget::Slider::new(0.0..=comparison.length() as f64, comparison.time as f64, |time| Message::Comparison(compare::Message::Seek(time))))
+      .push(widget::Text::new(format!("{:.1}s", comparison.time as f32 / 1000.0)).width(50))
+      .push(widget::Checkbox::new("Danger", comparison.heatmap, |heatmap| Message::Comparison(compare::Message::Heatmap(heatmap))))
+      .push(widget::Button::new("Close").on_press(Message::Comparison(compare::Message::Close)));
+    widget::Column::new().spacing(10).padding(10)
+      .push(boards)
+      .push(widget::Text::new(comparison.describe()).size(14))
+      .push(timeline)
+      .into()
+  }
+
+  /// A small board of numbers and covered cells, for drills and puzzles. `covered` makes the cell for each covered spot.
+  fn view_spots<'a>(&self, spots: &[Vec<drill::Spot>], covered: impl Fn(usize, usize, drill::Spot) -> cell::Cell<Message>) -> iced::Element<'a, Message> {
+    let spacing = self.theme.cell_spacing();
+    let style = self.theme.cell_style();
+    let mut board = widget::Column::new().spacing(spacing);
+    for (y, spots) in spots.iter().enumerate() {
+      let mut row = widget::Row::new().spacing(spacing);
+      for (x, &spot) in spots.iter().enumerate() {
+        let mut cell = match spot {
+          drill::Spot::Wall => {
+            row = row.push(widget::Space::new(CELL_LENGTH * DRILL_SCALE, CELL_LENGTH * DRILL_SCALE));
+            continue;
           },
-          Cell {status: CellStatus::Revealed, value: CellValue::Mined} => cell::Cell {content: '💣', revealed: true, ..Default::default()}.into(),
-          Cell {status: CellStatus::Revealed, value: CellValue::Number(0)} => cell::Cell {revealed: true, ..Default::default()}.into(),
-          Cell {status: CellStatus::Revealed, value: CellValue::Number(number)} => cell::Cell {
+          drill::Spot::Number(0) => cell::Cell {revealed: true, ..Default::default()},
+          drill::Spot::Number(number) => cell::Cell {
             revealed: true,
             content: (number + b'0') as char,
             size: 20,
             padding: [0,4].into(),
-            color: text_color(number),
-            on_press: Some(Message::Pressing(true)),
-            on_release: Some(Message::Pressing(false)),
-            on_middle_click: Some(Message::SpecialReveal(x, y)),
-            ..Default::default()}.into(),
+            color: self.theme.number_color(&self.settings, number),
+            ..Default::default()},
+          drill::Spot::Covered {..} => covered(x, y, spot),
         };
+        cell.scale = DRILL_SCALE;
+        cell.style = style;
         row = row.push(cell);
       }
-      column = column.push(row);
+      board = board.push(row);
     }
-    column.into()
+    board.into()
+  }
+
+  /// The drill's small board, what became of it, and how each pattern has gone so far.
+  fn view_drill<'a>(&'a self, drill: &'a drill::Drill) -> iced::Element<'a, Message> {
+    let board = self.view_spots(&drill.spots, |x, y, spot| {
+      let answer = drill.answers.get(&(x, y)).copied();
+      let drill::Spot::Covered {mine, asked} = spot else { return cell::Cell::default() };
+      let mut cell = if drill.solved.is_none() && answer.is_none() {
+        cell::Cell {
+          on_left_click: Some(Message::Drill(drill::Message::Answer(x, y, false))),
+          on_right_click: Some(Message::Drill(drill::Message::Answer(x, y, true))),
+          ..Default::default()
+        }
+      //Once it is over, or once answered, the cell shows what it really is.
+      } else if mine {
+        cell::Cell {content: if answer == Some(true) { '🚩' } else { '💣' }, size: 14, padding: 2.into(), ..Default::default()}
+      } else {
+        cell::Cell {revealed: true, ..Default::default()}
+      };
+      if answer.is_some_and(|answer| answer != mine || !asked) {
+        cell.background = Some(self.theme.palette().danger);
+        cell.stripes = self.settings.dual_coding;
+      }
+      cell
+    });
+    let result = match drill.solved {
+      None => String::from("Left click every safe cell the numbers decide, and right click every mine"),
+      Some(true) => format!("Solved in {:.1}s", drill.took.as_secs_f32()),
+      Some(false) => String::from("Not quite. The cell in red can't be that"),
+    };
+    let mut records = widget::Column::new().spacing(2);
+    for (pattern, (name, _)) in drill::PATTERNS.iter().enumerate() {
+      records = records.push(widget::Text::new(format!("{}: {}", name, self.drills.record(pattern).describe())).size(14));
+    }
+    widget::Column::new().spacing(10).padding(10)
+      .push(widget::Text::new(format!("Drill: {}", drill.name())).size(20))
+      .push(board)
+      .push(widget::Text::new(result).size(14))
+      .push(widget::Row::new().spacing(10)
+        .push(widget::Button::new("Next").on_press(Message::Drill(drill::Message::Next)))
+        .push(widget::Button::new("Close").on_press(Message::Drill(drill::Message::Close))))
+      .push(records)
+      .into()
+  }
+
+  /// The puzzle to solve, with the time, score and strikes so far.
+  fn view_rush<'a>(&'a self, rush: &'a rush::Rush) -> iced::Element<'a, Message> {
+    let task = if rush.puzzle.mine { "Find the mine" } else { "Find the safe cell" };
+    let board = self.view_spots(&rush.puzzle.spots, |x, y, _| if rush.is_over() {
+      cell::Cell::default()
+    } else {
+      cell::Cell {on_left_click: Some(Message::Rush(rush::Message::Pick(x, y))), ..Default::default()}
+    });
+    let status = if rush.is_over() {
+      format!("Solved {} puzzles. Best: {}", rush.solved, self.drills.best_rush)
+    } else {
+      let last = match rush.last {
+        Some(true) => "Right! ",
+        Some(false) => "Wrong! ",
+        None => "",
+      };
+      format!("{}{}s left, {} solved, {}/{} strikes", last, rush.remaining().as_secs(), rush.solved, rush.strikes, rush::STRIKES)
+    };
+    widget::Column::new().spacing(10).padding(10)
+      .push(widget::Text::new(format!("Puzzle rush: {}", task)).size(20))
+      .push(board)
+      .push(widget::Text::new(status).size(14))
+      .push(widget::Row::new().spacing(10)
+        .push(widget::Button::new("Start over").on_press(Message::Rush(rush::Message::Restart)))
+        .push(widget::Button::new("Close").on_press(Message::Rush(rush::Message::Close))))
+      .into()
+  }
+
+  /// Four boards at half size, two by two.
+  fn view_quad<'a>(&'a self, quad: &'a quad::Quad) -> iced::Element<'a, Message> {
+    let spacing = self.theme.cell_spacing();
+    let style = self.theme.cell_style();
+    let mut grid = widget::Column::new().spacing(4);
+    for pair in 0..quad::BOARDS / 2 {
+      let mut boards = widget::Row::new().spacing(4);
+      for index in [pair * 2, pair * 2 + 1] {
+        let engine = &quad.boards[index];
+        let on = move |action| Message::Quad(index, action);
+        let mut board = widget::Column::new().spacing(spacing);
+        for y in 0..engine.board.height {
+          let mut row = widget::Row::new().spacing(spacing);
+          for x in 0..engine.board.width {
+            let mut cell = self.board_cell(engine, Some(index), x, y, &on);
+            cell.scale = 0.5;
+            cell.style = style;
+            cell.pressed = self.pressed == Some((Some(index), (x, y))) && !engine.is_over();
+            row = row.push(cell);
+          }
+          board = board.push(row);
+        }
+        boards = boards.push(board);
+      }
+      grid = grid.push(boards);
+    }
+    widget::Container::new(grid)
+      .width(iced::Length::Fill)
+      .height(iced::Length::Fill)
+      .center_x()
+      .center_y()
+      .into()
+  }
+
+  /// A cell of the main board as it is cached, without anything only shown for a moment.
+  fn main_cell(&self, x: usize, y: usize) -> cell::Cell<Message> {
+    let on_board = |action| match action {
+      Action::Reveal(x, y) => Message::Reveal(x, y),
+      Action::Flag(x, y) => Message::Flag(x, y),
+      Action::Chord(x, y) => Message::SpecialReveal(x, y),
+    };
+    let mut cell = self.board_cell(&self.engine, None, x, y, &on_board);
+    if self.debug_overlay && self.engine.board[x][y].status != CellStatus::Revealed {
+      cell.content = match self.engine.board[x][y].value {
+        CellValue::Mined => '*',
+        CellValue::AntiMine => ANTIMINE,
+        CellValue::Treasure(_) => '$',
+        CellValue::Number(number) => number_content(number),
+      };
+      cell.size = 14;
+      cell.padding = [2,6].into();
+      cell.color = match self.engine.board[x][y].status {
+        CellStatus::Flagged => self.theme.palette().danger,
+        _ => iced::Color::WHITE,
+      };
+    }
+    if let Some(mines) = self.scanner.mines_around(x, y).filter(|_| self.engine.board[x][y].status == CellStatus::Covered) {
+      cell.content = (mines as u8 + b'0') as char;
+      cell.size = 14;
+      cell.padding = [2,6].into();
+      cell.color = self.theme.palette().primary;
+    }
+    cell.scale = self.camera.zoom;
+    cell.style = self.theme.cell_style();
+    if self.fatal == Some((x, y)) {
+      cell.background = Some(self.theme.palette().danger);
+      cell.color = self.theme.palette().text;
+      cell.stripes = self.settings.dual_coding;
+    }
+    cell.tooltip = self.number_tooltip(x, y);
+    cell.tooltip_delay = time::Duration::from_millis(self.settings.tooltip_delay_ms);
+    cell
+  }
+
+  /// One cell of a board, with `on` giving the message for each action on it.
+  /// `board` is the index of the quad board it is on, or `None` for the main board, so presses are told apart.
+  fn board_cell(&self, engine: &engine::Engine, board: Option<usize>, x: usize, y: usize, on: &dyn Fn(Action) -> Message) -> cell::Cell<Message> {
+    let mut cell = match engine.board[x][y] {
+      Cell {status: CellStatus::Flagged, .. } => cell::Cell {
+        content: '🚩',
+        size: 14,
+        padding: 2.into(),
+        on_right_click: Some(on(Action::Flag(x, y))),
+        ..Default::default()
+      },
+      Cell {status: CellStatus::AntiFlagged, .. } => cell::Cell {
+        content: '🏳',
+        size: 14,
+        padding: 2.into(),
+        on_right_click: Some(on(Action::Flag(x, y))),
+        ..Default::default()
+      },
+      Cell {status: CellStatus::Covered, .. } => match engine.status {
+        GameStatus::Playing => {
+          cell::Cell {
+            on_press: Some(Message::Press(Some((board, (x, y))))),
+            on_release: Some(Message::Press(None)),
+            on_left_click: Some(on(Action::Reveal(x, y))),
+            on_right_click: Some(on(Action::Flag(x, y))),
+            ..Default::default()
+          }
+        },
+        //Like the classic game, winning flags every mine left.
+        GameStatus::Won => match engine.board[x][y].value {
+          CellValue::Mined => cell::Cell {content: '🚩', size: 14, padding: 2.into(), ..Default::default()},
+          CellValue::AntiMine => cell::Cell {content: '🏳', size: 14, padding: 2.into(), ..Default::default()},
+          _ => cell::Cell {..Default::default()},
+        },
+        GameStatus::Lost => match engine.board[x][y].value {
+          CellValue::Mined => cell::Cell {content: '💣', ..Default::default()},
+          CellValue::AntiMine => cell::Cell {content: ANTIMINE, size: 20, padding: [0,4].into(), ..Default::default()},
+          _ => cell::Cell {..Default::default()},  //Removing on_press disables the buttons
+        },
+      },
+      Cell {status: CellStatus::Revealed, value: CellValue::Mined} => cell::Cell {content: '💣', revealed: true, ..Default::default()},
+      Cell {status: CellStatus::Revealed, value: CellValue::AntiMine} => cell::Cell {content: ANTIMINE, revealed: true, size: 20, padding: [0,4].into(), ..Default::default()},
+      //A 0 from mines and antimines cancelling out is shown, since its neighbors aren't all safe.
+      Cell {status: CellStatus::Revealed, value: CellValue::Number(0)} if engine.is_blank(x, y) => cell::Cell {revealed: true, ..Default::default()},
+      Cell {status: CellStatus::Revealed, value: CellValue::Treasure(0)} => cell::Cell {content: '💎', revealed: true, size: 14, padding: 2.into(), ..Default::default()},
+      //Treasures with a number keep it readable, in gold.
+      Cell {status: CellStatus::Revealed, value: CellValue::Treasure(number)} => cell::Cell {
+        revealed: true,
+        content: number_content(number),
+        size: 20,
+        padding: [0,4].into(),
+        color: TREASURE_COLOR,
+        on_press: Some(Message::Press(Some((board, (x, y))))),
+        on_release: Some(Message::Press(None)),
+        on_middle_click: Some(on(Action::Chord(x, y))),
+        ..Default::default()},
+      Cell {status: CellStatus::Revealed, value: CellValue::Number(number)} => cell::Cell {
+        revealed: true,
+        content: number_content(number),
+        size: 20,
+        padding: [0,4].into(),
+        color: self.theme.number_color(&self.settings, number.unsigned_abs()),
+        on_press: Some(Message::Press(Some((board, (x, y))))),
+        on_release: Some(Message::Press(None)),
+        on_middle_click: Some(on(Action::Chord(x, y))),
+        ..Default::default()},
+    };
+    if self.settings.dual_coding {
+      //Shapes on top of colors: striped flags, hollow mines left at the end and solid mines which went off, and underlined treasures.
+      match engine.board[x][y] {
+        Cell {status: CellStatus::Flagged | CellStatus::AntiFlagged, .. } => cell.stripes = true,
+        Cell {status: CellStatus::Covered, value: CellValue::Mined} if engine.is_over() => {
+          cell.content = '○';
+          cell.size = 20;
+          cell.padding = [0,4].into();
+        },
+        Cell {status: CellStatus::Revealed, value: CellValue::Mined} => {
+          cell.content = '●';
+          cell.size = 20;
+          cell.padding = [0,4].into();
+          cell.color = self.theme.palette().danger;
+        },
+        Cell {status: CellStatus::Revealed, value: CellValue::Treasure(_)} => cell.underline = true,
+        _ => (),
+      }
+    }
+    cell
+  }
+
+  /// For learners, how many of a number's mines are flagged so far.
+  fn number_tooltip(&self, x: usize, y: usize) -> Option<String> {
+    if !self.settings.number_tooltips || self.engine.is_over() {
+      return None;
+    }
+    let number = self.engine.board[x][y].value.number().filter(|&number| number != 0)?;
+    if self.engine.board[x][y].status != CellStatus::Revealed {
+      return None;
+    }
+    let flagged: i8 = self.engine.neighbors.of(&self.engine.board, x, y).map(|(x, y)| self.engine.board[x][y].known_charge()).sum();
+    Some(format!("{} flagged / needs {}", flagged, number))
+  }
+
+  /// A slider over every move of the game, for jumping back and forth between positions.
+  fn view_time_travel(&self) -> iced::Element<'_, Message> {
+    widget::Row::new().spacing(10).padding([0, 4]).align_items(iced::Alignment::Center)
+      .push(widget::Slider::new(0..=self.engine.len() as u32, self.engine.position() as u32, Message::TimeTravel))
+      .push(widget::Text::new(format!("Move {} of {}", self.engine.position(), self.engine.len())).size(14))
+      .into()
+  }
+
+  /// Every move so far, in words. The newest is on top, so it's always in view.
+  fn view_narration(&self) -> iced::Element<'_, Message> {
+    let mut lines = widget::Column::new().padding([0, 4]);
+    for line in self.narration.lines.iter().rev() {
+      lines = lines.push(widget::Text::new(line).size(14));
+    }
+    widget::Scrollable::new(lines)
+      .width(iced::Length::Fill)
+      .height(iced::Length::Fixed(100.0))
+      .into()
+  }
+
+  /// Everything the game has besides the board, one per line.
+  fn view_menu(&self) -> iced::Element<'_, Message> {
+    let mut entries = vec![
+      ("New game", Message::NewGame),
+      ("Daily challenge", Message::ToggleCalendar),
+      ("Session seeds", Message::ToggleSession),
+      ("Statistics", Message::ToggleStats),
+      ("Colors", Message::ToggleColors),
+      ("Sound", Message::ToggleAudio),
+      ("Drill", Message::Drill(drill::Message::Next)),
+      ("Puzzle rush", Message::Rush(rush::Message::Restart)),
+      ("Console, for replays and more", Message::ToggleConsole),
+      ("Player name", Message::Modal(modal::Message::EditName)),
+      ("Help", Message::ToggleHelp),
+    ];
+    match &self.hot_seat {
+      Some(seat) => entries.insert(8, ("Stop taking turns", Message::ToggleHotSeat(seat.mode))),
+      None => {
+        entries.insert(8, ("Hot seat, together", Message::ToggleHotSeat(hotseat::Mode::CoOp)));
+        entries.insert(9, ("Hot seat, versus", Message::ToggleHotSeat(hotseat::Mode::Versus)));
+      },
+    }
+    let mut menu = widget::Column::new().spacing(4).padding(10);
+    for (name, message) in entries {
+      menu = menu.push(widget::Button::new(name).width(260).style(iced::theme::Button::Secondary).on_press(Message::Menu(Box::new(message))));
+    }
+    //Other sizes can be set in `settings.toml`.
+    let size = (self.settings.board_width, self.settings.board_height, self.settings.mine_count);
+    let mut difficulties = widget::Row::new().spacing(4);
+    for (index, (name, width, height, mines)) in config::DIFFICULTIES.into_iter().enumerate() {
+      let style = if size == (width, height, mines) { iced::theme::Button::Primary } else { iced::theme::Button::Secondary };
+      difficulties = difficulties.push(widget::Button::new(widget::Text::new(name).size(14)).width(84).style(style).on_press(Message::Menu(Box::new(Message::Difficulty(index)))));
+    }
+    let safe_start = if self.settings.safe_start { "Safe first click: on" } else { "Safe first click: off" };
+    menu.push(difficulties)
+      .push(widget::Button::new(safe_start).width(260).style(iced::theme::Button::Secondary).on_press(Message::Menu(Box::new(Message::ToggleSafeStart))))
+      .into()
+  }
+
+  /// Every key and mouse gesture, followed by the extra mouse buttons bound in the settings.
+  fn view_help(&self) -> iced::Element<'_, Message> {
+    let mut help = widget::Column::new().spacing(2).padding(10);
+    let bound = self.settings.mouse_bindings.iter()
+      .filter_map(|(button, name)| BINDINGS.iter().find(|(binding, _)| binding == name).map(|(_, action)| (button.as_str(), *action)));
+    for (key, action) in KEYS.into_iter().chain(bound) {
+      help = help.push(widget::Row::new().spacing(10)
+        .push(widget::Text::new(key).width(140).font(iced::Font::MONOSPACE))
+        .push(widget::Text::new(action)));
+    }
+    help.push(widget::Space::with_height(8))
+      .push(widget::Button::new("Close").on_press(Message::ToggleHelp))
+      .into()
+  }
+
+  /// Seeds of the boards played this session, newest first, each to share or play again, and a box for pasting someone else's.
+  fn view_session(&self) -> iced::Element<'_, Message> {
+    let mut view = widget::Column::new().spacing(4).padding(10)
+      .push(widget::Row::new().spacing(10).align_items(iced::Alignment::Center)
+        .push(widget::Text::new("Boards played this session").size(20))
+        .push(widget::Button::new("Share all").style(iced::theme::Button::Secondary).on_press_maybe((!self.session.seeds.is_empty()).then_some(Message::Session(session::Message::Share(None))))));
+    for (index, seed) in self.session.seeds.iter().enumerate().rev() {
+      view = view.push(widget::Row::new().spacing(10).align_items(iced::Alignment::Center)
+        .push(widget::Text::new(seed).width(260).font(iced::Font::MONOSPACE))
+        .push(widget::Button::new("Share").style(iced::theme::Button::Secondary).on_press(Message::Session(session::Message::Share(Some(index)))))
+        .push(widget::Button::new("Retry").style(iced::theme::Button::Secondary).on_press(Message::Session(session::Message::Retry(index)))));
+    }
+    view = view.push(widget::Space::with_height(8))
+      .push(widget::Row::new().spacing(10)
+        .push(widget::TextInput::new("Paste seeds, one per line or separated by commas", &self.session.input)
+          .on_input(|text| Message::Session(session::Message::ListInput(text)))
+          .on_submit(Message::Session(session::Message::PlayList))
+          .width(400))
+        .push(widget::Button::new("Play these").on_press(Message::Session(session::Message::PlayList))));
+    if !self.session.queue.is_empty() {
+      view = view.push(widget::Row::new().spacing(10).align_items(iced::Alignment::Center)
+        .push(widget::Text::new(format!("{} boards to go, next {}", self.session.queue.len(), self.session.queue[0])))
+        .push(widget::Button::new("Clear").style(iced::theme::Button::Secondary).on_press(Message::Session(session::Message::ClearQueue))));
+    }
+    view.into()
+  }
+
+  /// Volumes and which sounds play.
+  fn view_audio(&self) -> iced::Element<'_, Message> {
+    let slider = |label, value, message: fn(f32) -> Message| widget::Row::new().spacing(10).align_items(iced::Alignment::Center)
+      .push(widget::Text::new(label).width(120))
+      .push(widget::Slider::new(0.0..=1.0, value, message).step(0.01).on_release(Message::SaveSettings));
+    widget::Column::new().spacing(10).padding(10)
+      .push(slider("Master volume", self.settings.master_volume, |volume| Message::Audio(audio::Message::MasterVolume(volume))))
+      .push(slider("Effects volume", self.settings.effects_volume, |volume| Message::Audio(audio::Message::EffectsVolume(volume))))
+      .push(widget::Checkbox::new("Notes when revealing numbers", self.settings.reveal_sounds, |on| Message::Audio(audio::Message::RevealSounds(on))))
+      .push(widget::Checkbox::new("Thud for big openings", self.settings.opening_sounds, |on| Message::Audio(audio::Message::OpeningSounds(on))))
+      .push(widget::Button::new("Test").on_press(Message::Audio(audio::Message::Test)))
+      .into()
   }
-}
\ No newline at end of file
+
+  /// Charts of the latest solve times in one category, with a row of difficulties and a row of rules to pick it.
+  fn view_stats(&self, category: &stats::Category) -> iced::Element<'_, Message> {
+    let mut view = widget::Column::new().spacing(10).padding(10);
+    let style = |selected| if selected { iced::theme::Button::Primary } else { iced::theme::Button::Secondary };
+    let mut difficulties = widget::Row::new().spacing(4);
+    for name in self.stats.difficulties() {
+      let mut button = widget::Button::new(name).style(style(name == category.difficulty));
+      if let Some(&first) = self.stats.categories(name).first() {
+        button = button.on_press(Message::Stats(stats::Message::Category(first.clone())));
+      }
+      difficulties = difficulties.push(button);
+    }
+    view = view.push(difficulties);
+    let mut rules = widget::Row::new().spacing(4);
+    for other in self.stats.categories(&category.difficulty) {
+      rules = rules.push(widget::Button::new(widget::Text::new(other.rules_name())).style(style(other == category)).on_press(Message::Stats(stats::Message::Category(other.clone()))));
+    }
+    view = view.push(rules);
+    let times = self.stats.solve_times(category, STATS_GAMES);
+    let summary = match times.iter().copied().reduce(f32::min) {
+      Some(best) => format!("Last {} wins: best {:.1}s, average {:.1}s", times.len(), best, times.iter().sum::<f32>() / times.len() as f32),
+      None => String::from("No wins yet"),
+    };
+    view = view.push(widget::Text::new(summary));
+    let percent = |rate: Option<f32>| rate.map_or(String::from("-"), |rate| format!("{:.0}%", rate * 100.0));
+    let strict = self.settings.abandoned_are_losses;
+    let win_rate = match self.stats.win_rate(category, strict) {
+      (rate, 0) => format!("Win rate: {}", percent(rate)),
+      (rate, abandoned) if strict => format!("Win rate: {} counting {} abandoned as losses, {} leaving them out", percent(rate), abandoned, percent(self.stats.win_rate(category, false).0)),
+      (rate, abandoned) => format!("Win rate: {} leaving out {} abandoned, {} counting them as losses", percent(rate), abandoned, percent(self.stats.win_rate(category, true).0)),
+    };
+    view = view.push(widget::Text::new(win_rate).size(14));
+    //Whether each win used flags is worked out from its moves, so both playstyles can be compared without switching modes.
+    let (no_flags, flags) = if category.is_no_flags() { (category.clone(), category.other_playstyle()) } else { (category.other_playstyle(), category.clone()) };
+    let playstyle = |category: &stats::Category| {
+      let times = self.stats.solve_times(category, STATS_GAMES);
+      match times.iter().copied().reduce(f32::min) {
+        Some(best) => format!("last {} wins best {:.1}s, average {:.1}s", times.len(), best, times.iter().sum::<f32>() / times.len() as f32),
+        None => String::from("no wins"),
+      }
+    };
+    view = view.push(widget::Text::new(format!("No flags: {}. Flags: {}.", playstyle(&no_flags), playstyle(&flags))).size(14));
+    view = view.push(widget::Canvas::new(chart::Trend(times.clone())).width(iced::Length::Fill).height(150));
+    view = view.push(widget::Canvas::new(chart::Histogram(times)).width(iced::Length::Fill).height(150));
+    view = view.push(widget::TextInput::new("Filter by tag", &self.history_filter).on_input(|filter| Message::Stats(stats::Message::Filter(filter))).size(14));
+    let mut games = widget::Column::new().spacing(2);
+    for (index, record) in self.stats.games.iter().enumerate().rev()
+      .filter(|(_, record)| record.category == *category && record.matches(&self.history_filter))
+      .take(STATS_GAMES) {
+      games = games.push(widget::Button::new(widget::Text::new(record.describe()).size(14))
+        .style(style(self.history_game == Some(index)))
+        .on_press(Message::Stats(stats::Message::Game(index))));
+      if self.history_game != Some(index) {
+        continue;
+      }
+      let mut tags = widget::Row::new().spacing(4).align_items(iced::Alignment::Center);
+      for tag in &record.tags {
+        tags = tags.push(widget::Button::new(widget::Text::new(format!("✕ {}", tag)).size(14)).on_press(Message::Stats(stats::Message::RemoveTag(tag.clone()))));
+      }
+      games = games
+        .push(tags.push(widget::TextInput::new("Add a tag", &self.tag_input).on_input(|tag| Message::Stats(stats::Message::TagInput(tag))).on_submit(Message::Stats(stats::Message::AddTag)).size(14).width(200)))
+        .push(widget::TextInput::new("Note", &record.note).on_input(|note| Message::Stats(stats::Message::Note(note))).on_submit(Message::Stats(stats::Message::Save)).size(14));
+    }
+    view = view.push(widget::Scrollable::new(games).height(iced::Length::Fill));
+    view.into()
+  }
+
+  /// Month of daily challenges. Won days are green, and any day up to today can be played.
+  fn view_calendar(&self, (year, month): (i64, u32)) -> iced::Element<'_, Message> {
+    let today = daily::today();
+    let first = daily::day(year, month, 1);
+    let next = if month == 12 { daily::day(year + 1, 1, 1) } else { daily::day(year, month + 1, 1) };
+    let mut calendar = widget::Column::new().spacing(4).padding(10).align_items(iced::Alignment::Center);
+    calendar = calendar.push(widget::Row::new().spacing(10).align_items(iced::Alignment::Center)
+      .push(widget::Button::new("◀").on_press(Message::Calendar(daily::Message::Month(-1))))
+      .push(widget::Text::new(format!("{} {}", daily::month_name(month), year)).width(150).horizontal_alignment(iced::alignment::Horizontal::Center))
+      .push(widget::Button::new("▶").on_press(Message::Calendar(daily::Message::Month(1)))));
+    let mut week = widget::Row::new().spacing(4);
+    for name in ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"] {
+      week = week.push(widget::Text::new(name).width(40).horizontal_alignment(iced::alignment::Horizontal::Center));
+    }
+    calendar = calendar.push(week);
+    week = widget::Row::new().spacing(4).push(widget::Space::with_width(44.0 * daily::weekday(first) as f32 - 4.0));
+    for day in first..next {
+      if daily::weekday(day) == 0 && day != first {
+        calendar = calendar.push(week);
+        week = widget::Row::new().spacing(4);
+      }
+      let style = if self.daily.completed.contains(&day) {
+        iced::theme::Button::Positive
+      } else if day == today {
+        iced::theme::Button::Primary
+      } else {
+        iced::theme::Button::Secondary
+      };
+      let mut button = widget::Button::new(widget::Text::new(daily::date(day).2.to_string()).horizontal_alignment(iced::alignment::Horizontal::Center))
+        .width(40)
+        .style(style);
+      if day <= today {
+        button = button.on_press(Message::Calendar(daily::Message::Play(day)));
+      }
+      week = week.push(button);
+    }
+    calendar = calendar.push(week);
+    calendar = calendar.push(widget::Text::new(format!("Streak: {} Best: {}", self.daily.streak(today), self.daily.best_streak())));
+    calendar.into()
+  }
+
+  /// Sliders for picking the color of each number.
+  fn view_colors(&self) -> iced::Element<'_, Message> {
+    let mut colors = widget::Column::new().spacing(4).padding(10);
+    for number in 1..=8u8 {
+      let mut row = widget::Row::new().spacing(10).align_items(iced::Alignment::Center);
+      row = row.push(cell::Cell {
+        revealed: true,
+        content: (number + b'0') as char,
+        size: 20,
+        padding: [0,4].into(),
+        color: self.theme.number_color(&self.settings, number),
+        ..Default::default()
+      });
+      for (channel, &value) in self.settings.number_colors[number as usize - 1].iter().enumerate() {
+        row = row.push(widget::Slider::new(0..=255, value, move |value| Message::NumberColor(number, channel, value))
+          .on_release(Message::SaveSettings));
+      }
+      colors = colors.push(row);
+    }
+    colors.into()
+  }
+
+  /// Draws the whole main board again, once it was replaced.
+  fn redraw_board(&mut self) {
+    self.tiles = board::Tiles::new((self.engine.board.width, self.engine.board.height));
+  }
+
+  /// Draws the whole main board again when something besides its cells changed how they look.
+  fn restyle_board(&mut self) {
+    let look = Some(BoardLook {
+      palette: self.theme.palette(),
+      style: self.theme.cell_style(),
+      numbers: std::array::from_fn(|index| self.theme.number_color(&self.settings, index as u8 + 1)),
+      dual_coding: self.settings.dual_coding,
+      debug_overlay: self.debug_overlay,
+      fatal: self.fatal,
+      scans: self.scanner.scans.len(),
+    });
+    if look != self.board_look {
+      self.board_look = look;
+      self.redraw_board();
+    }
+  }
+
+  /// Handles a message, after which the board is checked for needing to be drawn again.
+  fn handle(&mut self, message: Message) -> iced::Command<Message> {
+    if message.is_input() {
+      self.last_input = time::Instant::now();
+      if self.demo.is_some() {
+        //Any input ends the demo and hands over a fresh board.
+        self.demo = None;
+        self.new_game();
+        return iced::Command::none();
+      }
+    }
+    match message {
+      Message::NewGame => return self.leave(modal::Abandon::NewGame),
+      Message::CloseRequested => return self.leave(modal::Abandon::Close),
+      Message::Modal(message) => return self.handle_modal(message),
+      Message::Difficulty(index) => {
+        let (_, width, height, mines) = config::DIFFICULTIES[index];
+        (self.settings.board_width, self.settings.board_height, self.settings.mine_count) = (width, height, mines);
+        let _ = self.settings.save();
+        return iced::Command::batch([self.leave(modal::Abandon::NewGame), self.fit_window()]);
+      },
+      Message::ToggleSafeStart => {
+        self.settings.safe_start = !self.settings.safe_start;
+        let _ = self.settings.save();
+        return self.leave(modal::Abandon::NewGame);
+      },
+      Message::Press(cell) => self.pressed = cell,
+      Message::MouseBinding(name) => {
+        if let Some(message) = self.settings.mouse_bindings.get(&name).and_then(|action| binding(action)) {
+          return self.handle(message);
+        }
+      },
+      Message::Reveal(x, y) if self.scanner.armed => {
+        if let Some(mines) = self.scanner.scan(&self.engine.board, x, y) {
+          self.narration.print(format!("Scanned around {}: {} mines", narration::cell_name(x, y), mines));
+        }
+      },
+      Message::Reveal(x, y) => self.play(Action::Reveal(x, y)),
+      Message::Quad(board, action) => {
+        let Some(quad) = &mut self.quad else { return iced::Command::none() };
+        if quad.is_over() || (matches!(action, Action::Chord(..)) && self.camera.is_panning()) {
+          return iced::Command::none();
+        }
+        quad.act(board, action);
+        if quad.revealed_count() > 0 {
+          self.clock.start();
+        }
+        if quad.is_over() {
+          self.clock.stop();
+        }
+      },
+      Message::SpecialReveal(x, y) => {
+        //Releasing the middle button after dragging the board shouldn't chord the cell it landed on.
+        if !self.camera.is_panning() {
+          self.play(Action::Chord(x, y));
+        }
+      },
+      Message::Flag(x, y) => {
+        if self.engine.status == GameStatus::Playing {
+          self.act(Action::Flag(x, y));
+        }
+      },
+      Message::ModifiersChanged(modifiers) => self.modifiers = modifiers,
+      Message::CursorMoved(position) => {
+        self.cursor = position;
+        if let Some(offset) = self.camera.pan_to(position, board_size(self.board_cells(), self.camera.zoom, self.theme.cell_spacing())) {
+          return scrollable::scroll_to(board_scrollable_id(), offset);
+        }
+      },
+      Message::WheelScrolled(lines) => {
+        if self.modifiers.control() {
+          let anchor = iced::Point::new(self.cursor.x, (self.cursor.y - HEADER_HEIGHT).max(0.0));
+          self.camera.zoom_by(lines, anchor);
+        }
+      },
+      Message::Pan(true) => self.camera.pan_start(self.cursor),
+      Message::Pan(false) => self.camera.pan_end(),
+      Message::Scrolled(offset) => self.camera.scrolled(offset),
+      Message::Frame => {
+        self.animations.step();
+        if self.camera.is_animating() {
+          let (cells, spacing) = (self.board_cells(), self.theme.cell_spacing());
+          let offset = self.camera.step(|zoom| board_size(cells, zoom, spacing));
+          return scrollable::scroll_to(board_scrollable_id(), offset);
+        }
+      },
+      Message::Undo => {
+        //Finished games are already in the stats, so only a game still going can be taken back.
+        if self.quad.is_none() && !self.engine.is_over() {
+          if let Some(action) = self.engine.undo() {
+            self.tiles.clear(self.engine.changed());
+            let (x, y) = action.position();
+            self.narration.print(format!("Took back the move on {}", narration::cell_name(x, y)));
+            self.undos += 1;
+            self.hint = None;
+            self.update_overlay();
+          }
+        }
+      },
+      Message::TimeTravel(index) => {
+        if self.debug_overlay && self.quad.is_none() {
+          self.undos += 1;
+          self.hint = None;
+          self.engine.seek(index as usize);
+          self.tiles.clear(self.engine.changed());
+          if self.engine.status != GameStatus::Lost {
+            self.fatal = None;
+          }
+        }
+      },
+      Message::UiScale(step) => {
+        //0 goes back to normal size.
+        self.settings.ui_scale = if step == 0.0 { 1.0 } else { (self.settings.ui_scale() + step).clamp(settings::MIN_UI_SCALE, settings::MAX_UI_SCALE) };
+        let _ = self.settings.save();
+        return self.fit_window();
+      },
+      Message::ToggleFullscreen => {
+        self.settings.fullscreen = !self.settings.fullscreen;
+        let _ = self.settings.save();  //Not being able to remember the mode isn't worth interrupting the game over.
+        let mode = if self.settings.fullscreen { window::Mode::Fullscreen } else { window::Mode::Windowed };
+        return window::change_mode(mode);
+      },
+      Message::Resized(width, height) => {
+        let viewport = iced::Size::new(width as f32, (height as f32 - HEADER_HEIGHT).max(0.0));
+        self.camera.resize(viewport);
+        //Fullscreen scales the board up to fill the screen. Windowed goes back to the normal size.
+        let zoom = if self.settings.fullscreen {
+          let unzoomed = board_size(self.board_cells(), 1.0, self.theme.cell_spacing());
+          (viewport.width / unzoomed.width).min(viewport.height / unzoomed.height)
+        } else {
+          1.0
+        };
+        self.camera.zoom_to(zoom);
+      },
+      Message::Focused(false) => {
+        //The release can happen in another window, where it never reaches the board.
+        self.pressed = None;
+        //The demo is meant to be watched in the background.
+        if self.settings.pause_on_focus_loss && self.demo.is_none() && !self.clock.is_paused() {
+          self.clock.pause();
+          self.paused_by_focus = self.clock.is_paused();
+          self.update_overlay();
+        }
+      },
+      Message::Focused(true) => {
+        if std::mem::take(&mut self.paused_by_focus) {
+          self.clock.resume();
+          self.update_overlay();
+        }
+      },
+      Message::Input => (),
+      //Also redraws the clock, and runs out the countdown.
+      Message::Tick(now) => {
+        if self.demo.is_none() && self.clock.tick(now, time::SystemTime::now(), self.settings.timer_tick()) {
+          self.suspended = true;
+          self.toasts.push("Paused while the computer was asleep");
+          self.update_overlay();
+        }
+        if let Some(limit) = self.time_limit {
+          if self.demo.is_none() && self.clock.is_running() && self.clock.remaining(limit).is_zero() {
+            self.engine.lose();
+            self.tiles.clear(self.engine.changed());
+            self.finish();
+            self.update_overlay();
+          }
+        }
+        if self.engine.is_fresh() && self.quad.is_none() && !matches!(self.screen, screen::Screen::Drill(_) | screen::Screen::Rush(_)) && now.duration_since(self.last_input).as_secs() >= self.settings.idle_demo_seconds {
+          self.demo = Some(Demo {last_move: None, end_pause: 0});
+        }
+      },
+      Message::DemoStep => self.demo_step(),
+      Message::ToggleColors => self.screen.toggle(screen::Screen::Colors),
+      Message::NumberColor(number, channel, value) => {
+        self.settings.number_colors[number as usize - 1][channel] = value;
+      },
+      Message::SaveSettings => {
+        let _ = self.settings.save();
+      },
+      Message::ToggleDebugOverlay => {
+        //Showing what's under covered cells is cheating, so only developers get it.
+        if cfg!(debug_assertions) {
+          self.debug_overlay = !self.debug_overlay;
+        }
+      },
+      Message::ToggleConsole => {
+        self.console.open = !self.console.open;
+        if self.console.open {
+          return widget::text_input::focus(console_input_id());
+        }
+      },
+      Message::Console(message) => return self.handle_console(message),
+      Message::CopyLink => {
+        self.console.open = true;
+        return iced::Command::batch([
+          self.copy_link(),
+          scrollable::snap_to(console_scrollable_id(), scrollable::RelativeOffset::END),
+        ]);
+      },
+      Message::ReportIssue => {
+        self.console.open = true;
+        return iced::Command::batch([
+          self.report(),
+          scrollable::snap_to(console_scrollable_id(), scrollable::RelativeOffset::END),
+        ]);
+      },
+      Message::ToggleCalendar => {
+        let (year, month, _) = daily::date(daily::today());
+        self.screen.toggle(screen::Screen::Calendar(year, month));
+      },
+      Message::Calendar(message) => return self.handle_calendar(message),
+      Message::ToggleStats => self.screen.toggle(screen::Screen::Stats(stats::Category::standard(&self.engine.board))),
+      Message::Stats(message) => return self.handle_stats(message),
+      Message::ToggleAudio => self.screen.toggle(screen::Screen::Audio),
+      Message::Audio(message) => return self.handle_audio(message),
+      Message::MoveCursor(dx, dy) => {
+        let (width, height) = self.board_cells();
+        let (x, y) = self.keyboard_cursor.unwrap_or((width / 2, height / 2));
+        //The first press only shows the cursor, in the middle of the board.
+        self.keyboard_cursor = Some(match self.keyboard_cursor {
+          Some(_) => (
+            (x as i32 + dx).clamp(0, width as i32 - 1) as usize,
+            (y as i32 + dy).clamp(0, height as i32 - 1) as usize,
+          ),
+          None => (x, y),
+        });
+      },
+      Message::CursorReveal => {
+        if let Some((x, y)) = self.keyboard_cursor {
+          return match self.engine.board[x][y].status {
+            CellStatus::Revealed => self.handle(Message::SpecialReveal(x, y)),
+            _ => self.handle(Message::Reveal(x, y)),
+          };
+        }
+      },
+      Message::CursorFlag => {
+        if let Some((x, y)) = self.keyboard_cursor {
+          return self.handle(Message::Flag(x, y));
+        }
+      },
+      Message::CursorChord => {
+        if let Some((x, y)) = self.keyboard_cursor {
+          return self.handle(Message::SpecialReveal(x, y));
+        }
+      },
+      Message::Hint => {
+        let cooldown = time::Duration::from_secs(self.settings.hint_cooldown_seconds);
+        if let Some(wait) = self.last_hint.map(|last| cooldown.saturating_sub(last.elapsed())).filter(|wait| !wait.is_zero()) {
+          self.console.print(format!("Next hint in {} seconds", wait.as_secs() + 1));
+          return iced::Command::none();
+        }
+        if self.engine.antimines > 0 {
+          self.console.print("Hints don't work with antimines");
+          return iced::Command::none();
+        }
+        //Only moves the solver is sure about. Guesses aren't much of a hint.
+        self.hint = self.settings.solver.solver().next_move(&self.engine.board, self.engine.neighbors).filter(|step| step.reason != solver::Reason::Guess && !self.engine.is_over());
+        match self.hint {
+          Some(step) => {
+            self.keyboard_cursor = Some(step.action.position());
+            self.hints_used += 1;
+            self.last_hint = Some(time::Instant::now());
+            self.clock.penalty(time::Duration::from_secs(self.settings.hint_penalty_seconds));
+          },
+          None => self.console.print("No safe move found, time to guess"),
+        }
+      },
+      Message::TogglePause => {
+        self.paused_by_focus = false;
+        if self.clock.is_paused() {
+          self.clock.resume();
+        } else {
+          self.clock.pause();
+        }
+        self.update_overlay();
+      },
+      Message::ToggleHelp => self.screen.toggle(screen::Screen::Help),
+      Message::ToggleSession => self.screen.toggle(screen::Screen::Session),
+      Message::Session(message) => return self.handle_session(message),
+      Message::ToggleMenu => self.screen.toggle(screen::Screen::Menu),
+      Message::Menu(message) => {
+        self.screen = screen::Screen::Game;
+        return self.handle(*message);
+      },
+      Message::Comparison(message) => return self.handle_comparison(message),
+      Message::Drill(message) => return self.handle_drill(message),
+      Message::Rush(message) => return self.handle_rush(message),
+      //Starts on a new board, or ends with the board being played.
+      Message::ToggleHotSeat(mode) => {
+        if self.hot_seat.take().is_none() {
+          self.hot_seat = Some(hotseat::HotSeat::new(mode, &self.settings.hot_seat_players, &self.settings.hot_seat_handicaps));
+          return self.handle(Message::NewGame);
+        }
+      },
+      Message::ToggleNarration => self.narration.open = !self.narration.open,
+      Message::ArmScanner => {
+        if self.settings.scanner && !self.engine.is_over() {
+          self.scanner.toggle();
+        }
+      },
+      Message::CheckDay => {
+        let today = daily::today();
+        if today != self.today {
+          self.today = today;
+          if self.settings.notifications {
+            let (year, month, day) = daily::date(today);
+            notify::send(String::from("New daily challenge"), format!("The board for {} {} {} is ready", daily::month_name(month), day, year));
+          }
+        }
+      },
+      Message::ToastTick(now) => self.toasts.tick(now),
+      Message::CheckTheme => {
+        let modified = theme::Theme::modified();
+        //The system's color scheme only matters when there's no theme file.
+        let dark = if modified.is_none() { self.settings.is_dark() } else { self.dark };
+        if modified != self.theme_modified || dark != self.dark {
+          self.theme_modified = modified;
+          self.dark = dark;
+          let spacing = self.theme.cell_spacing();
+          self.theme = theme::Theme::load(dark);
+          //Different spacing changes the size of the board, so the window follows.
+          if self.theme.cell_spacing() != spacing {
+            return self.fit_window();
+          }
+        }
+      },
+    }
+    iced::Command::none()
+  }
+
+  fn handle_stats(&mut self, message: stats::Message) -> iced::Command<Message> {
+    match message {
+      stats::Message::Category(category) => self.screen = screen::Screen::Stats(category),
+      stats::Message::Filter(filter) => self.history_filter = filter,
+      stats::Message::Game(index) => {
+        self.history_game = if self.history_game == Some(index) { None } else { Some(index) };
+        self.tag_input.clear();
+      },
+      stats::Message::TagInput(tag) => self.tag_input = tag,
+      stats::Message::AddTag => {
+        let tag = self.tag_input.trim().to_string();
+        if let Some(record) = self.history_game.and_then(|index| self.stats.games.get_mut(index)) {
+          if !tag.is_empty() && !record.tags.contains(&tag) {
+            record.tags.push(tag);
+            let _ = self.stats.save();
+          }
+        }
+        self.tag_input.clear();
+      },
+      stats::Message::RemoveTag(tag) => {
+        if let Some(record) = self.history_game.and_then(|index| self.stats.games.get_mut(index)) {
+          record.tags.retain(|existing| *existing != tag);
+          let _ = self.stats.save();
+        }
+      },
+      //Notes are saved when Enter is pressed, not on every key.
+      stats::Message::Note(note) => {
+        if let Some(record) = self.history_game.and_then(|index| self.stats.games.get_mut(index)) {
+          record.note = note;
+        }
+      },
+      stats::Message::Save => {
+        let _ = self.stats.save();
+      },
+    }
+    iced::Command::none()
+  }
+
+  fn handle_calendar(&mut self, message: daily::Message) -> iced::Command<Message> {
+    match message {
+      daily::Message::Month(step) => {
+        if let screen::Screen::Calendar(year, month) = &mut self.screen {
+          let months = *year * 12 + *month as i64 - 1 + step as i64;
+          (*year, *month) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+        }
+      },
+      daily::Message::Play(day) => return self.leave(modal::Abandon::Daily(day)),
+    }
+    iced::Command::none()
+  }
+
+  fn handle_audio(&mut self, message: audio::Message) -> iced::Command<Message> {
+    match message {
+      audio::Message::MasterVolume(volume) => self.settings.master_volume = volume,
+      audio::Message::EffectsVolume(volume) => self.settings.effects_volume = volume,
+      audio::Message::RevealSounds(on) => {
+        self.settings.reveal_sounds = on;
+        let _ = self.settings.save();
+      },
+      audio::Message::OpeningSounds(on) => {
+        self.settings.opening_sounds = on;
+        let _ = self.settings.save();
+      },
+      audio::Message::Test => {
+        self.audio.play(audio::Sound::Number(1), self.settings.effects_volume());
+        self.audio.play(audio::Sound::Opening, self.settings.effects_volume());
+      },
+    }
+    iced::Command::none()
+  }
+
+  fn handle_session(&mut self, message: session::Message) -> iced::Command<Message> {
+    match message {
+      session::Message::Share(index) => {
+        let text = match index {
+          Some(index) => self.session.seeds[index].clone(),
+          None => self.session.share(),
+        };
+        self.toasts.push(if index.is_some() { "Seed copied" } else { "Seeds copied" });
+        return iced::clipboard::write(text);
+      },
+      session::Message::Retry(index) => return self.leave(modal::Abandon::Retry(index)),
+      session::Message::ListInput(text) => self.session.input = text,
+      session::Message::PlayList => {
+        let input = std::mem::take(&mut self.session.input);
+        if self.session.queue_list(&input) > 0 {
+          self.screen = screen::Screen::Game;
+          return self.leave(modal::Abandon::NewGame);
+        }
+      },
+      session::Message::ClearQueue => self.session.queue.clear(),
+    }
+    iced::Command::none()
+  }
+
+  fn handle_comparison(&mut self, message: compare::Message) -> iced::Command<Message> {
+    match message {
+      compare::Message::Seek(time) => {
+        if let screen::Screen::Comparison(comparison) = &mut self.screen {
+          comparison.seek(time as u64);
+        }
+      },
+      compare::Message::Play => {
+        if let screen::Screen::Comparison(comparison) = &mut self.screen {
+          //Playing from the end starts over.
+          if comparison.time >= comparison.length() {
+            comparison.seek(0);
+          }
+          comparison.playing = !comparison.playing;
+        }
+      },
+      compare::Message::Heatmap(heatmap) => {
+        if let screen::Screen::Comparison(comparison) = &mut self.screen {
+          comparison.heatmap = heatmap;
+        }
+      },
+      compare::Message::Step => {
+        if let screen::Screen::Comparison(comparison) = &mut self.screen {
+          comparison.seek(comparison.time + COMPARE_STEP.as_millis() as u64);
+          comparison.playing = comparison.time < comparison.length();
+        }
+      },
+      compare::Message::Close => self.screen = screen::Screen::Game,
+    }
+    iced::Command::none()
+  }
+
+  fn handle_drill(&mut self, message: drill::Message) -> iced::Command<Message> {
+    match message {
+      drill::Message::Answer(x, y, mine) => {
+        let screen::Screen::Drill(drill) = &mut self.screen else { return iced::Command::none() };
+        let over = drill.solved.is_some();
+        drill.answer(x, y, mine);
+        if !over && drill.solved.is_some() {
+          self.drills.finish(drill);
+          let _ = self.drills.save();
+        }
+      },
+      drill::Message::Next => {
+        let previous = match &self.screen {
+          screen::Screen::Drill(drill) => Some(drill.pattern),
+          _ => None,
+        };
+        self.screen = screen::Screen::Drill(drill::Drill::new(self.drills.next(previous)));
+      },
+      drill::Message::Close => self.screen = screen::Screen::Game,
+    }
+    iced::Command::none()
+  }
+
+  fn handle_rush(&mut self, message: rush::Message) -> iced::Command<Message> {
+    match message {
+      rush::Message::Pick(x, y) => {
+        if let screen::Screen::Rush(rush) = &mut self.screen {
+          rush.click(x, y);
+        }
+        self.end_rush();
+      },
+      rush::Message::Tick => self.end_rush(),
+      rush::Message::Restart => self.screen = screen::Screen::Rush(rush::Rush::new()),
+      rush::Message::Close => self.screen = screen::Screen::Game,
+    }
+    iced::Command::none()
+  }
+
+  fn handle_console(&mut self, message: console::Message) -> iced::Command<Message> {
+    match message {
+      //The backtick which toggles the console shouldn't end up in the command.
+      console::Message::Input(input) => self.console.input = input.replace('`', ""),
+      console::Message::Submit => {
+        let line = std::mem::take(&mut self.console.input);
+        return iced::Command::batch([
+          self.run_command(&line),
+          scrollable::snap_to(console_scrollable_id(), scrollable::RelativeOffset::END),
+        ]);
+      },
+    }
+    iced::Command::none()
+  }
+
+  fn handle_modal(&mut self, message: modal::Message) -> iced::Command<Message> {
+    match message {
+      modal::Message::ConfirmAbandon => {
+        if let Some(modal::Modal::Abandon(abandon)) = self.modal {
+          return self.go(abandon);
+        }
+      },
+      modal::Message::Dismiss => {
+        if self.modal.take().is_none() {
+          self.screen = screen::Screen::Game;
+        }
+      },
+      modal::Message::EditName => {
+        self.modal = Some(modal::Modal::PlayerName(self.settings.player_name.clone()));
+        return widget::text_input::focus(modal::input_id());
+      },
+      modal::Message::NameInput(input) => {
+        if let Some(modal::Modal::PlayerName(name)) = &mut self.modal {
+          *name = input;
+        }
+      },
+      modal::Message::SaveName => {
+        if let Some(modal::Modal::PlayerName(name)) = self.modal.take() {
+          self.settings.player_name = name.trim().to_string();
+          let _ = self.settings.save();
+        }
+      },
+      modal::Message::ConfirmMove => {
+        if let Some(modal::Modal::FatalMove(action, _)) = self.modal {
+          self.act(action);
+        }
+      },
+    }
+    iced::Command::none()
+  }
+
+}
+
+impl iced::Application for Game {
+  type Executor = iced::executor::Default;
+  type Message = Message;
+  type Theme = iced::Theme;
+  type Flags = ();
+
+  fn new(_flags: ()) -> (Self, iced::Command<Message>) {
+    let game_settings = settings::Settings::load();
+    let viewport = board_size((game_settings.board_width, game_settings.board_height), 1.0, theme::Theme::default().cell_spacing());
+    let mut game = Game {
+      engine: history::History::new(GameConfig::new(0).build()),
+      seed_phrase: None,
+      camera: camera::Camera::new(viewport),
+      animations: animation::Animations::default(),
+      audio: audio::Audio::default(),
+      cursor: iced::Point::ORIGIN,
+      keyboard_cursor: None,
+      hint: None,
+      hints_used: 0,
+      undos: 0,
+      modal: None,
+      toasts: toast::Toasts::default(),
+      warnings: 0,
+      guesses: 0,
+      forced_guesses: 0,
+      fatal: None,
+      last_hint: None,
+      pressed: None,
+      tiles: board::Tiles::default(),
+      board_look: None,
+      quad: None,
+      drills: drill::Drills::load(),
+      screen: screen::Screen::Game,
+      modifiers: keyboard::Modifiers::default(),
+      avatar: game_settings.avatar_image(),
+      settings: game_settings,
+      last_input: time::Instant::now(),
+      demo: None,
+      theme: theme::Theme::default(),
+      theme_modified: theme::Theme::modified(),
+      dark: false,
+      debug_overlay: false,
+      console: console::Console::default(),
+      narration: narration::Narration::default(),
+      clock: clock::Clock::default(),
+      move_times: Vec::new(),
+      time_limit: None,
+      streak: 0,
+      best_time: None,
+      score: None,
+      total_score: 0,
+      combo: combo::Combo::default(),
+      suspended: false,
+      paused_by_focus: false,
+      scanner: scanner::Scanner::default(),
+      hot_seat: None,
+      session: session::Session::default(),
+      pack: None,
+      pack_board: false,
+      daily: daily::Daily::load(),
+      daily_day: None,
+      today: daily::today(),
+      stats: stats::Stats::load(),
+      history_filter: String::new(),
+      history_game: None,
+      tag_input: String::new(),
+      overlay: None,
+    };
+    game.new_game();
+    game.dark = game.settings.is_dark();
+    game.theme = theme::Theme::load(game.dark);
+    game.restyle_board();
+    if let Some(port) = game.settings.overlay_port {
+      let delay = time::Duration::from_secs(game.settings.watch_delay_seconds);
+      match overlay::Overlay::start(&game.settings.overlay_address, port, delay) {
+        Ok(overlay) => game.overlay = Some(overlay),
+        Err(error) => game.console.print(format!("Overlay failed to start on port {}: {}", port, error)),
+      }
+      game.update_overlay();
+    }
+    let command = if game.settings.fullscreen {
+      window::change_mode(window::Mode::Fullscreen)
+    } else {
+      iced::Command::none()
+    };
+    (game, command)
+  }
+
+  fn title(&self) -> String {
+    match self.engine.status {
+      GameStatus::Won => String::from("Minesweeper - You Won"),
+      GameStatus::Lost => String::from("Minesweeper - You Lost"),
+      _ if self.demo.is_some() => String::from("Minesweeper - Demo"),
+      _ => match self.daily_day.map(daily::date) {
+        Some((year, month, day)) => format!("Minesweeper - Daily {}-{:02}-{:02}", year, month, day),
+        None => format!("Minesweeper - {}", self.seed_text()),
+      },
+    }
+  }
+  
+  fn theme(&self) -> iced::Theme {
+    iced::Theme::custom(self.theme.palette())
+  }
+
+  fn scale_factor(&self) -> f64 {
+    self.settings.ui_scale() as f64
+  }
+
+  fn subscription(&self) -> iced::Subscription<Message> {
+    let events = iced::subscription::events_with(|event, status| match event {
+      iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => Some(Message::ModifiersChanged(modifiers)),
+      iced::Event::Mouse(mouse::Event::CursorMoved {position}) => Some(Message::CursorMoved(position)),
+      iced::Event::Mouse(mouse::Event::WheelScrolled {delta: mouse::ScrollDelta::Lines {x, y: _}}) if x < 0.0 => Some(Message::MouseBinding(String::from("tilt_left"))),
+      iced::Event::Mouse(mouse::Event::WheelScrolled {delta: mouse::ScrollDelta::Lines {x, y: _}}) if x > 0.0 => Some(Message::MouseBinding(String::from("tilt_right"))),
+      iced::Event::Mouse(mouse::Event::WheelScrolled {delta: mouse::ScrollDelta::Lines {y, ..}}) => Some(Message::WheelScrolled(y)),
+      iced::Event::Mouse(mouse::Event::WheelScrolled {delta: mouse::ScrollDelta::Pixels {y, ..}}) => Some(Message::WheelScrolled(y / 50.0)),
+      iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Middle)) => Some(Message::Pan(true)),
+      iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Middle)) => Some(Message::Pan(false)),
+      iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Other(number))) => Some(Message::MouseBinding(format!("button{}", number))),
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code: keyboard::KeyCode::F1, ..}) => Some(Message::ToggleHelp),
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code: keyboard::KeyCode::Escape, ..}) => Some(Message::Modal(modal::Message::Dismiss)),
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code: keyboard::KeyCode::F11, ..}) => Some(Message::ToggleFullscreen),
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code: keyboard::KeyCode::F12, ..}) => Some(Message::ToggleDebugOverlay),
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code: keyboard::KeyCode::Grave, ..}) => Some(Message::ToggleConsole),
+      iced::Event::Window(window::Event::Resized {width, height}) => Some(Message::Resized(width, height)),
+      iced::Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+      iced::Event::Window(window::Event::Focused) => Some(Message::Focused(true)),
+      iced::Event::Window(window::Event::Unfocused) => Some(Message::Focused(false)),
+      iced::Event::Mouse(mouse::Event::CursorLeft) => Some(Message::Press(None)),
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code, modifiers}) if modifiers.control() => match key_code {
+        keyboard::KeyCode::Equals | keyboard::KeyCode::Plus | keyboard::KeyCode::NumpadAdd => Some(Message::UiScale(UI_SCALE_STEP)),
+        keyboard::KeyCode::Minus | keyboard::KeyCode::NumpadSubtract => Some(Message::UiScale(-UI_SCALE_STEP)),
+        keyboard::KeyCode::Key0 | keyboard::KeyCode::Numpad0 => Some(Message::UiScale(0.0)),
+        keyboard::KeyCode::Z => Some(Message::Undo),
+        _ => Some(Message::Input),
+      },
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {key_code, modifiers}) if status == iced::event::Status::Ignored && !modifiers.control() && !modifiers.alt() => {
+        Some(board_key(key_code).unwrap_or(Message::Input))
+      },
+      iced::Event::Keyboard(keyboard::Event::KeyPressed {..}) | iced::Event::Mouse(mouse::Event::ButtonPressed(_)) => Some(Message::Input),
+      _ => None,
+    });
+    let mut subscriptions = vec![
+      events,
+      iced::time::every(time::Duration::from_secs(1)).map(|_| Message::CheckTheme),
+      iced::time::every(time::Duration::from_secs(60)).map(|_| Message::CheckDay),
+    ];
+    if self.camera.is_animating() || self.animations.is_playing() {
+      subscriptions.push(window::frames().map(|_| Message::Frame));
+    }
+    match &self.screen {
+      screen::Screen::Rush(rush) if !rush.ended => subscriptions.push(iced::time::every(time::Duration::from_secs(1)).map(|_| Message::Rush(rush::Message::Tick))),
+      screen::Screen::Comparison(comparison) if comparison.playing => subscriptions.push(iced::time::every(COMPARE_STEP).map(|_| Message::Comparison(compare::Message::Step))),
+      _ => (),
+    }
+    if !self.toasts.is_empty() {
+      subscriptions.push(iced::time::every(toast::STEP).map(Message::ToastTick));
+    }
+    if self.demo.is_some() {
+      subscriptions.push(iced::time::every(DEMO_STEP).map(|_| Message::DemoStep));
+    } else if self.clock.is_running() || (self.engine.is_fresh() && self.settings.idle_demo_seconds > 0) {
+      subscriptions.push(iced::time::every(self.settings.timer_tick()).map(Message::Tick));
+    }
+    iced::Subscription::batch(subscriptions)
+  }
+
+  fn update(&mut self, message: Message) -> iced::Command<Message> {
+    let command = self.handle(message);
+    self.restyle_board();
+    command
+  }
+
+  fn view(&self) -> iced::Element<'_, Message> {
+    let mut column = widget::Column::new().spacing(1);
+    //Quad boards show their own presses on the main engine, so only the end of the run comes from them.
+    let quad_status = self.quad.as_ref().map(quad::Quad::status).filter(|status| *status != GameStatus::Playing);
+    let mood = match quad_status.as_ref().unwrap_or(&self.engine.status) {
+      GameStatus::Playing if self.pressed.is_some() => face::Mood::Pressed,
+      GameStatus::Playing => face::Mood::Playing,
+      GameStatus::Lost => face::Mood::Lost,
+      GameStatus::Won => face::Mood::Won,
+    };
+    let mut top_row = widget::Row::new().padding(2);
+    top_row = top_row.push(cell::Cell {
+      content: '☰',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ToggleMenu),
+      ..Default::default()
+    });
+    if let Some(avatar) = &self.avatar {
+      top_row = top_row.push(widget::Canvas::new(face::Drawing {mood: face::Mood::Playing, indicator: face::Indicator::Emoji, progress: 0.0, image: Some(avatar)}).width(28).height(28));
+      top_row = top_row.push(widget::Space::with_width(4));
+    }
+    let avatar = if self.avatar.is_some() { "" } else { &self.settings.avatar };
+    let player = format!("{} {}", avatar, self.settings.player_name);
+    if !player.trim().is_empty() {
+      top_row = top_row.push(widget::Button::new(widget::Text::new(player.trim().to_string()).size(20).font(self.theme.header_font()))
+        .style(iced::theme::Button::Text)
+        .padding(0)
+        .on_press(Message::Modal(modal::Message::EditName)));
+      top_row = top_row.push(widget::Space::with_width(10));
+    }
+    if let Some(seat) = self.hot_seat.as_ref().filter(|_| !self.engine.is_over()) {
+      top_row = top_row.push(widget::Text::new(format!("▶ {}", seat.current().name)).size(20).font(self.theme.header_font()).style(self.theme.palette().primary));
+      top_row = top_row.push(widget::Space::with_width(10));
+    }
+    let mines_left = match &self.quad {
+      Some(quad) => quad.mines_left(),
+      None => mines_left(&self.engine),
+    };
+    top_row = top_row.push(widget::Text::new(format!("Mines: {}", self.settings.number(mines_left as u64))).size(20).font(self.theme.header_font()));
+    if self.engine.flag_budget() < self.engine.board.mines {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(format!("🚩 {}", self.engine.flag_budget() - self.engine.flag_count)).size(20).font(self.theme.header_font()));
+    }
+    if self.engine.treasures > 0 {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(format!("💎 {}/{}", self.engine.treasures_found, self.engine.treasures)).size(20).font(self.theme.header_font()));
+    }
+    if let Some(seat) = self.hot_seat.as_ref().filter(|seat| seat.mode == hotseat::Mode::Versus) {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(seat.scores()).size(20).font(self.theme.header_font()));
+    } else if self.engine.lives > 0 {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(format!("❤ {}", self.engine.lives)).size(20).font(self.theme.header_font()));
+    }
+    if self.engine.neighbors != engine::Neighbors::All {
+      //Numbers mean something else in the variants, so they are named where they can't be missed.
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(self.engine.neighbors.name()).size(20).font(self.theme.header_font()).style(self.theme.palette().danger));
+    }
+    if let (Some((pack, results)), true) = (&self.pack, self.pack_board) {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(format!("🏆 {}/{}", results.games.len() + 1, pack.boards.len())).size(20).font(self.theme.header_font()));
+    }
+    if self.engine.gradient {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new("Gradient ▸").size(20).font(self.theme.header_font()).style(self.theme.palette().danger));
+    }
+    top_row = top_row.push(widget::Space::with_width(iced::Length::Fill));
+    top_row = match (self.theme.face_image(mood), self.theme.face) {
+      (None, face::Indicator::Emoji) => top_row.push(cell::Cell {
+        content: mood.emoji(),
+        padding: [5,2].into(),
+        size: 18,
+        length: 28,
+        on_left_click: Some(Message::NewGame),
+        ..Default::default()
+      }),
+      (None, face::Indicator::Text) => top_row.push(widget::Button::new(widget::Text::new(mood.text()).size(20).font(self.theme.header_font()))
+        .style(iced::theme::Button::Text)
+        .padding(0)
+        .on_press(Message::NewGame)),
+      (image, indicator) => {
+        let progress = self.engine.revealed_count as f32 / (self.engine.board.width * self.engine.board.height - self.engine.board.mines) as f32;
+        top_row.push(widget::Button::new(widget::Canvas::new(face::Drawing {mood, indicator, progress, image}).width(28).height(28))
+          .style(iced::theme::Button::Text)
+          .padding(0)
+          .on_press(Message::NewGame))
+      },
+    };
+    if let Some(toast) = self.toasts.current() {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Container::new(widget::Text::new(toast).size(16))
+        .padding([2, 8])
+        .style(|theme: &iced::Theme| widget::container::Appearance {
+          background: Some(iced::Background::Color(theme.palette().text)),
+          text_color: Some(theme.palette().background),
+          border_radius: 4.0.into(),
+          ..Default::default()
+        }));
+    }
+    top_row = top_row.push(widget::Space::with_width(iced::Length::Fill));
+    let status_text = if self.debug_overlay {
+      format!("{:?} revealed {} flags {}", self.engine.status, self.engine.revealed_count, self.engine.flag_count)
+    } else {
+      match (&self.demo, self.score) {
+        (Some(Demo {last_move: Some(step), ..}), _) => format!("Demo: {}", step.reason.describe(&self.engine.board)),
+        (Some(_), _) => String::from("Demo"),
+        (None, Some(score)) => format!("Score: {} Total: {}", self.settings.number(score), self.settings.number(self.total_score)),
+        (None, None) if self.clock.is_paused() => String::from("Paused"),
+        (None, None) => match self.best_time {
+          Some(best) => format!("Time: {} Best: {}", self.settings.time(self.clock.elapsed()), self.settings.time(time::Duration::from_secs_f32(best))),
+          None => format!("Time: {}", self.settings.time(self.clock.elapsed())),
+        },
+      }
+    };
+    let mut status = widget::Text::new(status_text).size(20).font(self.theme.header_font());
+    if let (Some(limit), None, None, false) = (self.time_limit, &self.demo, self.score, self.debug_overlay) {
+      //The countdown replaces the clock, turning red for the last 10 seconds.
+      let remaining = self.clock.remaining(limit);
+      status = widget::Text::new(format!("⏳ {}", self.settings.time(remaining))).size(24).font(self.theme.header_font());
+      if remaining.as_secs() <= 10 {
+        status = status.style(self.theme.palette().danger);
+      }
+    }
+    if self.score.is_some() && self.demo.is_none() && !self.debug_overlay {
+      top_row = top_row.push(widget::Tooltip::new(status, self.guess_summary(), widget::tooltip::Position::Bottom)
+        .style(|theme: &iced::Theme| widget::container::Appearance {
+          background: Some(iced::Background::Color(theme.palette().text)),
+          text_color: Some(theme.palette().background),
+          ..Default::default()
+        }));
+    } else {
+      top_row = top_row.push(status);
+    }
+    if self.settings.show_pace && self.demo.is_none() && self.quad.is_none() {
+      let seconds = self.clock.elapsed().as_secs_f32();
+      let pace = if seconds > 0.0 { self.engine.solved_three_bv() as f32 / seconds } else { 0.0 };
+      let mut text = widget::Text::new(format!("{:.2} 3BV/s", pace)).size(20).font(self.theme.header_font());
+      if self.settings.target_pace > 0.0 && seconds > 0.0 {
+        let palette = self.theme.palette();
+        text = text.style(if pace >= self.settings.target_pace { palette.success } else { palette.danger });
+      }
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(text);
+    }
+    if self.settings.territory && self.demo.is_none() && self.quad.is_none() {
+      //Finished games are already in the total.
+      let territory = scoring::territory(&self.engine);
+      let total = self.stats.territory + if self.engine.is_over() { 0 } else { territory };
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(format!("🗺 {} Total: {}", self.settings.number(territory), self.settings.number(total))).size(20).font(self.theme.header_font()));
+    }
+    if self.settings.scanner && self.demo.is_none() && self.quad.is_none() {
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(cell::Cell {
+        content: '📡',
+        padding: [5,2].into(),
+        size: 18,
+        length: 28,
+        on_left_click: Some(Message::ArmScanner),
+        pressed: self.scanner.armed,
+        ..Default::default()
+      });
+      top_row = top_row.push(widget::Text::new(format!("{}/{}", self.scanner.charges, scanner::MAX_CHARGES)).size(20).font(self.theme.header_font()));
+    }
+    if self.settings.combo && self.demo.is_none() && !self.engine.is_over() {
+      let moves = self.combo.current();
+      top_row = top_row.push(widget::Space::with_width(10));
+      top_row = top_row.push(widget::Text::new(format!("Combo {} x{:.1}", moves, scoring::combo_multiplier(moves))).size(20).font(self.theme.header_font()));
+    }
+    top_row = top_row.push(cell::Cell {
+      content: '🎨',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ToggleColors),
+      ..Default::default()
+    });
+    top_row = top_row.push(cell::Cell {
+      content: '❔',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ToggleHelp),
+      ..Default::default()
+    });
+    top_row = top_row.push(cell::Cell {
+      content: '🔊',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ToggleAudio),
+      ..Default::default()
+    });
+    top_row = top_row.push(cell::Cell {
+      content: '📈',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ToggleStats),
+      ..Default::default()
+    });
+    top_row = top_row.push(cell::Cell {
+      content: '📅',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ToggleCalendar),
+      ..Default::default()
+    });
+    top_row = top_row.push(cell::Cell {
+      content: '🐞',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::ReportIssue),
+      ..Default::default()
+    });
+    top_row = top_row.push(cell::Cell {
+      content: '🔗',
+      padding: [5,2].into(),
+      size: 18,
+      length: 28,
+      on_left_click: Some(Message::CopyLink),
+      ..Default::default()
+    });
+    column = column.push(top_row);
+    if self.debug_overlay && self.quad.is_none() {
+      column = column.push(self.view_time_travel());
+    }
+    if let Some(seat) = self.hot_seat.as_ref().filter(|_| self.engine.is_over()) {
+      for line in seat.summary() {
+        column = column.push(widget::Text::new(line).size(16));
+      }
+    }
+    let question = |text: String| widget::Text::new(text).size(16).style(self.theme.palette().danger);
+    match &self.modal {
+      Some(modal::Modal::FatalMove(_, (x, y))) => {
+        column = column.push(modal::view(question(format!("{} is certainly a mine. Play it anyway?", narration::cell_name(*x, *y))),
+          vec![("Play it", Message::Modal(modal::Message::ConfirmMove)), ("Cancel", Message::Modal(modal::Message::Dismiss))]));
+      },
+      Some(modal::Modal::Abandon(abandon)) => {
+        let text = match abandon {
+          modal::Abandon::NewGame => "Give up this game for a new one? It will count as abandoned",
+          modal::Abandon::Daily(_) => "Give up this game for the daily challenge? It will count as abandoned",
+          modal::Abandon::Retry(_) => "Give up this game to play that board again? It will count as abandoned",
+          modal::Abandon::Close => "Give up this game and quit? It will count as abandoned",
+        };
+        column = column.push(modal::view(question(String::from(text)), vec![("Give up", Message::Modal(modal::Message::ConfirmAbandon)), ("Keep playing", Message::Modal(modal::Message::Dismiss))]));
+      },
+      Some(modal::Modal::PlayerName(name)) => {
+        let input = widget::TextInput::new("Player name", name).id(modal::input_id()).on_input(|input| Message::Modal(modal::Message::NameInput(input))).on_submit(Message::Modal(modal::Message::SaveName)).size(16).width(240);
+        column = column.push(modal::view(input, vec![("Save", Message::Modal(modal::Message::SaveName)), ("Cancel", Message::Modal(modal::Message::Dismiss))]));
+      },
+      None => (),
+    }
+    if self.console.open {
+      column = column.push(self.view_console());
+    }
+    if self.narration.open {
+      column = column.push(self.view_narration());
+    }
+    let screen = match &self.screen {
+      screen::Screen::Game => None,
+      screen::Screen::Menu => Some(self.view_menu()),
+      screen::Screen::Colors => Some(self.view_colors()),
+      screen::Screen::Audio => Some(self.view_audio()),
+      screen::Screen::Help => Some(self.view_help()),
+      screen::Screen::Session => Some(self.view_session()),
+      screen::Screen::Stats(category) => Some(self.view_stats(category)),
+      &screen::Screen::Calendar(year, month) => Some(self.view_calendar((year, month))),
+      screen::Screen::Comparison(comparison) => Some(self.view_comparison(comparison)),
+      screen::Screen::Drill(drill) => Some(self.view_drill(drill)),
+      screen::Screen::Rush(rush) => Some(self.view_rush(rush)),
+    };
+    if let Some(screen) = screen {
+      return column.push(screen).into();
+    }
+    if self.clock.is_paused() {
+      //Covering the board keeps it from being studied, or clicked by accident, while the clock is stopped.
+      return column.push(widget::Container::new(widget::Text::new("Paused").size(40).font(self.theme.header_font()))
+        .width(iced::Length::Fill)
+        .height(iced::Length::Fill)
+        .center_x()
+        .center_y()).into();
+    }
+    if let Some(quad) = &self.quad {
+      return column.push(self.view_quad(quad)).into();
+    }
+    //Cells which look different from their cached drawing are drawn over it every frame.
+    let demo_move = self.demo.as_ref().and_then(|demo| demo.last_move);
+    let fatal_move = match self.modal {
+      Some(modal::Modal::FatalMove(_, mine)) => Some(mine),
+      _ => None,
+    };
+    let highlighted: Vec<(usize, usize)> = demo_move.or(self.hint).into_iter().flat_map(|step| step.cells()).chain(self.keyboard_cursor).chain(fatal_move).collect();
+    let pressed = self.pressed.filter(|_| !self.engine.is_over()).and_then(|(board, cell)| board.is_none().then_some(cell));
+    let mut live: Vec<((usize, usize), cell::Cell<Message>)> = Vec::new();
+    for (x, y) in self.animations.cells().chain(pressed).chain(highlighted.iter().copied()) {
+      if !self.engine.board.contains(x, y) || live.iter().any(|&(position, _)| position == (x, y)) {
+        continue;
+      }
+      let mut cell = self.main_cell(x, y);
+      match self.animations.progress(x, y) {
+        Some((animation::Kind::FlagPlaced, progress)) => cell.content_scale = animation::pop(progress),
+        //The flag is already gone, so it is drawn again while it fades out.
+        Some((animation::Kind::FlagRemoved, progress)) if cell.content == ' ' => {
+          cell.content = '🚩';
+          cell.size = 14;
+          cell.padding = 2.into();
+          cell.content_scale = 1.0 - progress;
+          cell.color.a = 1.0 - progress;
+        },
+        Some((animation::Kind::TooManyFlags, progress)) => {
+          cell.flash = animation::blink(progress);
+          if self.settings.dual_coding {
+            cell.content_scale = 1.0 - 0.4 * cell.flash;
+          }
+        },
+        _ => (),
+      }
+      cell.pressed = pressed == Some((x, y));
+      cell.highlight = highlighted.contains(&(x, y));
+      live.push(((x, y), cell));
+    }
+    let board = board::Board::new(&self.tiles, (self.engine.board.width, self.engine.board.height), |x, y| self.main_cell(x, y))
+      .length(CELL_LENGTH * self.camera.zoom)
+      .spacing(self.theme.cell_spacing())
+      .background(self.theme.grid_color())
+      .live(live);
+    let board = widget::Scrollable::new(board)
+      .id(board_scrollable_id())
+      .direction(scrollable::Direction::Both {
+        vertical: scrollable::Properties::default(),
+        horizontal: scrollable::Properties::default(),
+      })
+      .on_scroll(|viewport| Message::Scrolled(viewport.absolute_offset()));
+    //Keep the board centered when it is smaller than the window, like in fullscreen.
+    column = column.push(widget::Container::new(board)
+      .width(iced::Length::Fill)
+      .height(iced::Length::Fill)
+      .center_x()
+      .center_y());
+    column.into()
+  }
+}
@@ -0,0 +1,53 @@
+//! Questions which wait on an answer, shown as a bar under the header.
+//!
+//! Only one is open at a time. Escape dismisses it, the same as its last button.
+use iced::widget;
+use minesweeper::action::Action;
+
+/// Answers to the modals.
+#[derive(Clone, Debug)]
+pub enum Message {
+  /// Play the move waiting to be confirmed.
+  ConfirmMove,
+  /// Give up the game in progress, to go where the player asked.
+  ConfirmAbandon,
+  /// Close the open modal without doing what it asked about. Also Escape, which goes back to the game when none is open.
+  Dismiss,
+  /// Open the modal for typing the player's name.
+  EditName,
+  NameInput(String),
+  SaveName,
+}
+
+/// Ways of leaving a game before it is over.
+#[derive(Clone, Copy)]
+pub enum Abandon {
+  NewGame,
+  Daily(i64),
+  /// The board of a seed in the session's log, by its index.
+  Retry(usize),
+  Close,
+}
+
+pub enum Modal {
+  /// A move waiting to be confirmed, since it would reveal a mine, and the mine it would reveal.
+  FatalMove(Action, (usize, usize)),
+  /// Where the player wants to go, waiting on them confirming they are giving up the game in progress.
+  Abandon(Abandon),
+  /// The player's name, as typed so far.
+  PlayerName(String),
+}
+
+/// Id of the text input of modals which have one, for focusing it when they open.
+pub fn input_id() -> widget::text_input::Id {
+  widget::text_input::Id::new("modal")
+}
+
+/// A modal's bar: its question or input, then a button for each answer.
+pub fn view<'a, Message: Clone + 'a>(content: impl Into<iced::Element<'a, Message>>, answers: Vec<(&'a str, Message)>) -> iced::Element<'a, Message> {
+  let mut bar = widget::Row::new().spacing(10).padding([0, 4]).align_items(iced::Alignment::Center).push(content);
+  for (label, message) in answers {
+    bar = bar.push(widget::Button::new(label).on_press(message));
+  }
+  bar.into()
+}
@@ -0,0 +1,57 @@
+//! Moves described in words, like "Chorded B7, opened 4 cells", for the narration panel.
+//!
+//! Cells are named like a spreadsheet: columns are letters from A, and rows are numbers from 1.
+use crate::action::Action;
+use crate::engine::{Engine, GameStatus};
+
+//Older lines get dropped, so the log doesn't grow forever.
+const MAX_LINES: usize = 200;
+
+pub fn cell_name(x: usize, y: usize) -> String {
+  let mut column = String::new();
+  let mut x = x + 1;
+  while x > 0 {
+    x -= 1;
+    column.insert(0, (b'A' + (x % 26) as u8) as char);
+    x /= 26;
+  }
+  format!("{}{}", column, y + 1)
+}
+
+#[derive(Default)]
+pub struct Narration {
+  pub open: bool,
+  pub lines: Vec<String>,
+}
+
+impl Narration {
+  /// Describe `action`, given how many cells were revealed and flagged before it.
+  pub fn record(&mut self, action: Action, revealed_count: usize, flag_count: usize, engine: &Engine) {
+    let (x, y) = action.position();
+    let name = cell_name(x, y);
+    let opened = engine.revealed_count - revealed_count;
+    let line = match action {
+      Action::Reveal(..) if engine.status == GameStatus::Lost => format!("BOOM at {}", name),
+      Action::Chord(..) if engine.status == GameStatus::Lost => format!("Chorded {}, BOOM", name),
+      Action::Flag(..) if engine.flag_count > flag_count => format!("Flagged {}", name),
+      Action::Flag(..) if engine.flag_count < flag_count => format!("Unflagged {}", name),
+      Action::Flag(..) => return,
+      Action::Reveal(..) if opened == 1 => format!("Revealed {}", name),
+      Action::Reveal(..) => format!("Revealed {}, opened {} cells", name, opened),
+      Action::Chord(..) if opened == 0 => format!("Chorded {}, nothing opened", name),
+      Action::Chord(..) if opened == 1 => format!("Chorded {}, opened 1 cell", name),
+      Action::Chord(..) => format!("Chorded {}, opened {} cells", name, opened),
+    };
+    self.print(line);
+    if engine.status == GameStatus::Won {
+      self.print("Cleared the board!");
+    }
+  }
+
+  pub fn print(&mut self, line: impl Into<String>) {
+    self.lines.push(line.into());
+    if self.lines.len() > MAX_LINES {
+      self.lines.remove(0);
+    }
+  }
+}
@@ -0,0 +1,8 @@
+//! Native desktop notifications.
+
+/// Shows a notification without waiting for the system to take it. Failures are ignored, since notifications are only a nicety.
+pub fn send(summary: String, body: String) {
+  std::thread::spawn(move || {
+    let _ = notify_rust::Notification::new().appname("Minesweeper").summary(&summary).body(&body).show();
+  });
+}
@@ -0,0 +1,185 @@
+//! Stream overlay, served as a web page for OBS browser sources.
+//!
+//! Set `overlay_port` in the settings, then add `http://localhost:<port>` as a browser source.
+//! The page has a green background for chroma keying, and polls the game for the timer, mines left, 3BV/s and win streak.
+//!
+//! `http://<address>:<port>/watch` shows the board as the player sees it, for someone else to watch the game live.
+//! It runs `watch_delay_seconds` behind, so a watcher can't coach the player on what is on the board right now.
+use crate::clock;
+use minesweeper::engine::{Board, CellStatus, CellValue};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{io, net, thread};
+
+#[derive(Clone, Default)]
+pub struct Stats {
+  pub clock: clock::Clock,
+  pub mines_left: usize,
+  pub solved_three_bv: usize,
+  pub streak: u32,
+  /// What the player sees, as [`visible_rows`].
+  pub board: Vec<String>,
+}
+
+/// The board as the player sees it, a line of text for each row. Nothing covered is given away.
+pub fn visible_rows(board: &Board) -> Vec<String> {
+  (0..board.height).map(|y| (0..board.width).map(|x| match (board[x][y].status, board[x][y].value) {
+    (CellStatus::Covered, _) => '■',
+    (CellStatus::Flagged, _) => '⚑',
+    (CellStatus::AntiFlagged, _) => '⚐',
+    (CellStatus::Revealed, CellValue::Mined | CellValue::AntiMine) => '✹',
+    (CellStatus::Revealed, CellValue::Number(0) | CellValue::Treasure(0)) => '·',
+    (CellStatus::Revealed, CellValue::Number(number) | CellValue::Treasure(number)) if number < 0 => '-',
+    (CellStatus::Revealed, CellValue::Number(number) | CellValue::Treasure(number)) => char::from_digit(number as u32, 10).unwrap_or('?'),
+  }).collect()).collect()
+}
+
+#[derive(Serialize)]
+struct Snapshot {
+  seconds: f32,
+  mines_left: usize,
+  pace: f32,
+  streak: u32,
+}
+
+impl Stats {
+  fn snapshot(&self) -> Snapshot {
+    let seconds = self.clock.elapsed().as_secs_f32();
+    Snapshot {
+      seconds,
+      mines_left: self.mines_left,
+      pace: if seconds > 0.0 { self.solved_three_bv as f32 / seconds } else { 0.0 },
+      streak: self.streak,
+    }
+  }
+}
+
+//How long a connection may keep its thread waiting, like a browser opening one ahead of time and never using it.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><style>
+body { background: #00ff00; color: white; font: bold 48px sans-serif; text-shadow: 2px 2px 4px black; margin: 16px; }
+span { display: inline-block; min-width: 4em; }
+</style></head><body>
+<div>⏱ <span id="seconds">0</span> 💣 <span id="mines_left">0</span></div>
+<div>3BV/s <span id="pace">0</span> 🔥 <span id="streak">0</span></div>
+<script>
+setInterval(async () => {
+  const stats = await (await fetch("/stats.json")).json();
+  document.getElementById("seconds").textContent = stats.seconds.toFixed(1);
+  document.getElementById("mines_left").textContent = stats.mines_left;
+  document.getElementById("pace").textContent = stats.pace.toFixed(2);
+  document.getElementById("streak").textContent = stats.streak;
+}, 200);
+</script></body></html>"#;
+
+const WATCH_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><style>
+body { background: #202020; color: #e0e0e0; margin: 16px; }
+pre { font: 24px/1.1 monospace; }
+</style></head><body>
+<pre id="board"></pre>
+<script>
+setInterval(async () => {
+  const board = await (await fetch("/board.json")).json();
+  document.getElementById("board").textContent = board.join("\n");
+}, 500);
+</script></body></html>"#;
+
+#[derive(Default)]
+struct Shared {
+  stats: Stats,
+  /// Boards as they changed, oldest first, and when.
+  boards: VecDeque<(Instant, Vec<String>)>,
+}
+
+impl Shared {
+  fn push_board(&mut self, now: Instant, board: Vec<String>, delay: Duration) {
+    if self.boards.back().is_none_or(|(_, last)| *last != board) {
+      self.boards.push_back((now, board));
+    }
+    //Only the newest board older than the delay is still needed from before it.
+    while self.boards.get(1).is_some_and(|&(time, _)| now.duration_since(time) >= delay) {
+      self.boards.pop_front();
+    }
+  }
+
+  /// The board as it was `delay` ago, or nothing when the game hasn't been going that long.
+  fn delayed_board(&self, now: Instant, delay: Duration) -> Vec<String> {
+    self.boards.iter().rev().find(|&&(time, _)| now.duration_since(time) >= delay).map(|(_, board)| board.clone()).unwrap_or_default()
+  }
+}
+
+pub struct Overlay {
+  shared: Arc<Mutex<Shared>>,
+  delay: Duration,
+}
+
+impl Overlay {
+  /// Starts serving on `address` in the background, with the board watched `delay` behind.
+  pub fn start(address: &str, port: u16, delay: Duration) -> io::Result<Overlay> {
+    let listener = net::TcpListener::bind((address, port))?;
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    let served = shared.clone();
+    thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        //Each connection gets its own thread, so a slow or idle one doesn't hold up the rest,
+        //and a broken connection only affects that one request.
+        let served = served.clone();
+        thread::spawn(move || {
+          let _ = stream.set_read_timeout(Some(TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+            .and_then(|()| respond(stream, &served, delay));
+        });
+      }
+    });
+    Ok(Overlay {shared, delay})
+  }
+
+  pub fn update(&self, stats: Stats) {
+    if let Ok(mut shared) = self.shared.lock() {
+      shared.push_board(Instant::now(), stats.board.clone(), self.delay);
+      shared.stats = stats;
+    }
+  }
+}
+
+fn respond(mut stream: net::TcpStream, shared: &Mutex<Shared>, delay: Duration) -> io::Result<()> {
+  let mut request_line = String::new();
+  BufReader::new(&stream).read_line(&mut request_line)?;
+  let (content_type, body) = if request_line.starts_with("GET /stats.json") {
+    let snapshot = shared.lock().map(|shared| shared.stats.snapshot()).unwrap_or_else(|_| Stats::default().snapshot());
+    ("application/json", serde_json::to_string(&snapshot).unwrap_or_default())
+  } else if request_line.starts_with("GET /board.json") {
+    let board = shared.lock().map(|shared| shared.delayed_board(Instant::now(), delay)).unwrap_or_default();
+    ("application/json", serde_json::to_string(&board).unwrap_or_default())
+  } else if request_line.starts_with("GET /watch") {
+    ("text/html; charset=utf-8", String::from(WATCH_PAGE))
+  } else {
+    ("text/html; charset=utf-8", String::from(PAGE))
+  };
+  write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", content_type, body.len(), body)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn watchers_see_the_board_late() {
+    let delay = Duration::from_secs(10);
+    let start = Instant::now();
+    let mut shared = Shared::default();
+    for second in 0..30 {
+      shared.push_board(start + Duration::from_secs(second), vec![second.to_string()], delay);
+    }
+    let now = start + Duration::from_secs(29);
+    assert_eq!(shared.delayed_board(now, delay), vec![String::from("19")]);
+    assert_eq!(shared.boards.len(), 11);
+    assert!(Shared::default().delayed_board(now, delay).is_empty());
+  }
+}
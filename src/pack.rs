@@ -0,0 +1,242 @@
+//! Seed packs for tournaments: boards and their rules, in the order everyone plays them,
+//! and the results file with a replay of each board which goes back to the organizer.
+//!
+//! A pack's boards are replays without moves. Packs are signed with the organizer's key, and results with the player's, using ed25519.
+//! Each file carries the public half of the key it was signed with, and the signature of the rest of the file, so a file changed after it was signed doesn't load.
+//! A signature only says which key signed, so a pack can be trusted when its key is the one the organizer announced, and results when their key is the one the player gave the organizer.
+//! Results still have to hold replays which, played back, end up the way they say and match the pack's boards.
+use crate::engine::{Engine, GameStatus};
+use crate::replay::Replay;
+use crate::schema::{self, Schema};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{fs, io, path};
+
+/// Where the signature goes.
+const SIGNATURE: &str = "signature";
+/// Where the public key goes.
+const KEY: &str = "key";
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+  if text.len() != N * 2 || !text.is_ascii() {
+    return None;
+  }
+  let mut bytes = [0; N];
+  for (index, byte) in bytes.iter_mut().enumerate() {
+    *byte = u8::from_str_radix(&text[index * 2..index * 2 + 2], 16).ok()?;
+  }
+  Some(bytes)
+}
+
+/// What gets signed: `document` as JSON, leaving its own signature out.
+fn signed_text(mut document: Value) -> String {
+  if let Value::Object(map) = &mut document {
+    map.remove(SIGNATURE);
+  }
+  document.to_string()
+}
+
+/// The signature of `document` by `key`, in hex.
+fn sign(document: Value, key: &SigningKey) -> String {
+  to_hex(&key.sign(signed_text(document).as_bytes()).to_bytes())
+}
+
+/// Reads `json`, failing when it isn't signed by the key it holds.
+/// The signature is of the file as it was written, so it is checked before the file is migrated to the current format.
+fn read_signed<T: DeserializeOwned>(schema: &Schema, json: &str) -> Result<T, String> {
+  let document: Value = serde_json::from_str(json).map_err(|error| error.to_string())?;
+  let key = document.get(KEY).and_then(Value::as_str).and_then(from_hex).and_then(|key| VerifyingKey::from_bytes(&key).ok())
+    .ok_or("The file has no key to check its signature with")?;
+  let signature = document.get(SIGNATURE).and_then(Value::as_str).and_then(from_hex).map(|signature| Signature::from_bytes(&signature))
+    .ok_or("The file isn't signed")?;
+  key.verify(signed_text(document.clone()).as_bytes(), &signature).map_err(|_| "The file doesn't match its signature")?;
+  schema.read(document)
+}
+
+/// The public half of `key`, in hex, which is what others know the signer by.
+pub fn public_key(key: &SigningKey) -> String {
+  to_hex(key.verifying_key().as_bytes())
+}
+
+/// The secret key saved at `path`, or a new one saved there when there is none yet, along with whether it is new.
+pub fn load_or_create_key(path: &path::Path) -> Result<(SigningKey, bool), String> {
+  let error = |error: io::Error| format!("{}: {}", path.display(), error);
+  match fs::read_to_string(path) {
+    Ok(text) => from_hex(text.trim()).map(|key| (SigningKey::from_bytes(&key), false)).ok_or_else(|| format!("{} isn't a key", path.display())),
+    Err(read) if read.kind() == io::ErrorKind::NotFound => {
+      let key = SigningKey::from_bytes(&rand::random());
+      if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(error)?;
+      }
+      let mut options = fs::OpenOptions::new();
+      options.write(true).create_new(true);
+      //Only its owner gets to read a secret key.
+      #[cfg(unix)]
+      std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+      io::Write::write_all(&mut options.open(path).map_err(error)?, to_hex(&key.to_bytes()).as_bytes()).map_err(error)?;
+      Ok((key, true))
+    },
+    Err(read) => Err(error(read)),
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pack {
+  pub name: String,
+  /// The boards, in the order they are played.
+  pub boards: Vec<Replay>,
+  /// The organizer's public key, in hex.
+  #[serde(default)]
+  pub key: String,
+  #[serde(default)]
+  pub signature: String,
+}
+
+impl Pack {
+  /// Signs the pack with the organizer's `key`.
+  pub fn sign(&mut self, key: &SigningKey) {
+    self.key = public_key(key);
+    self.signature = sign(schema::PACK.write(self), key);
+  }
+
+  /// Reads a pack, failing when it was changed since it was signed.
+  pub fn from_json(json: &str) -> Result<Pack, String> {
+    let pack: Pack = read_signed(&schema::PACK, json)?;
+    if pack.boards.is_empty() {
+      return Err(format!("The pack {} has no boards", pack.name));
+    }
+    Ok(pack)
+  }
+
+  pub fn to_json(&self) -> String {
+    schema::PACK.to_json(self)
+  }
+
+  /// The board to play after the games in `results`, or `None` when every board has been played.
+  pub fn next<'a>(&'a self, results: &Results) -> Option<&'a Replay> {
+    self.boards.get(results.games.len())
+  }
+}
+
+/// A player's games of a pack, one for each board played so far, in order.
+#[derive(Serialize, Deserialize)]
+pub struct Results {
+  pub pack: String,
+  /// The pack's signature, which tells it apart from other packs and other versions of it.
+  pub pack_signature: String,
+  pub player: String,
+  pub games: Vec<Replay>,
+  /// The player's public key, in hex.
+  #[serde(default)]
+  pub key: String,
+  #[serde(default)]
+  pub signature: String,
+}
+
+impl Results {
+  pub fn new(pack: &Pack, player: &str) -> Results {
+    Results {pack: pack.name.clone(), pack_signature: pack.signature.clone(), player: String::from(player), games: Vec::new(), key: String::new(), signature: String::new()}
+  }
+
+  /// Adds the game played on the pack's next board.
+  pub fn add(&mut self, game: Replay) {
+    self.games.push(game);
+  }
+
+  /// Signs the results with the player's `key`, before sending them in.
+  pub fn sign(&mut self, key: &SigningKey) {
+    self.key = public_key(key);
+    self.signature = sign(schema::RESULTS.write(self), key);
+  }
+
+  pub fn to_json(&self) -> String {
+    schema::RESULTS.to_json(self)
+  }
+
+  /// Reads results, failing when they were changed since they were signed.
+  pub fn from_json(json: &str) -> Result<Results, String> {
+    read_signed(&schema::RESULTS, json)
+  }
+
+  /// Plays every game back, making sure the results are of `pack` and each game was played on its board,
+  /// and returns how they ended.
+  pub fn check(&self, pack: &Pack) -> Result<Vec<Engine>, String> {
+    if self.pack_signature != pack.signature {
+      return Err(format!("The results are of another pack than {}", pack.name));
+    }
+    if self.games.len() > pack.boards.len() {
+      return Err(format!("{} games for {} boards", self.games.len(), pack.boards.len()));
+    }
+    self.games.iter().zip(&pack.boards).enumerate().map(|(index, (game, board))| {
+      if game.board() != board.board() {
+        return Err(format!("Game {} wasn't played on board {}", index + 1, index + 1));
+      }
+      game.run().map_err(|error| format!("Game {}: {}", index + 1, error))
+    }).collect()
+  }
+
+  /// Boards won out of those played, in words.
+  pub fn describe(&self, pack: &Pack) -> String {
+    let won = self.check(pack).map_or(0, |engines| engines.iter().filter(|engine| engine.status == GameStatus::Won).count());
+    format!("{}: {} of {} boards played, {} won", self.pack, self.games.len(), pack.boards.len(), won)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::action::Action;
+  use crate::config::GameConfig;
+
+  #[test]
+  fn results_are_checked_against_the_pack() {
+    let (organizer, player) = (SigningKey::from_bytes(&[1; 32]), SigningKey::from_bytes(&[2; 32]));
+    let mut pack = Pack {name: String::from("Cup"), boards: vec![Replay::record(&GameConfig::new(3).build()), Replay::record(&GameConfig::new(4).with_fair_layout(true).build())], key: String::new(), signature: String::new()};
+    pack.sign(&organizer);
+    let pack = Pack::from_json(&pack.to_json()).unwrap();
+    assert_eq!(pack.key, public_key(&organizer));
+    let mut results = Results::new(&pack, "Ann");
+    let mut engine = pack.next(&results).unwrap().start();
+    engine.act(Action::Reveal(0, 0));
+    results.add(Replay::record(&engine));
+    results.sign(&player);
+    let results = Results::from_json(&results.to_json()).unwrap();
+    assert_eq!(results.check(&pack).unwrap().len(), 1);
+    assert!(pack.next(&results).unwrap().config.fair);
+
+    let mut edited = pack.clone();
+    edited.name = String::from("Other cup");
+    assert!(Pack::from_json(&edited.to_json()).is_err());
+    let mut edited = Results::from_json(&results.to_json()).unwrap();
+    edited.player = String::from("Bob");
+    assert!(Results::from_json(&edited.to_json()).is_err());
+    //Claiming the player's key doesn't help without the secret half.
+    edited.sign(&organizer);
+    edited.key = results.key.clone();
+    assert!(Results::from_json(&edited.to_json()).is_err());
+  }
+
+  #[test]
+  fn packs_signed_again_show_whose_key_signed() {
+    let organizer = SigningKey::from_bytes(&[1; 32]);
+    let mut pack = Pack {name: String::from("Cup"), boards: vec![Replay::record(&GameConfig::new(3).build())], key: String::new(), signature: String::new()};
+    pack.sign(&organizer);
+    //Someone else can sign a changed pack, but only with their own key, which isn't the one the organizer announced.
+    let mut tampered = pack.clone();
+    tampered.boards = vec![Replay::record(&GameConfig::new(4).build())];
+    tampered.sign(&SigningKey::from_bytes(&[3; 32]));
+    let tampered = Pack::from_json(&tampered.to_json()).unwrap();
+    assert_ne!(tampered.key, public_key(&organizer));
+    //Results signed for a board of another pack don't pass the check against the real one.
+    let mut results = Results::new(&tampered, "Ann");
+    results.add(Replay::record(&GameConfig::new(4).build()));
+    results.sign(&SigningKey::from_bytes(&[2; 32]));
+    let results = Results::from_json(&results.to_json()).unwrap();
+    assert_eq!(results.check(&pack).err().unwrap(), "The results are of another pack than Cup");
+  }
+}
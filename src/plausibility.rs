@@ -0,0 +1,74 @@
+//! Checks on whether a replay looks like a person played it, for moderating shared times.
+//!
+//! Nothing here is proof. The luck check goes by the odds of [`probability::Exact`], which are only an estimate on boards too tangled to count,
+//! and treats the cells of a chord as if they were independent, so a suspicion is a reason to look closer at a replay, not to throw it out.
+use crate::action::Action;
+use crate::engine::{CellStatus, GameStatus};
+use crate::replay::Replay;
+use crate::probability;
+use crate::solver::Solver;
+
+/// Moves on different cells closer together than this are faster than anyone reacts.
+pub const MIN_REACTION_MS: u64 = 40;
+/// More moves than this in any one second is faster than anyone clicks.
+pub const MAX_MOVES_PER_SECOND: usize = 15;
+/// Surviving every guess with less of a chance than this is too lucky to believe.
+pub const MIN_LUCK: f64 = 0.001;
+
+/// Reasons a replay looks suspicious, in words. Empty when nothing stands out.
+pub fn check(replay: &Replay) -> Vec<String> {
+  let mut suspicions = Vec::new();
+  let times = &replay.times;
+  if !times.is_empty() {
+    let fast = times.windows(2).zip(replay.moves.windows(2))
+      .filter(|(times, moves)| times[1].saturating_sub(times[0]) < MIN_REACTION_MS && moves[0].position() != moves[1].position())
+      .count();
+    if fast > 0 {
+      suspicions.push(format!("{} moves came less than {}ms after the one before, on another cell", fast, MIN_REACTION_MS));
+    }
+    let busiest = (0..times.len()).map(|index| times[index..].iter().take_while(|&&time| time < times[index] + 1000).count()).max().unwrap_or_default();
+    if busiest > MAX_MOVES_PER_SECOND {
+      suspicions.push(format!("{} moves within one second", busiest));
+    }
+  }
+
+  //Reveals the numbers didn't prove safe, and how likely it was to get through all of them.
+  let mut engine = replay.start();
+  let mut guesses = 0;
+  let mut luck = 1.0;
+  for &action in &replay.moves {
+    let (x, y) = action.position();
+    let cells: Vec<(usize, usize)> = match action {
+      //The first click has nothing to go by, so it isn't counted.
+      _ if engine.revealed_count == 0 => Vec::new(),
+      Action::Reveal(..) => vec![(x, y)],
+      Action::Chord(..) => engine.neighbors.of(&engine.board, x, y).filter(|&(x, y)| engine.board[x][y].status == CellStatus::Covered).collect(),
+      Action::Flag(..) => Vec::new(),
+    };
+    let danger = probability::Exact.danger(&engine.board, engine.neighbors);
+    let survival: f64 = cells.iter().filter_map(|&(x, y)| danger[x][y]).map(|danger| 1.0 - danger as f64).product();
+    engine.act(action);
+    if survival < 1.0 && engine.status != GameStatus::Lost {
+      guesses += 1;
+      luck *= survival;
+    }
+  }
+  //The solver can't read boards with antimines, so it can't say how lucky their guesses were.
+  if luck < MIN_LUCK && engine.antimines == 0 {
+    suspicions.push(format!("Got through {} guesses with a {:.3}% chance", guesses, luck * 100.0));
+  }
+  suspicions
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn inhuman_timing_is_suspicious() {
+    let replay = Replay::from_json(include_str!("../tests/replays/win.json")).unwrap();
+    assert!(check(&replay).is_empty());
+    let timed = Replay {times: (0..replay.moves.len() as u64).map(|index| index * 10).collect(), ..replay};
+    assert_eq!(check(&timed).len(), 2);
+  }
+}
@@ -0,0 +1,344 @@
+//! The exact chance of each covered cell being a mine, given what the player can see and how many mines the board has.
+//!
+//! Every way of placing mines around the numbers is counted, weighted by the ways of placing the rest of the mines on the cells no number touches.
+//! Cells the numbers settle are taken out first, and the remaining cells next to numbers are split into groups no number links,
+//! which are counted on their own and combined by how many mines each takes. That keeps a board with several small fronts quick,
+//! but one huge front can still have too many ways to count. Past [`MAX_STEPS`], the solver falls back to [`solver::danger`]'s estimate.
+use crate::engine::{Board, Cell, CellStatus, CellValue, Neighbors};
+use crate::solver::{self, Solver};
+
+/// Most cells tried one way or the other while counting, across all groups, before giving up on exact odds.
+pub const MAX_STEPS: usize = 200_000;
+
+/// Exact odds, falling back to the estimate on boards with too many ways to count.
+pub struct Exact;
+
+impl Solver for Exact {
+  fn name(&self) -> &'static str {
+    "exact"
+  }
+
+  fn known(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<bool>>> {
+    match odds(board, neighbors) {
+      Some(odds) => odds.known,
+      None => solver::deduce(board, neighbors),
+    }
+  }
+
+  fn danger(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<f32>>> {
+    match odds(board, neighbors) {
+      Some(odds) => odds.danger,
+      None => solver::danger(board, neighbors),
+    }
+  }
+}
+
+struct Odds {
+  known: Vec<Vec<Option<bool>>>,
+  danger: Vec<Vec<Option<f32>>>,
+}
+
+/// A number's mines still to place among its unsettled neighbors, given as indexes into the group's cells.
+struct Constraint {
+  missing: usize,
+  cells: Vec<usize>,
+}
+
+/// The cells of a group and the ways its mines can be placed, by how many mines they take.
+struct Group {
+  cells: Vec<(usize, usize)>,
+  /// Ways of placing `k` mines, at index `k`.
+  ways: Vec<f64>,
+  /// Of those ways, how many put a mine on each cell, at `[k][cell]`.
+  mines: Vec<Vec<f64>>,
+}
+
+/// Weights of `a` and `b` combined by adding up their mine counts.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+  let mut sum = vec![0.0; a.len() + b.len() - 1];
+  for (i, &a) in a.iter().enumerate() {
+    for (j, &b) in b.iter().enumerate() {
+      sum[i + j] += a * b;
+    }
+  }
+  sum
+}
+
+/// `None` when the board has too many ways to count, or none at all, like when antimines make the numbers mean something else.
+fn odds(board: &Board, neighbors: Neighbors) -> Option<Odds> {
+  let mut known = solver::deduce(board, neighbors);
+  let cells = || board.positions();
+  let mines_known = cells().filter(|&(x, y)| known[x][y] == Some(true)).count();
+  let mines_left = board.mines.checked_sub(mines_known)?;
+
+  //Every number still missing mines, as a constraint on its unsettled neighbors.
+  let mut constraints: Vec<(usize, Vec<(usize, usize)>)> = Vec::new();
+  for (x, y) in cells() {
+    let Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} = board[x][y] else { continue };
+    let number = usize::try_from(number).ok()?;
+    let unsettled: Vec<(usize, usize)> = neighbors.of(board, x, y).filter(|&(x, y)| known[x][y].is_none()).collect();
+    if !unsettled.is_empty() {
+      let mines = neighbors.of(board, x, y).filter(|&(x, y)| known[x][y] == Some(true)).count();
+      constraints.push((number.checked_sub(mines)?, unsettled));
+    }
+  }
+
+  //Numbers sharing a cell go in the same group.
+  let mut groups: Vec<Vec<usize>> = Vec::new();
+  for (index, (_, unsettled)) in constraints.iter().enumerate() {
+    let shares = |group: &Vec<usize>| group.iter().any(|&number| constraints[number].1.iter().any(|cell| unsettled.contains(cell)));
+    let (linked, mut rest): (Vec<_>, Vec<_>) = groups.into_iter().partition(shares);
+    rest.push(linked.into_iter().flatten().chain([index]).collect());
+    groups = rest;
+  }
+
+  let mut steps = 0;
+  let mut counted = Vec::new();
+  for numbers in groups {
+    let mut group_cells: Vec<(usize, usize)> = numbers.iter().flat_map(|&number| constraints[number].1.iter().copied()).collect();
+    group_cells.sort_unstable();
+    group_cells.dedup();
+    let constraints: Vec<Constraint> = numbers.iter().map(|&number| {
+      let (missing, unsettled) = &constraints[number];
+      Constraint {missing: *missing, cells: unsettled.iter().map(|cell| group_cells.binary_search(cell).expect("Numbers' cells are in their group")).collect()}
+    }).collect();
+    counted.push(count(group_cells, &constraints, mines_left, &mut steps)?);
+  }
+
+  //The cells no number touches share whatever mines are left evenly.
+  let rest: Vec<(usize, usize)> = cells().filter(|&(x, y)| known[x][y].is_none() && !counted.iter().any(|group| group.cells.contains(&(x, y)))).collect();
+  //Ways of placing the leftover mines on those cells, from ln of factorials so large boards don't overflow.
+  let ln_factorial: Vec<f64> = std::iter::once(0.0).chain((1..=rest.len()).scan(0.0, |sum, n| {
+    *sum += (n as f64).ln();
+    Some(*sum)
+  })).collect();
+  let ln_choose = |k: usize| (k <= rest.len()).then(|| ln_factorial[rest.len()] - ln_factorial[k] - ln_factorial[rest.len() - k]);
+  let largest = (0..=mines_left).filter_map(ln_choose).fold(f64::MIN, f64::max);
+  let rest_ways = |front_mines: usize| mines_left.checked_sub(front_mines).and_then(ln_choose).map_or(0.0, |ln| (ln - largest).exp());
+
+  let all = counted.iter().fold(vec![1.0], |all, group| convolve(&all, &group.ways));
+  let total: f64 = all.iter().enumerate().map(|(mines, ways)| ways * rest_ways(mines)).sum();
+  if total <= 0.0 || !total.is_finite() {
+    return None;
+  }
+
+  let mut danger = board.grid(None);
+  for (index, group) in counted.iter().enumerate() {
+    let others = counted.iter().enumerate().filter(|&(other, _)| other != index).fold(vec![1.0], |all, (_, group)| convolve(&all, &group.ways));
+    //How much the group placing `k` mines weighs, with every way of placing the other mines.
+    let weight: Vec<f64> = (0..group.ways.len()).map(|k| others.iter().enumerate().map(|(mines, ways)| ways * rest_ways(k + mines)).sum()).collect();
+    for (cell, &(x, y)) in group.cells.iter().enumerate() {
+      let mined: f64 = (0..group.ways.len()).map(|k| group.mines[k][cell] * weight[k]).sum();
+      danger[x][y] = Some((mined / total) as f32);
+      //The counts are whole numbers, so these comparisons are exact.
+      if mined == 0.0 {
+        known[x][y] = Some(false);
+      } else if (0..group.ways.len()).all(|k| weight[k] == 0.0 || group.mines[k][cell] == group.ways[k]) {
+        known[x][y] = Some(true);
+      }
+    }
+  }
+  if !rest.is_empty() {
+    let feasible = || all.iter().enumerate().filter(|&(mines, &ways)| ways > 0.0 && rest_ways(mines) > 0.0).map(|(mines, _)| mines_left - mines);
+    let share: f64 = all.iter().enumerate().map(|(mines, ways)| ways * rest_ways(mines) * mines_left.saturating_sub(mines) as f64 / rest.len() as f64).sum::<f64>() / total;
+    let settled = if feasible().all(|left| left == 0) { Some(false) } else if feasible().all(|left| left == rest.len()) { Some(true) } else { None };
+    for &(x, y) in &rest {
+      danger[x][y] = Some(share as f32);
+      known[x][y] = settled;
+    }
+  }
+  for (x, y) in cells() {
+    match (board[x][y].status, known[x][y]) {
+      (CellStatus::Revealed, _) => danger[x][y] = None,
+      (_, Some(mine)) => danger[x][y] = Some(if mine { 1.0 } else { 0.0 }),
+      _ => (),
+    }
+  }
+  Some(Odds {known, danger})
+}
+
+/// Counts every way of placing mines on the group's cells which gives each number its mines, taking at most `most` mines.
+/// `None` once `steps` goes past [`MAX_STEPS`].
+fn count(cells: Vec<(usize, usize)>, constraints: &[Constraint], most: usize, steps: &mut usize) -> Option<Group> {
+  //For each cell, the numbers around it.
+  let mut touching = vec![Vec::new(); cells.len()];
+  for (index, constraint) in constraints.iter().enumerate() {
+    for &cell in &constraint.cells {
+      touching[cell].push(index);
+    }
+  }
+  let mut group = Group {ways: vec![0.0; cells.len() + 1], mines: vec![vec![0.0; cells.len()]; cells.len() + 1], cells};
+  let mut placed = vec![0; constraints.len()];
+  let mut open: Vec<usize> = constraints.iter().map(|constraint| constraint.cells.len()).collect();
+  let mut mined = vec![false; group.cells.len()];
+  let mut search = Search {constraints, touching: &touching, placed: &mut placed, open: &mut open, mined: &mut mined, group: &mut group, most, steps};
+  search.place(0, 0)?;
+  Some(group)
+}
+
+struct Search<'a> {
+  constraints: &'a [Constraint],
+  touching: &'a [Vec<usize>],
+  /// Mines placed around each number so far.
+  placed: &'a mut [usize],
+  /// Cells around each number not decided yet.
+  open: &'a mut [usize],
+  mined: &'a mut [bool],
+  group: &'a mut Group,
+  most: usize,
+  steps: &'a mut usize,
+}
+
+impl Search<'_> {
+  /// Decides the cells from `cell` on, with `mines` placed before it.
+  fn place(&mut self, cell: usize, mines: usize) -> Option<()> {
+    if cell == self.mined.len() {
+      self.group.ways[mines] += 1.0;
+      for (index, &mined) in self.mined.iter().enumerate() {
+        if mined {
+          self.group.mines[mines][index] += 1.0;
+        }
+      }
+      return Some(());
+    }
+    for mine in [false, true] {
+      *self.steps += 1;
+      if *self.steps > MAX_STEPS {
+        return None;
+      }
+      if mine && mines == self.most {
+        continue;
+      }
+      for &number in &self.touching[cell] {
+        self.open[number] -= 1;
+        self.placed[number] += mine as usize;
+      }
+      //Every number around the cell can still get exactly its mines.
+      let fits = self.touching[cell].iter().all(|&number| {
+        let missing = self.constraints[number].missing;
+        self.placed[number] <= missing && self.placed[number] + self.open[number] >= missing
+      });
+      if fits {
+        self.mined[cell] = mine;
+        let result = self.place(cell + 1, mines + mine as usize);
+        self.mined[cell] = false;
+        result?;
+      }
+      for &number in &self.touching[cell] {
+        self.open[number] += 1;
+        self.placed[number] -= mine as usize;
+      }
+    }
+    Some(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::action::Action;
+  use crate::config::GameConfig;
+
+  #[test]
+  fn odds_add_up_to_the_mines() {
+    let mut engine = GameConfig::new(1).build();
+    let (x, y) = engine.board.positions().find(|&(x, y)| engine.board[x][y].value == CellValue::Number(0)).unwrap();
+    engine.act(Action::Reveal(x, y));
+    let danger = Exact.danger(&engine.board, engine.neighbors);
+    let expected: f32 = danger.iter().flatten().flatten().sum();
+    assert!((expected - engine.board.mines as f32).abs() < 0.01, "{}", expected);
+    //Exact odds settle at least as much as comparing numbers in pairs, and never call a mine safe.
+    let known = Exact.known(&engine.board, engine.neighbors);
+    let deduced = solver::deduce(&engine.board, engine.neighbors);
+    for (x, y) in engine.board.positions() {
+      assert!(deduced[x][y].is_none() || known[x][y] == deduced[x][y]);
+      if known[x][y] == Some(false) {
+        assert!(!engine.board[x][y].value.is_mine());
+      }
+    }
+  }
+
+  /// A covered expert board with `numbers` revealed on it.
+  fn board(numbers: &[((usize, usize), i8)]) -> Board {
+    let (_, width, height, mines) = crate::config::EXPERT;
+    let mut board = Board::new(width, height, mines);
+    for &((x, y), number) in numbers {
+      board[x][y] = Cell {status: CellStatus::Revealed, value: CellValue::Number(number)};
+    }
+    board
+  }
+
+  /// The odds found by trying every way of placing mines next to the numbers, with the rest spread over the other covered cells.
+  fn brute_force(board: &Board) -> Vec<((usize, usize), f64)> {
+    let cells = || board.positions();
+    let numbers: Vec<_> = cells().filter(|&(x, y)| board[x][y].status == CellStatus::Revealed).collect();
+    let mut front: Vec<_> = numbers.iter().flat_map(|&(x, y)| Neighbors::All.of(board, x, y)).filter(|&(x, y)| board[x][y].status == CellStatus::Covered).collect();
+    front.sort_unstable();
+    front.dedup();
+    let rest = cells().filter(|&(x, y)| board[x][y].status == CellStatus::Covered).count() - front.len();
+    let ln_choose = |n: usize, k: usize| (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum::<f64>();
+    let (mut total, mut mined, mut rest_mines) = (0.0, vec![0.0; front.len()], 0.0);
+    for placement in 0..1u32 << front.len() {
+      let mine = |cell: &(usize, usize)| front.iter().position(|other| other == cell).is_some_and(|index| placement >> index & 1 == 1);
+      let fits = numbers.iter().all(|&(x, y)| {
+        let CellValue::Number(number) = board[x][y].value else { unreachable!() };
+        Neighbors::All.of(board, x, y).filter(mine).count() == number as usize
+      });
+      let mines = placement.count_ones() as usize;
+      if !fits || mines > board.mines {
+        continue;
+      }
+      //Relative to placing every mine off the front, so the weights stay small.
+      let weight = (ln_choose(rest, board.mines - mines) - ln_choose(rest, board.mines)).exp();
+      total += weight;
+      rest_mines += weight * (board.mines - mines) as f64;
+      for (index, mined) in mined.iter_mut().enumerate() {
+        if placement >> index & 1 == 1 {
+          *mined += weight;
+        }
+      }
+    }
+    let off_front = cells().find(|&(x, y)| board[x][y].status == CellStatus::Covered && !front.contains(&(x, y))).unwrap();
+    front.into_iter().zip(mined).map(|(cell, mined)| (cell, mined / total)).chain([(off_front, rest_mines / total / rest as f64)]).collect()
+  }
+
+  #[test]
+  fn small_fronts_match_trying_every_way() {
+    //1-2-1 along the top edge, with a 2 on its end leaving a coin flip, and a lone 1 in the middle of the board.
+    let board = board(&[((0, 0), 1), ((1, 0), 1), ((2, 0), 2), ((3, 0), 1), ((4, 0), 2), ((15, 8), 1)]);
+    let danger = Exact.danger(&board, Neighbors::All);
+    for ((x, y), expected) in brute_force(&board) {
+      let danger = danger[x][y].unwrap() as f64;
+      assert!((danger - expected).abs() < 1e-4, "{} {}: {} instead of {}", x, y, danger, expected);
+    }
+    assert_eq!(danger[1][1], Some(1.0));
+    assert_eq!(danger[5][0], Some(0.5));
+  }
+
+  #[test]
+  fn moves_follow_what_the_solver_knows() {
+    let board = board(&[((0, 0), 1), ((1, 0), 1), ((2, 0), 2), ((3, 0), 1), ((4, 0), 2), ((15, 8), 1)]);
+    for kind in [solver::Kind::Pairs, solver::Kind::Exact] {
+      let step = kind.solver().next_move(&board, Neighbors::All).unwrap();
+      let (x, y) = step.action.position();
+      assert_eq!(step.reason, solver::Reason::Deduced);
+      assert_eq!(kind.solver().known(&board, Neighbors::All)[x][y], Some(matches!(step.action, Action::Flag(..))));
+    }
+  }
+
+  #[test]
+  fn too_many_ways_fall_back_to_the_estimate() {
+    //4s on every other cell of the second row share their sides, making one front with far too many ways to count.
+    let board = board(&(1..30).step_by(2).map(|x| ((x, 1), 4)).collect::<Vec<_>>());
+    assert!(odds(&board, Neighbors::All).is_none());
+    assert_eq!(Exact.danger(&board, Neighbors::All), solver::danger(&board, Neighbors::All));
+    assert_eq!(Exact.known(&board, Neighbors::All), solver::deduce(&board, Neighbors::All));
+  }
+
+  #[test]
+  fn untouched_boards_are_even() {
+    let board = GameConfig::new(2).build().board;
+    let danger = Exact.danger(&board, Neighbors::All);
+    assert_eq!(danger[3][4], Some(board.mines as f32 / (board.width * board.height) as f32));
+  }
+}
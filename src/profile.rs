@@ -0,0 +1,146 @@
+//! Everything kept about a player in one file, for moving to another machine by hand.
+//!
+//! Importing merges instead of replacing, so nothing already on this machine is lost:
+//! games and won daily challenges missing here are added, tags are added to games on both sides, drill records keep whichever side practiced more,
+//! and of the settings only the player's own ones are taken, when this machine has no player yet:
+//! their name, avatar, color scheme and board.
+use crate::{daily, drill, face, settings, stats};
+use minesweeper::schema;
+use serde::{Deserialize, Serialize};
+use std::{fs, path};
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+  pub settings: settings::Settings,
+  pub stats: stats::Stats,
+  pub daily: daily::Daily,
+  pub drills: drill::Drills,
+  /// The avatar's picture, when it is one, since its file isn't in the settings.
+  pub avatar_image: Option<face::Image>,
+}
+
+/// What an import added.
+#[derive(Debug, Default, PartialEq)]
+pub struct Merged {
+  pub games: usize,
+  pub days: usize,
+  pub drills: usize,
+}
+
+impl Profile {
+  pub fn save(&self, path: &path::Path) -> Result<(), String> {
+    fs::write(path, schema::PROFILE.to_json(self)).map_err(|error| error.to_string())
+  }
+
+  /// Reads a profile, with the settings, stats and records in it brought up to date like their own files.
+  pub fn load(path: &path::Path) -> Result<Profile, String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    schema::PROFILE.from_json(&text)
+  }
+
+  /// Adds what `other` has and this doesn't.
+  pub fn merge(&mut self, other: Profile) -> Merged {
+    let mut merged = Merged::default();
+    //Both sides' totals may already include the same games, so adding them up could count games twice.
+    self.stats.territory = self.stats.territory.max(other.stats.territory);
+    //Records have no date, so ones which match in every way are taken to be the same game.
+    for game in other.stats.games {
+      let same = |record: &stats::Record| record.category == game.category && record.seed == game.seed && record.won == game.won
+        && record.seconds == game.seconds && record.three_bv == game.three_bv;
+      match self.stats.games.iter_mut().find(|record| same(record)) {
+        Some(record) => {
+          for tag in game.tags {
+            if !record.tags.contains(&tag) {
+              record.tags.push(tag);
+            }
+          }
+          if record.note.is_empty() {
+            record.note = game.note;
+          }
+        },
+        None => {
+          self.stats.games.push(game);
+          merged.games += 1;
+        },
+      }
+    }
+    for day in other.daily.completed {
+      merged.days += self.daily.completed.insert(day) as usize;
+    }
+    for (name, record) in other.drills.records {
+      let mine = self.drills.records.entry(name).or_default();
+      if record.attempts > mine.attempts {
+        *mine = record;
+        merged.drills += 1;
+      }
+    }
+    self.drills.best_rush = self.drills.best_rush.max(other.drills.best_rush);
+    if self.settings.player_name.is_empty() && self.settings.avatar.is_empty() {
+      self.settings.player_name = other.settings.player_name;
+      self.settings.avatar = other.settings.avatar;
+      self.avatar_image = other.avatar_image;
+      self.settings.color_scheme = other.settings.color_scheme;
+      self.settings.board_width = other.settings.board_width;
+      self.settings.board_height = other.settings.board_height;
+      self.settings.mine_count = other.settings.mine_count;
+      self.settings.safe_start = other.settings.safe_start;
+    }
+    merged
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn importing_twice_adds_nothing_more() {
+    let record = stats::Record {category: stats::Category::standard(&minesweeper::config::GameConfig::default().build().board), seed: 1, won: true, seconds: 60.0, three_bv: 100, guesses: 0, forced_guesses: 0, tags: Vec::new(), note: String::new(), abandoned: false};
+    let exported = || {
+      let mut profile = Profile::default();
+      profile.stats.games.push(record.clone());
+      profile.daily.completed.insert(20000);
+      profile.drills.records.insert(String::from("1-2-1"), drill::Record {attempts: 3, solved: 2, solved_ms: 4000, level: 2});
+      profile
+    };
+    let mut profile = Profile::default();
+    profile.stats.games.push(stats::Record {seed: 2, ..record.clone()});
+    assert_eq!(profile.merge(exported()), Merged {games: 1, days: 1, drills: 1});
+    assert_eq!(profile.merge(exported()), Merged::default());
+    assert_eq!(profile.stats.games.len(), 2);
+  }
+
+  #[test]
+  fn a_new_machine_takes_the_players_look() {
+    let mut exported = Profile::default();
+    exported.settings.player_name = String::from("Ann");
+    exported.settings.avatar = String::from("ann.png");
+    exported.avatar_image = Some(face::Image {width: 1, height: 1, pixels: vec![[1, 2, 3, 255]]});
+    exported.settings.color_scheme = settings::ColorScheme::Dark;
+    (exported.settings.board_width, exported.settings.board_height, exported.settings.mine_count) = (9, 9, 10);
+    let mut fresh = Profile::default();
+    fresh.merge(schema::PROFILE.from_json(&schema::PROFILE.to_json(&exported)).unwrap());
+    assert_eq!(fresh.settings.avatar, "ann.png");
+    assert_eq!(fresh.avatar_image.unwrap().pixels, [[1, 2, 3, 255]]);
+    assert!(fresh.settings.color_scheme == settings::ColorScheme::Dark);
+    assert_eq!(fresh.settings.game_config(1).mines, 10);
+    //Someone already playing here keeps their own.
+    let mut playing = Profile::default();
+    playing.settings.player_name = String::from("Bob");
+    playing.merge(exported);
+    assert_eq!(playing.settings.avatar, "");
+    assert!(playing.settings.color_scheme == settings::ColorScheme::System);
+  }
+
+  #[test]
+  fn exports_from_before_formats_still_load() {
+    let old = r#"{"settings": {"player_name": "Ann"}, "daily": {"completed": [20000]}, "drills": {"best_rush": 4}}"#;
+    let profile: Profile = schema::PROFILE.from_json(old).unwrap();
+    assert_eq!(profile.settings.player_name, "Ann");
+    let written = schema::PROFILE.write(&profile);
+    assert_eq!(written["settings"][schema::KEY], schema::SETTINGS.version());
+    let again: Profile = schema::PROFILE.read(written).unwrap();
+    assert_eq!(again.drills.best_rush, 4);
+  }
+}
@@ -0,0 +1,49 @@
+//! Four boards played at once. Hitting a mine on any of them ends the run, and all four have to be cleared to win.
+use minesweeper::action::Action;
+use minesweeper::config::GameConfig;
+use minesweeper::engine::{Engine, GameStatus};
+
+pub const BOARDS: usize = 4;
+
+pub struct Quad {
+  pub boards: Vec<Engine>,
+}
+
+impl Quad {
+  /// Boards for `seed` and the seeds right after it.
+  pub fn new(seed: u64) -> Quad {
+    Quad {boards: (0..BOARDS as u64).map(|index| GameConfig::new(seed.wrapping_add(index)).build()).collect()}
+  }
+
+  pub fn act(&mut self, board: usize, action: Action) {
+    let Some(engine) = self.boards.get_mut(board) else { return };
+    engine.act(action);
+    if engine.status == GameStatus::Lost {
+      for engine in &mut self.boards {
+        engine.lose();
+      }
+    }
+  }
+
+  pub fn status(&self) -> GameStatus {
+    if self.boards.iter().any(|engine| engine.status == GameStatus::Lost) {
+      GameStatus::Lost
+    } else if self.boards.iter().all(|engine| engine.status == GameStatus::Won) {
+      GameStatus::Won
+    } else {
+      GameStatus::Playing
+    }
+  }
+
+  pub fn is_over(&self) -> bool {
+    self.status() != GameStatus::Playing
+  }
+
+  pub fn revealed_count(&self) -> usize {
+    self.boards.iter().map(|engine| engine.revealed_count).sum()
+  }
+
+  pub fn mines_left(&self) -> usize {
+    self.boards.iter().map(crate::mines_left).sum()
+  }
+}
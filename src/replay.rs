@@ -0,0 +1,169 @@
+//! Re-running recorded games without a window.
+//!
+//! A replay is a seed and the actions played on it. Bug reports are valid replays too.
+use crate::action::Action;
+use crate::config::GameConfig;
+use crate::engine::Engine;
+use crate::schema;
+use serde::{Deserialize, Serialize};
+
+//Spacing given to moves of replays without timing.
+const UNTIMED_STEP_MS: u64 = 500;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+  /// The seed and rules, stored alongside the moves.
+  #[serde(flatten)]
+  pub config: GameConfig,
+  #[serde(default)]
+  pub moves: Vec<Action>,
+  /// Milliseconds on the game clock when each move was played. Empty when the timing wasn't recorded.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub times: Vec<u64>,
+  /// [`Engine::state_hash`] at the end of the game, if it should be checked.
+  pub hash: Option<u64>,
+}
+
+impl Replay {
+  pub fn record(engine: &Engine) -> Replay {
+    Replay {
+      config: GameConfig::of(engine),
+      moves: engine.moves.clone(),
+      times: Vec::new(),
+      hash: Some(engine.state_hash()),
+    }
+  }
+
+  /// Reads a replay in this format or an older one.
+  pub fn from_json(json: &str) -> Result<Replay, String> {
+    schema::REPLAY.from_json(json)
+  }
+
+  pub fn to_json(&self) -> String {
+    schema::REPLAY.to_json(self)
+  }
+
+  /// Milliseconds into the game a move was played. Replays without timing get evenly spaced moves.
+  pub fn time(&self, index: usize) -> u64 {
+    self.times.get(index).copied().unwrap_or(index as u64 * UNTIMED_STEP_MS)
+  }
+
+  /// The board and its rules, without the moves.
+  pub fn board(&self) -> Replay {
+    Replay {moves: Vec::new(), times: Vec::new(), hash: None, ..self.clone()}
+  }
+
+  /// The board before any moves.
+  pub fn start(&self) -> Engine {
+    self.config.build()
+  }
+
+  /// Play the moves on a fresh board. Fails if the game ended somewhere different than recorded.
+  pub fn run(&self) -> Result<Engine, String> {
+    let mut engine = self.start();
+    for &action in &self.moves {
+      engine.act(action);
+    }
+    match self.hash {
+      Some(hash) if hash != engine.state_hash() => Err(format!("Expected state {:x}, got {:x}", hash, engine.state_hash())),
+      _ => Ok(engine),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::GameStatus;
+
+  fn replay(json: &str) -> Engine {
+    Replay::from_json(json).unwrap().run().unwrap()
+  }
+
+  #[test]
+  fn reveal_cascade() {
+    let engine = replay(include_str!("../tests/replays/reveal_cascade.json"));
+    assert_eq!(engine.status, GameStatus::Playing);
+    assert!(engine.revealed_count > 1);
+  }
+
+  #[test]
+  fn reveal_mine() {
+    let engine = replay(include_str!("../tests/replays/reveal_mine.json"));
+    assert_eq!(engine.status, GameStatus::Lost);
+  }
+
+  #[test]
+  fn nothing_after_game_over() {
+    let mut engine = replay(include_str!("../tests/replays/reveal_mine.json"));
+    let hash = engine.state_hash();
+    let moves = engine.moves.len();
+    engine.act(Action::Reveal(0, 0));
+    engine.act(Action::Flag(1, 1));
+    engine.act(Action::Chord(2, 2));
+    assert_eq!(engine.state_hash(), hash);
+    assert_eq!(engine.moves.len(), moves);
+    assert_eq!(engine.status, GameStatus::Lost);
+  }
+
+  #[test]
+  fn flag_toggle() {
+    let engine = replay(include_str!("../tests/replays/flag_toggle.json"));
+    //Flagging twice removes the flag, and revealed cells can't be flagged.
+    assert_eq!(engine.flag_count, 1);
+  }
+
+  #[test]
+  fn chord_with_flags() {
+    let before = replay(include_str!("../tests/replays/reveal_cascade.json"));
+    let engine = replay(include_str!("../tests/replays/chord_with_flags.json"));
+    assert_eq!(engine.status, GameStatus::Playing);
+    assert!(engine.revealed_count > before.revealed_count);
+  }
+
+  #[test]
+  fn chord_without_flags() {
+    let before = replay(include_str!("../tests/replays/reveal_cascade.json"));
+    let engine = replay(include_str!("../tests/replays/chord_without_flags.json"));
+    assert_eq!(engine.state_hash(), before.state_hash());
+  }
+
+  #[test]
+  fn chord_on_wrong_flag() {
+    let engine = replay(include_str!("../tests/replays/chord_on_wrong_flag.json"));
+    assert_eq!(engine.status, GameStatus::Lost);
+  }
+
+  #[test]
+  fn win() {
+    let engine = replay(include_str!("../tests/replays/win.json"));
+    assert_eq!(engine.status, GameStatus::Won);
+  }
+
+  #[test]
+  fn won_games_solve_every_three_bv() {
+    let engine = replay(include_str!("../tests/replays/win.json"));
+    assert_eq!(engine.solved_three_bv(), engine.three_bv());
+    assert_eq!(GameConfig::new(engine.seed).build().solved_three_bv(), 0);
+  }
+
+  #[test]
+  fn flag_win() {
+    let mines: Vec<_> = GameConfig::new(5).build().board.positions()
+      .filter(|&(x, y)| GameConfig::new(5).build().board[x][y].value == crate::engine::CellValue::Mined).collect();
+    for flag_win in [false, true] {
+      let mut engine = GameConfig::new(5).with_flag_win(flag_win).build();
+      for &(x, y) in &mines {
+        engine.act(Action::Flag(x, y));
+      }
+      assert_eq!(engine.status == GameStatus::Won, flag_win);
+    }
+  }
+
+  #[test]
+  fn mismatched_hash() {
+    let mut recorded = Replay::from_json(include_str!("../tests/replays/win.json")).unwrap();
+    recorded.moves.pop();
+    assert!(recorded.run().is_err());
+  }
+}
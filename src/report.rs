@@ -0,0 +1,31 @@
+//! Bug report bundles, with everything needed to reproduce a game exactly.
+//!
+//! A report is also a valid [`Replay`] file.
+use crate::{replay::Replay, schema, settings};
+use serde::Serialize;
+use std::{fs, io, path};
+
+#[derive(Serialize)]
+pub struct Report<'a> {
+  pub version: &'static str,
+  pub status: String,
+  #[serde(flatten)]
+  pub game: Replay,
+  pub settings: &'a settings::Settings,
+}
+
+impl Report<'_> {
+  pub fn to_json(&self) -> String {
+    schema::REPLAY.to_json(self)
+  }
+
+  /// Writes the report next to the settings. Returns where it went.
+  pub fn save(&self) -> io::Result<path::PathBuf> {
+    let path = settings::config_path("bug-report.json").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, self.to_json())?;
+    Ok(path)
+  }
+}
@@ -0,0 +1,204 @@
+//! Puzzle rush: as many small positions as can be solved in three minutes.
+//!
+//! Each puzzle is a window onto a board part way through, with exactly one covered cell in it which the numbers
+//! prove is safe, or one which they prove is a mine. Clicking any other cell is a strike, and three strikes end the rush early.
+use crate::drill::Spot;
+use minesweeper::action::Action;
+use minesweeper::config::GameConfig;
+use minesweeper::engine::{Board, CellStatus, CellValue};
+use minesweeper::solver;
+use rand::Rng;
+use std::time;
+
+/// What the puzzle rush screen asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  /// Picks a cell of the rush's puzzle.
+  Pick(usize, usize),
+  /// Checks whether time ran out.
+  Tick,
+  /// Start a new rush.
+  Restart,
+  Close,
+}
+
+pub const LENGTH: time::Duration = time::Duration::from_secs(180);
+pub const STRIKES: usize = 3;
+const WIDTH: usize = 7;
+const HEIGHT: usize = 5;
+//Solver moves played at most before looking for a puzzle. Fewer leave more of the board to look at.
+const MAX_STEPS: usize = 80;
+const WINDOW_TRIES: usize = 20;
+//Puzzles with more covered cells around their numbers than this take too long to check.
+const MAX_UNKNOWNS: usize = 20;
+
+pub struct Puzzle {
+  /// The window, as `spots[y][x]`. Only the cell to find is asked for.
+  pub spots: Vec<Vec<Spot>>,
+  /// Whether the cell to find is a mine, rather than safe.
+  pub mine: bool,
+}
+
+/// Which covered cells in the window at `left`, `top` the numbers in it prove are mines, `true`, or safe, `false`.
+///
+/// Every way of placing mines around those numbers is tried, so patterns like 1-2-1, which take more than one number,
+/// are worked out too. Covered cells just outside the window count as unknown. `None` when there are too many to try.
+fn certain(board: &Board, left: usize, top: usize) -> Option<Vec<((usize, usize), bool)>> {
+  let mut unknowns: Vec<(usize, usize)> = Vec::new();
+  let mut numbers = Vec::new();
+  for x in left..left + WIDTH {
+    for y in top..top + HEIGHT {
+      let (CellStatus::Revealed, CellValue::Number(number)) = (board[x][y].status, board[x][y].value) else { continue };
+      let mut around = Vec::new();
+      for near in board.neighbors(x, y).filter(|&(near_x, near_y)| board[near_x][near_y].status != CellStatus::Revealed) {
+        if !unknowns.contains(&near) {
+          unknowns.push(near);
+        }
+        around.push(unknowns.iter().position(|&unknown| unknown == near).unwrap_or_default());
+      }
+      numbers.push((number as usize, around));
+    }
+  }
+  if unknowns.len() > MAX_UNKNOWNS {
+    return None;
+  }
+  //Each unknown seen as safe and as a mine, over every placement which fits.
+  let mut seen = vec![[false; 2]; unknowns.len()];
+  let mut mines = vec![None; unknowns.len()];
+  fn place(index: usize, mines: &mut Vec<Option<bool>>, numbers: &[(usize, Vec<usize>)], seen: &mut [[bool; 2]]) {
+    let fits = numbers.iter().all(|(number, around)| {
+      let placed = around.iter().filter(|&&unknown| mines[unknown] == Some(true)).count();
+      let open = around.iter().filter(|&&unknown| mines[unknown].is_none()).count();
+      placed <= *number && placed + open >= *number
+    });
+    if !fits {
+      return;
+    }
+    if index == mines.len() {
+      for (seen, mine) in seen.iter_mut().zip(mines.iter()) {
+        seen[(*mine == Some(true)) as usize] = true;
+      }
+      return;
+    }
+    for mine in [false, true] {
+      mines[index] = Some(mine);
+      place(index + 1, mines, numbers, seen);
+    }
+    mines[index] = None;
+  }
+  place(0, &mut mines, &numbers, &mut seen);
+  Some(unknowns.into_iter().zip(seen)
+    .filter(|&((x, y), _)| (left..left + WIDTH).contains(&x) && (top..top + HEIGHT).contains(&y))
+    .filter_map(|(cell, [safe, mine])| (safe != mine).then_some((cell, mine)))
+    .collect())
+}
+
+impl Puzzle {
+  /// Plays a random board part of the way with the solver, and looks for a window with one cell to find.
+  pub fn generate(rng: &mut impl Rng) -> Puzzle {
+    loop {
+      let mut engine = GameConfig::new(rng.gen()).build();
+      let openings: Vec<(usize, usize)> = engine.board.positions()
+        .filter(|&(x, y)| engine.board[x][y].value == CellValue::Number(0)).collect();
+      let Some(&(x, y)) = openings.get(rng.gen_range(0..openings.len().max(1))) else { continue };
+      engine.act(Action::Reveal(x, y));
+      for _ in 0..rng.gen_range(0..MAX_STEPS) {
+        match solver::next_move(&engine.board, engine.neighbors) {
+          Some(step) if step.reason != solver::Reason::Guess => engine.act(step.action),
+          _ => break,
+        }
+      }
+      if engine.is_over() {
+        continue;
+      }
+      for _ in 0..WINDOW_TRIES {
+        let left = rng.gen_range(0..=engine.board.width - WIDTH);
+        let top = rng.gen_range(0..=engine.board.height - HEIGHT);
+        let mine = rng.gen_bool(0.5);
+        let Some(known) = certain(&engine.board, left, top) else { continue };
+        let [(answer, _)] = known.iter().filter(|(_, known)| *known == mine).copied().collect::<Vec<_>>()[..] else { continue };
+        let spots = (top..top + HEIGHT).map(|y| (left..left + WIDTH).map(|x| match (engine.board[x][y].status, engine.board[x][y].value) {
+          (CellStatus::Revealed, value) => Spot::Number(value.number().unwrap_or_default() as u8),
+          //Flags aren't shown, so only the numbers count.
+          (_, value) => Spot::Covered {mine: value == CellValue::Mined, asked: (x, y) == answer},
+        }).collect()).collect();
+        return Puzzle {spots, mine};
+      }
+    }
+  }
+}
+
+pub struct Rush {
+  pub puzzle: Puzzle,
+  pub solved: usize,
+  pub strikes: usize,
+  pub started: time::Instant,
+  /// Whether the last click was right, shown until the next one.
+  pub last: Option<bool>,
+  /// Set once the end has been noticed, so it is only counted once.
+  pub ended: bool,
+}
+
+impl Rush {
+  pub fn new() -> Rush {
+    Rush {puzzle: Puzzle::generate(&mut rand::thread_rng()), solved: 0, strikes: 0, started: time::Instant::now(), last: None, ended: false}
+  }
+
+  pub fn remaining(&self) -> time::Duration {
+    LENGTH.saturating_sub(self.started.elapsed())
+  }
+
+  pub fn is_over(&self) -> bool {
+    self.strikes >= STRIKES || self.remaining().is_zero()
+  }
+
+  /// Picks a cell of the puzzle, and moves on to the next one.
+  pub fn click(&mut self, x: usize, y: usize) {
+    if self.is_over() {
+      return;
+    }
+    let right = matches!(self.puzzle.spots[y][x], Spot::Covered {asked: true, ..});
+    if right {
+      self.solved += 1;
+    } else {
+      self.strikes += 1;
+    }
+    self.last = Some(right);
+    self.puzzle = Puzzle::generate(&mut rand::thread_rng());
+  }
+
+  /// True the first time it is called after the rush is over.
+  pub fn end(&mut self) -> bool {
+    let ending = self.is_over() && !self.ended;
+    self.ended |= ending;
+    ending
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::SeedableRng;
+
+  #[test]
+  fn puzzles_have_one_cell_to_find() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    for _ in 0..10 {
+      let puzzle = Puzzle::generate(&mut rng);
+      let asked: Vec<Spot> = puzzle.spots.iter().flatten().copied().filter(|spot| matches!(spot, Spot::Covered {asked: true, ..})).collect();
+      assert_eq!(asked, vec![Spot::Covered {mine: puzzle.mine, asked: true}]);
+    }
+    //A 1-2-1 along the top edge, under a row of covered cells.
+    let mut engine = GameConfig::new(0).build();
+    for x in 0..engine.board.width {
+      for y in 0..engine.board.height {
+        let mines = if y == 1 { [1, 3].iter().filter(|&&mine: &&usize| mine.abs_diff(x) <= 1).count() } else { 0 };
+        engine.board[x][y].value = if y == 0 && [1, 3].contains(&x) { CellValue::Mined } else { CellValue::Number(mines as i8) };
+        engine.board[x][y].status = if y == 0 { CellStatus::Covered } else { CellStatus::Revealed };
+      }
+    }
+    let mut known = certain(&engine.board, 0, 0).unwrap();
+    known.sort();
+    assert_eq!(known, (0..WIDTH).map(|x| ((x, 0), [1, 3].contains(&x))).collect::<Vec<_>>());
+  }
+}
@@ -0,0 +1,70 @@
+//! Scanner powerup for the arcade mode. Big openings charge it, and a charge counts the mines under a 3×3 area
+//! without showing where they are.
+use minesweeper::engine::{Board, CellStatus};
+
+/// Cells one move has to open to charge the scanner.
+pub const CHARGE_CELLS: usize = 20;
+pub const MAX_CHARGES: usize = 3;
+
+#[derive(Default)]
+pub struct Scanner {
+  pub charges: usize,
+  /// Whether the next reveal scans around the cell instead.
+  pub armed: bool,
+  /// The middle of each area scanned, and the mines in it.
+  pub scans: Vec<((usize, usize), usize)>,
+}
+
+impl Scanner {
+  /// A move which opened `opened` cells.
+  pub fn opened(&mut self, opened: usize) {
+    if opened >= CHARGE_CELLS {
+      self.charges = (self.charges + 1).min(MAX_CHARGES);
+    }
+  }
+
+  /// Arms the scanner, or disarms it if it already was. Does nothing without a charge.
+  pub fn toggle(&mut self) {
+    self.armed = !self.armed && self.charges > 0;
+  }
+
+  /// Uses a charge on the area around a covered cell, returning how many mines it holds.
+  pub fn scan(&mut self, board: &Board, x: usize, y: usize) -> Option<usize> {
+    if !self.armed || self.charges == 0 || board[x][y].status == CellStatus::Revealed {
+      return None;
+    }
+    self.armed = false;
+    self.charges -= 1;
+    let mines = board.neighbors(x, y).chain([(x, y)]).filter(|&(x, y)| board[x][y].value.is_mine()).count();
+    self.scans.push(((x, y), mines));
+    Some(mines)
+  }
+
+  /// Mines found by scanning around a cell.
+  pub fn mines_around(&self, x: usize, y: usize) -> Option<usize> {
+    self.scans.iter().rev().find(|(center, _)| *center == (x, y)).map(|&(_, mines)| mines)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use minesweeper::config::GameConfig;
+  use minesweeper::engine::CellValue;
+
+  #[test]
+  fn scans_take_a_charge_each() {
+    let engine = GameConfig::new(3).build();
+    let mut scanner = Scanner::default();
+    scanner.toggle();
+    assert!(!scanner.armed);
+    scanner.opened(CHARGE_CELLS - 1);
+    scanner.opened(CHARGE_CELLS);
+    assert_eq!(scanner.charges, 1);
+    scanner.toggle();
+    let mines = (0..3).flat_map(|x| (0..3).map(move |y| (x, y))).filter(|&(x, y)| engine.board[x][y].value == CellValue::Mined).count();
+    assert_eq!(scanner.scan(&engine.board, 1, 1), Some(mines));
+    assert_eq!(scanner.scan(&engine.board, 1, 1), None);
+    assert_eq!((scanner.charges, scanner.mines_around(1, 1)), (0, Some(mines)));
+  }
+}
@@ -0,0 +1,176 @@
+//! Versions of the formats games, boards, stats, settings and the rest are saved in, and how files in older ones are brought up to date.
+//!
+//! Files carry their format's version under [`KEY`]. Files saved before versions were written down have none, and count as version 0.
+//! Reading runs the migrations from the file's version up to the current one on the JSON, before it becomes a struct,
+//! so fields can be renamed or moved around later without breaking saves and replays already out there.
+//! Files from a newer version are read as they are, since fields unknown here are skipped, which is better than losing them.
+//! Files holding others, like packs holding replays, bring those up to date with their own formats too.
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+/// Where the version goes. Bug reports already use `version` for the game's version.
+pub const KEY: &str = "format";
+
+/// Turns a file of one version into the next.
+pub type Migration = fn(&mut Map<String, Value>);
+
+pub struct Schema {
+  /// The migration from version `n` is at index `n`, so the current version is how many there are.
+  pub migrations: &'static [Migration],
+  /// Fields holding a file of another format, or a list of them.
+  pub nested: &'static [(&'static str, &'static Schema)],
+}
+
+/// Replays, and the bug reports which are replays too.
+pub const REPLAY: Schema = Schema {migrations: &[versioned], nested: &[]};
+/// Seed packs, with their boards.
+pub const PACK: Schema = Schema {migrations: &[versioned, pack_checksum, pack_signed], nested: &[("boards", &REPLAY)]};
+/// Results of a pack, with the games played on it.
+pub const RESULTS: Schema = Schema {migrations: &[versioned, results_checksum, results_signed], nested: &[("games", &REPLAY)]};
+/// `stats.json`, with every finished game.
+pub const STATS: Schema = Schema {migrations: &[versioned], nested: &[]};
+/// `settings.toml`.
+pub const SETTINGS: Schema = Schema {migrations: &[versioned], nested: &[]};
+/// `daily.toml`, with the daily challenges won.
+pub const DAILY: Schema = Schema {migrations: &[versioned], nested: &[]};
+/// `drills.toml`, with the drill and puzzle rush records.
+pub const DRILLS: Schema = Schema {migrations: &[versioned], nested: &[]};
+/// A whole profile, exported to move to another machine.
+pub const PROFILE: Schema = Schema {migrations: &[versioned], nested: &[("settings", &SETTINGS), ("stats", &STATS), ("daily", &DAILY), ("drills", &DRILLS)]};
+
+//Version 1 is version 0 with the version written down.
+fn versioned(_: &mut Map<String, Value>) {}
+
+//Version 2 of packs and results has a checksum of the whole file instead of a signature of some fields, so the old one is no use.
+fn pack_checksum(map: &mut Map<String, Value>) {
+  map.remove("signature");
+}
+
+fn results_checksum(map: &mut Map<String, Value>) {
+  map.remove("signature");
+  if let Some(pack) = map.remove("pack_signature") {
+    map.insert(String::from("pack_checksum"), pack);
+  }
+}
+
+//Version 3 of packs and results is signed with a key instead, and a checksum can't be turned into a signature.
+fn pack_signed(map: &mut Map<String, Value>) {
+  map.remove("checksum");
+}
+
+fn results_signed(map: &mut Map<String, Value>) {
+  map.remove("checksum");
+  map.remove("pack_checksum");
+}
+
+impl Schema {
+  pub fn version(&self) -> u64 {
+    self.migrations.len() as u64
+  }
+
+  /// Brings `value` up to the current version.
+  pub fn upgrade(&self, value: &mut Value) -> Result<(), String> {
+    let Value::Object(map) = value else { return Err(String::from("Expected an object")) };
+    let version = match map.get(KEY) {
+      None => 0,
+      Some(version) => version.as_u64().ok_or_else(|| format!("Can't read the format version {}", version))?,
+    };
+    for migration in self.migrations.iter().skip(version as usize) {
+      migration(map);
+    }
+    map.insert(String::from(KEY), Value::from(self.version().max(version)));
+    for (field, schema) in self.nested {
+      match map.get_mut(*field) {
+        Some(Value::Array(values)) => values.iter_mut().try_for_each(|value| schema.upgrade(value))?,
+        Some(value @ Value::Object(_)) => schema.upgrade(value)?,
+        _ => (),
+      }
+    }
+    Ok(())
+  }
+
+  //Writes down the current version of `value` and the files nested in it.
+  fn stamp(&self, value: &mut Value) {
+    let Value::Object(map) = value else { return };
+    map.insert(String::from(KEY), Value::from(self.version()));
+    for (field, schema) in self.nested {
+      match map.get_mut(*field) {
+        Some(Value::Array(values)) => values.iter_mut().for_each(|value| schema.stamp(value)),
+        Some(value) => schema.stamp(value),
+        None => (),
+      }
+    }
+  }
+
+  pub fn read<T: DeserializeOwned>(&self, mut value: Value) -> Result<T, String> {
+    self.upgrade(&mut value)?;
+    serde_json::from_value(value).map_err(|error| error.to_string())
+  }
+
+  pub fn from_json<T: DeserializeOwned>(&self, json: &str) -> Result<T, String> {
+    self.read(serde_json::from_str(json).map_err(|error| error.to_string())?)
+  }
+
+  /// `value` as JSON, with the current version written down.
+  pub fn write<T: Serialize>(&self, value: &T) -> Value {
+    let mut value = serde_json::to_value(value).unwrap_or_default();
+    self.stamp(&mut value);
+    value
+  }
+
+  pub fn to_json<T: Serialize>(&self, value: &T) -> String {
+    serde_json::to_string_pretty(&self.write(value)).unwrap_or_default()
+  }
+
+  /// Reads TOML in this format or an older one. It is migrated as JSON, like every other file.
+  pub fn from_toml<T: DeserializeOwned>(&self, text: &str) -> Result<T, String> {
+    let table: toml::Table = toml::from_str(text).map_err(|error| error.to_string())?;
+    self.read(serde_json::to_value(table).map_err(|error| error.to_string())?)
+  }
+
+  pub fn to_toml<T: Serialize>(&self, value: &T) -> Result<String, String> {
+    //Written first, ahead of any tables, and the rest keeps the order of the fields.
+    let text = toml::to_string(value).map_err(|error| error.to_string())?;
+    Ok(format!("{} = {}\n{}", KEY, self.version(), text))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn older_files_are_migrated() {
+    fn rename(map: &mut Map<String, Value>) {
+      if let Some(seed) = map.remove("board") {
+        map.insert(String::from("seed"), seed);
+      }
+    }
+    let schema = Schema {migrations: &[versioned, rename], nested: &[]};
+    let mut unversioned: Value = serde_json::from_str(r#"{"board": 7}"#).unwrap();
+    schema.upgrade(&mut unversioned).unwrap();
+    assert_eq!(unversioned, serde_json::json!({"seed": 7, "format": 2}));
+    let mut current = schema.write(&serde_json::json!({"seed": 7}));
+    schema.upgrade(&mut current).unwrap();
+    assert_eq!(current, unversioned);
+    let mut newer = serde_json::json!({"board": 7, "format": 3});
+    schema.upgrade(&mut newer).unwrap();
+    assert_eq!(newer["format"], 3);
+    assert!(schema.upgrade(&mut serde_json::json!([7])).is_err());
+  }
+
+  #[test]
+  fn nested_files_are_migrated() {
+    fn rename(map: &mut Map<String, Value>) {
+      if let Some(seed) = map.remove("board") {
+        map.insert(String::from("seed"), seed);
+      }
+    }
+    const INNER: Schema = Schema {migrations: &[versioned, rename], nested: &[]};
+    let outer = Schema {migrations: &[versioned], nested: &[("games", &INNER), ("best", &INNER)]};
+    let mut old = serde_json::json!({"games": [{"board": 1}, {"board": 2, "format": 1}], "best": {"board": 3}});
+    outer.upgrade(&mut old).unwrap();
+    assert_eq!(old, serde_json::json!({"games": [{"seed": 1, "format": 2}, {"seed": 2, "format": 2}], "best": {"seed": 3, "format": 2}, "format": 1}));
+    assert_eq!(outer.write(&serde_json::json!({"games": [{"seed": 1}, {"seed": 2}], "best": {"seed": 3}})), old);
+  }
+}
@@ -0,0 +1,46 @@
+//! A single number for how well a game went.
+//!
+//! ```text
+//! score = 10 × 3BV × (1 + 3BV/s) × efficiency × difficulty × combo
+//! ```
+//!
+//! * 3BV is how much of the board was cleared, so lost games still get partial credit.
+//! * 3BV/s is that 3BV divided by the time taken, counting at least 1 second. Time taken off by treasures doesn't count.
+//! * Efficiency is that 3BV divided by the clicks made, including flags. Chording well can take it above 1.
+//! * Difficulty is the mine density, relative to expert's 99 mines on 30 × 16. Expert is 1.
+//! * Combo is the best multiplier reached in combo mode, or 1 when it is off. Every 5 moves in a row which open cells add 0.1, up to 3.
+//!
+//! For casual play, [`territory`] scores how much of the board has been explored instead, with no regard for time.
+use crate::engine::{Board, CellStatus, Engine};
+use std::time::Duration;
+
+const EXPERT_DENSITY: f64 = 99.0 / (30.0 * 16.0);
+const MAX_COMBO_MULTIPLIER: f64 = 3.0;
+
+/// Mine density of `board` relative to expert.
+pub fn difficulty(board: &Board) -> f64 {
+  board.mines as f64 / (board.width * board.height) as f64 / EXPERT_DENSITY
+}
+
+/// Multiplier for a combo of `moves` in a row.
+pub fn combo_multiplier(moves: u32) -> f64 {
+  (1.0 + 0.1 * (moves / 5) as f64).min(MAX_COMBO_MULTIPLIER)
+}
+
+pub fn score(engine: &Engine, time: Duration, combo_multiplier: f64) -> u64 {
+  let three_bv = engine.solved_three_bv() as f64;
+  if three_bv == 0.0 {
+    return 0;
+  }
+  let speed = three_bv / time.as_secs_f64().max(1.0);
+  let efficiency = three_bv / engine.moves.len().max(1) as f64;
+  (10.0 * three_bv * (1.0 + speed) * efficiency * difficulty(&engine.board) * combo_multiplier).round() as u64
+}
+
+/// Points for the cells revealed so far. Each is worth 1, plus 1 for every mine around it, so dense areas count for more.
+pub fn territory(engine: &Engine) -> u64 {
+  engine.board.iter().flatten()
+    .filter(|cell| cell.status == CellStatus::Revealed && !cell.value.is_mine())
+    .map(|cell| 1 + cell.value.number().unwrap_or_default().unsigned_abs() as u64)
+    .sum()
+}
@@ -0,0 +1,31 @@
+//! Which screen fills the window under the header. Only one is shown at a time, and each keeps its own state,
+//! so leaving a screen drops what was going on in it.
+use crate::{compare, drill, rush, stats};
+
+#[derive(Default)]
+pub enum Screen {
+  /// The board, or the quad boards.
+  #[default]
+  Game,
+  Menu,
+  Colors,
+  Audio,
+  Help,
+  /// The seeds of the boards played this session.
+  Session,
+  /// The statistics, charting a category.
+  Stats(stats::Category),
+  /// The daily challenges of a year and month.
+  Calendar(i64, u32),
+  Comparison(Box<compare::Comparison>),
+  /// A pattern being practiced instead of playing.
+  Drill(drill::Drill),
+  Rush(rush::Rush),
+}
+
+impl Screen {
+  /// Shows `screen`, or goes back to the game when a screen of its kind is already shown.
+  pub fn toggle(&mut self, screen: Screen) {
+    *self = if std::mem::discriminant(self) == std::mem::discriminant(&screen) { Screen::Game } else { screen };
+  }
+}
@@ -0,0 +1,49 @@
+//! Seeds as memorable phrases like `purple-otter-42`, which are easier to read out than numbers.
+//!
+//! A phrase is hashed into the number the board is generated from. Plain numbers are still accepted as they are.
+use rand::Rng;
+
+const ADJECTIVES: [&str; 64] = [
+  "amber", "bold", "brave", "bright", "calm", "clever", "cosmic", "crimson",
+  "curly", "dizzy", "eager", "fancy", "fierce", "fluffy", "frosty", "gentle",
+  "giant", "golden", "grumpy", "happy", "hidden", "humble", "icy", "jolly",
+  "lazy", "lucky", "lunar", "mellow", "misty", "mighty", "noble", "odd",
+  "orange", "plucky", "polite", "proud", "purple", "quick", "quiet", "rapid",
+  "royal", "rusty", "sandy", "shiny", "silent", "silver", "sleepy", "sly",
+  "snowy", "solar", "spicy", "stormy", "sunny", "swift", "tiny", "velvet",
+  "wild", "windy", "wise", "witty", "woolly", "young", "zany", "zesty",
+];
+
+const NOUNS: [&str; 64] = [
+  "badger", "bear", "beetle", "bison", "camel", "cobra", "comet", "crane",
+  "crow", "dingo", "dragon", "eagle", "falcon", "ferret", "finch", "fox",
+  "gecko", "goat", "goose", "hawk", "hedgehog", "heron", "hippo", "ibis",
+  "jackal", "koala", "lemur", "lion", "llama", "lynx", "magpie", "marmot",
+  "meerkat", "mole", "moose", "newt", "ocelot", "octopus", "otter", "owl",
+  "panda", "parrot", "pelican", "penguin", "puffin", "quokka", "rabbit", "raven",
+  "robin", "salmon", "seal", "shark", "sloth", "spider", "squid", "swan",
+  "tapir", "tiger", "toad", "turtle", "walrus", "whale", "wolf", "yak",
+];
+
+pub fn random_phrase() -> String {
+  let mut rng = rand::thread_rng();
+  format!("{}-{}-{}", ADJECTIVES[rng.gen_range(0..ADJECTIVES.len())], NOUNS[rng.gen_range(0..NOUNS.len())], rng.gen_range(0..100))
+}
+
+/// Tidies up a phrase as it is shown, or returns None for a plain number.
+/// Phrases ignore case, and spaces count the same as dashes.
+pub fn phrase(text: &str) -> Option<String> {
+  if text.trim().parse::<u64>().is_ok() {
+    return None;
+  }
+  Some(text.to_lowercase().split(|c: char| c.is_whitespace() || c == '-').filter(|word| !word.is_empty()).collect::<Vec<_>>().join("-"))
+}
+
+/// The seed for a number or a phrase.
+pub fn parse(text: &str) -> u64 {
+  match phrase(text) {
+    //FNV-1a, so a phrase means the same board on every platform and version.
+    Some(phrase) => phrase.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3)),
+    None => text.trim().parse().unwrap_or_default(),
+  }
+}
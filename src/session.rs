@@ -0,0 +1,72 @@
+//! Seeds of the boards played since the game was opened, for sharing or playing again,
+//! and seed lists pasted in, like a friend's session, to play the same boards in the same order.
+use std::collections::VecDeque;
+
+/// What the session's panel asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  /// Copy a seed of the log by its index, or the whole log with `None`.
+  Share(Option<usize>),
+  /// Play the board of a seed in the log again, by its index.
+  Retry(usize),
+  ListInput(String),
+  /// Queue the pasted seeds, and start on the first.
+  PlayList,
+  ClearQueue,
+}
+
+//Older seeds get dropped, so the log doesn't grow forever.
+const MAX_SEEDS: usize = 50;
+
+#[derive(Default)]
+pub struct Session {
+  /// Seeds of the boards played, as numbers or phrases, oldest first.
+  pub seeds: Vec<String>,
+  /// Pasted seeds still to be played, next first.
+  pub queue: VecDeque<String>,
+  /// The seed list as typed or pasted so far.
+  pub input: String,
+}
+
+impl Session {
+  /// A board was started. Starting the same board again right away isn't logged twice.
+  pub fn played(&mut self, seed: String) {
+    if self.seeds.last() == Some(&seed) {
+      return;
+    }
+    self.seeds.push(seed);
+    if self.seeds.len() > MAX_SEEDS {
+      self.seeds.remove(0);
+    }
+  }
+
+  /// The log as a list which [`Session::queue_list`] reads back.
+  pub fn share(&self) -> String {
+    self.seeds.join("\n")
+  }
+
+  /// Queues the seeds of a list, one per line or separated by commas, after any still queued. Returns how many there were.
+  pub fn queue_list(&mut self, text: &str) -> usize {
+    let seeds: Vec<String> = text.split(['\n', ',']).map(str::trim).filter(|seed| !seed.is_empty()).map(String::from).collect();
+    let count = seeds.len();
+    self.queue.extend(seeds);
+    count
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shared_lists_queue_in_order() {
+    let mut session = Session::default();
+    session.played(String::from("purple-otter-42"));
+    session.played(String::from("purple-otter-42"));
+    session.played(String::from("1234"));
+    let mut friend = Session::default();
+    assert_eq!(friend.queue_list(&session.share()), 2);
+    assert_eq!(friend.queue_list(" 7 ,, lucky fox 3\n"), 2);
+    assert_eq!(friend.queue, ["purple-otter-42", "1234", "7", "lucky fox 3"]);
+  }
+}
@@ -0,0 +1,304 @@
+//! User preferences, stored as `settings.toml` in the platform's config directory.
+use crate::{clock, face, theme};
+use minesweeper::config::{self, GameConfig};
+use minesweeper::engine::{Neighbors, Reward};
+use minesweeper::{schema, solver};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path, time};
+
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 2.0;
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+  /// Light or dark, whichever the system prefers.
+  #[default]
+  System,
+  Light,
+  Dark,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+  /// Shown in the header, after the avatar. Nothing is shown when both are empty.
+  pub player_name: String,
+  /// An emoji, like "🦊", or a PNG file in the config folder, like "avatar.png", shown in the header.
+  pub avatar: String,
+  /// Names of the players in hot seat games, in turn order.
+  pub hot_seat_players: Vec<String>,
+  /// Points each hot seat player starts versus with, in the same order as the names.
+  pub hot_seat_handicaps: Vec<i64>,
+  pub fullscreen: bool,
+  /// Seconds without input on a fresh board before the demo starts playing. 0 disables the demo.
+  pub idle_demo_seconds: u64,
+  /// Built in theme to use when there is no `theme.toml`.
+  pub color_scheme: ColorScheme,
+  /// RGB color of each number, from 1 to 8.
+  pub number_colors: [[u8; 3]; 8],
+  /// Beat the clock: every board has to be cleared before its time limit runs out.
+  pub countdown: bool,
+  /// Seconds the countdown gives for each point of the board's 3BV.
+  pub countdown_seconds_per_three_bv: f32,
+  /// Show the 3BV cleared per second so far, updating as the game goes.
+  pub show_pace: bool,
+  /// 3BV per second to beat. The pace shows green at or above it and red below. 0 for no target.
+  pub target_pace: f32,
+  /// Show territory points for the cells explored, and all of them explored so far, instead of caring about time.
+  pub territory: bool,
+  /// Arcade mode, where opening cells quickly in a row builds a combo which multiplies the score.
+  pub combo: bool,
+  /// Arcade mode, where big openings charge a scanner which counts the mines in a 3×3 area without showing where they are.
+  pub scanner: bool,
+  /// Pixel size of each cell in exported animations.
+  pub export_cell_size: u32,
+  /// Milliseconds between moves in exported animations.
+  pub export_frame_ms: u16,
+  /// Desktop notifications for new daily challenges and personal bests.
+  pub notifications: bool,
+  /// Volume of everything, from 0 to 1.
+  pub master_volume: f32,
+  /// Volume of sound effects, from 0 to 1, on top of the master volume.
+  pub effects_volume: f32,
+  /// Play a note for each revealed number.
+  pub reveal_sounds: bool,
+  /// Play a thud for big openings.
+  pub opening_sounds: bool,
+  /// Cells one move has to open to count as a big opening.
+  pub opening_sound_cells: usize,
+  /// Pause the clock and cover the board when the window loses focus.
+  pub pause_on_focus_loss: bool,
+  /// Port to serve the stream overlay on. Off when not set.
+  pub overlay_port: Option<u16>,
+  /// Address the overlay listens on. `0.0.0.0` lets other computers on the network watch.
+  pub overlay_address: String,
+  /// How far behind the game watchers see the board, so they can't coach the player.
+  pub watch_delay_seconds: u64,
+  /// Columns of new boards. 9 for beginner, 16 for intermediate and 30 for expert.
+  pub board_width: usize,
+  /// Rows of new boards. 9 for beginner and 16 for intermediate and expert.
+  pub board_height: usize,
+  /// Mines on new boards. 10 for beginner, 40 for intermediate and 99 for expert.
+  pub mine_count: usize,
+  /// The first cell revealed on new boards is never a mine, and opens up when there's room.
+  pub safe_start: bool,
+  /// Which neighbors the numbers count on new boards: `all`, `diagonal` or `orthogonal`.
+  pub neighbors: Neighbors,
+  /// Treasures hidden among the safe cells of new boards.
+  pub treasures: usize,
+  /// What a treasure gives: `time` or `life`.
+  pub treasure_reward: Reward,
+  /// Seconds taken off the clock by each treasure, when they give time.
+  pub treasure_seconds: u64,
+  /// Flags allowed at once, for the limited flags challenge. One for every mine when not set.
+  pub flag_limit: Option<usize>,
+  /// New boards are also won by flagging every mine, with no flags anywhere else.
+  pub flag_win: bool,
+  /// New boards get their mines moved out of coin flips the numbers can never settle.
+  pub fair_boards: bool,
+  /// New boards get few mines on the left and more and more towards the right.
+  pub gradient_boards: bool,
+  /// Mines on new boards which are antimines instead, taking one off the numbers around them.
+  pub antimines: usize,
+  /// How the clock is shown: `seconds`, `minutes` for minutes and seconds, or `tenths`.
+  pub timer_format: clock::TimerFormat,
+  /// Put between groups of three digits in the clock, scores and other counters, like `","`, `"."`, `" "` or `""` for none.
+  pub thousands_separator: String,
+  /// Milliseconds between redraws of the clock. When not set, just often enough for the format.
+  /// Redrawing less often saves power, and doesn't change the times recorded.
+  pub timer_tick_ms: Option<u64>,
+  /// Seconds to wait after a hint before asking for another.
+  pub hint_cooldown_seconds: u64,
+  /// Seconds added to the clock for each hint, so hinted games stay comparable.
+  pub hint_penalty_seconds: u64,
+  /// Size of everything in the window, from 0.75 to 2, on top of the system's own scaling.
+  pub ui_scale: f32,
+  /// Asks before revealing a cell the numbers prove is a mine, instead of losing straight away. Off for the daily challenge.
+  /// Games where it asked count as assisted.
+  pub confirm_fatal_moves: bool,
+  /// Which solver works out fatal moves, hints, the demo's moves, `minesweeper solve` and the danger shown when comparing replays: `single`, which looks at one number at a time,
+  /// `pairs`, which compares numbers sharing cells too, or `exact`, which counts every way the mines could be placed.
+  pub solver: solver::Kind,
+  /// Asks before leaving a game in progress for a new game or a daily challenge, or closing the window.
+  pub confirm_abandon: bool,
+  /// Abandoned games count as losses in the win rate, and end the win streak. Otherwise they are left out of both.
+  pub abandoned_are_losses: bool,
+  /// Adds shapes and patterns to everything shown by color, so nothing relies on color alone.
+  pub dual_coding: bool,
+  /// Resting the cursor on a revealed number shows how many of its mines are flagged. Games with it on count as assisted.
+  pub number_tooltips: bool,
+  /// Milliseconds the cursor has to rest on a number before its tooltip shows.
+  pub tooltip_delay_ms: u64,
+  /// Extra mouse buttons, named like `button4`, and wheel tilts, `tilt_left` and `tilt_right`, bound to `hint`, `pause`, `new_game`, `log` or `help`.
+  pub mouse_bindings: BTreeMap<String, String>,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Settings {
+      player_name: String::new(),
+      avatar: String::new(),
+      hot_seat_players: vec![String::from("Player 1"), String::from("Player 2")],
+      hot_seat_handicaps: Vec::new(),
+      fullscreen: false,
+      idle_demo_seconds: 60,
+      color_scheme: ColorScheme::System,
+      number_colors: [
+        [0, 0, 255],  //Blue
+        [0, 128, 0],  //Green
+        [255, 0, 0],  //Red
+        [0, 0, 128],  //Dark blue
+        [128, 0, 0],  //Dark red
+        [0, 128, 128],  //Cyan
+        [0, 0, 0],  //Black
+        [128, 128, 128],  //Grey
+      ],
+      countdown: false,
+      countdown_seconds_per_three_bv: 2.0,
+      show_pace: false,
+      target_pace: 0.0,
+      territory: false,
+      combo: false,
+      scanner: false,
+      export_cell_size: 16,
+      export_frame_ms: 300,
+      notifications: true,
+      master_volume: 1.0,
+      effects_volume: 1.0,
+      reveal_sounds: false,
+      opening_sounds: false,
+      opening_sound_cells: 15,
+      pause_on_focus_loss: true,
+      overlay_port: None,
+      overlay_address: String::from("127.0.0.1"),
+      watch_delay_seconds: 10,
+      board_width: config::EXPERT.1,
+      board_height: config::EXPERT.2,
+      mine_count: config::EXPERT.3,
+      safe_start: false,
+      neighbors: Neighbors::All,
+      treasures: 0,
+      treasure_reward: Reward::Time,
+      treasure_seconds: 10,
+      flag_limit: None,
+      flag_win: false,
+      fair_boards: false,
+      gradient_boards: false,
+      antimines: 0,
+      timer_format: clock::TimerFormat::Seconds,
+      thousands_separator: String::from(","),
+      timer_tick_ms: None,
+      hint_cooldown_seconds: 30,
+      hint_penalty_seconds: 10,
+      ui_scale: 1.0,
+      confirm_fatal_moves: false,
+      solver: solver::Kind::Exact,
+      confirm_abandon: false,
+      abandoned_are_losses: true,
+      dual_coding: false,
+      number_tooltips: false,
+      tooltip_delay_ms: 600,
+      mouse_bindings: BTreeMap::new(),
+    }
+  }
+}
+
+/// Location of a file in the platform's config directory for this game.
+pub fn config_path(file_name: &str) -> Option<path::PathBuf> {
+  let dirs = directories::ProjectDirs::from("", "", "minesweeper")?;
+  Some(dirs.config_dir().join(file_name))
+}
+
+impl Settings {
+  /// The avatar's picture, when it is a PNG file which loads.
+  pub fn avatar_image(&self) -> Option<face::Image> {
+    if !self.avatar.ends_with(".png") {
+      return None;
+    }
+    face::Image::load(&config_path(&self.avatar)?).ok()
+  }
+
+  pub fn ui_scale(&self) -> f32 {
+    self.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+  }
+
+  pub fn number_color(&self, number: u8) -> iced::Color {
+    match self.number_colors.get((number as usize).wrapping_sub(1)) {
+      Some(&[r, g, b]) => iced::Color::from_rgb8(r, g, b),
+      None => iced::Color::WHITE,
+    }
+  }
+
+  pub fn is_dark(&self) -> bool {
+    match self.color_scheme {
+      ColorScheme::System => theme::system_is_dark(),
+      ColorScheme::Light => false,
+      ColorScheme::Dark => true,
+    }
+  }
+
+  pub fn effects_volume(&self) -> f32 {
+    self.master_volume.clamp(0.0, 1.0) * self.effects_volume.clamp(0.0, 1.0)
+  }
+
+  /// The board for `seed` under the rules picked here.
+  pub fn game_config(&self, seed: u64) -> GameConfig {
+    GameConfig::new(seed)
+      .with_size(self.board_width, self.board_height, self.mine_count)
+      .with_safe_start(self.safe_start)
+      .with_neighbors(self.neighbors)
+      .with_gradient(self.gradient_boards)
+      .with_fair_layout(self.fair_boards)
+      .with_antimines(self.antimines)
+      .with_treasures(self.treasures, self.treasure_reward)
+      .with_flag_limit(self.flag_limit)
+      .with_flag_win(self.flag_win)
+  }
+
+  /// How long a countdown board is allowed to take, when countdown mode is on.
+  pub fn time_limit(&self, three_bv: usize) -> Option<time::Duration> {
+    self.countdown.then(|| time::Duration::from_secs_f32(three_bv as f32 * self.countdown_seconds_per_three_bv.max(0.0)))
+  }
+
+  /// `number` grouped with the thousands separator.
+  pub fn number(&self, number: u64) -> String {
+    clock::group_digits(number, &self.thousands_separator)
+  }
+
+  /// `time` on the clock, in the timer format.
+  pub fn time(&self, time: time::Duration) -> String {
+    self.timer_format.format(time, &self.thousands_separator)
+  }
+
+  /// Time between redraws of the clock.
+  pub fn timer_tick(&self) -> time::Duration {
+    self.timer_tick_ms.map_or(self.timer_format.tick(), |tick| time::Duration::from_millis(tick.max(clock::MIN_TICK_MS)))
+  }
+
+  /// Missing or unreadable settings fall back to the defaults.
+  pub fn load() -> Settings {
+    config_path("settings.toml")
+      .and_then(|path| fs::read_to_string(path).ok())
+      .and_then(|text| Settings::from_toml(&text).ok())
+      .unwrap_or_default()
+  }
+
+  /// Reads settings in this format or an older one.
+  pub fn from_toml(text: &str) -> Result<Settings, String> {
+    schema::SETTINGS.from_toml(text)
+  }
+
+  pub fn to_toml(&self) -> Result<String, String> {
+    schema::SETTINGS.to_toml(self)
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let path = config_path("settings.toml").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    let text = self.to_toml().map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    fs::write(path, text)
+  }
+}
@@ -0,0 +1,344 @@
+//! Finds the next move using only what the player can see.
+//!
+//! Covered cells are never peeked at. When no number gives a certain answer, the solver guesses.
+//! Boards with antimines aren't understood, since a number there doesn't say how many mines are around it,
+//! and the game doesn't ask the solver about them.
+//!
+//! Ways of working out the board go behind the [`Solver`] trait, so they can be swapped in the settings and compared with `minesweeper solvers`.
+use crate::action::Action;
+use crate::engine::{Board, Cell, CellStatus, CellValue, Neighbors};
+use crate::probability;
+use serde::{Deserialize, Serialize};
+
+/// Why the solver picked a move. Numbers are given by the position of the revealed cell they are on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reason {
+  /// The number already has as many flags around it as it needs, so its other neighbors are safe.
+  Satisfied(usize, usize),
+  /// The number has exactly as many covered neighbors as it is missing flags, so they are all mines.
+  OnlyMines(usize, usize),
+  /// Certain from the numbers together, with no one number settling it.
+  Deduced,
+  /// Nothing is certain.
+  Guess,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+  pub action: Action,
+  pub reason: Reason,
+}
+
+impl Move {
+  /// Whether the cell is the one being played, or the number that justified it.
+  pub fn involves(&self, x: usize, y: usize) -> bool {
+    self.cells().any(|cell| cell == (x, y))
+  }
+
+  /// The cell being played, and the number that justified it if there is one.
+  pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+    let number = match self.reason {
+      Reason::Satisfied(x, y) | Reason::OnlyMines(x, y) => Some((x, y)),
+      Reason::Deduced | Reason::Guess => None,
+    };
+    std::iter::once(self.action.position()).chain(number)
+  }
+}
+
+impl Reason {
+  /// Short explanation to show next to the board.
+  pub fn describe(&self, board: &Board) -> String {
+    let number = |x: usize, y: usize| match board[x][y].value {
+      CellValue::Number(number) | CellValue::Treasure(number) => number,
+      CellValue::Mined | CellValue::AntiMine => 0,
+    };
+    match *self {
+      Reason::Satisfied(x, y) => format!("{} has its flags", number(x, y)),
+      Reason::OnlyMines(x, y) => format!("{} needs all", number(x, y)),
+      Reason::Deduced => String::from("Worked out"),
+      Reason::Guess => String::from("Guessing"),
+    }
+  }
+}
+
+/// How a reveal looked when it was played, going by what could be worked out from the numbers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Judgement {
+  /// The cell was certainly safe.
+  Certain,
+  /// The cell wasn't certain, but some other cell was.
+  Guess,
+  /// Nothing was certainly safe, so some guess had to be made.
+  ForcedGuess,
+}
+
+/// Works out what it can of the board from what the player can see.
+pub trait Solver {
+  fn name(&self) -> &'static str;
+
+  /// Which cells are certainly mines, `Some(true)`, or certainly safe, `Some(false)`.
+  fn known(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<bool>>>;
+
+  /// The chance of each covered cell being a mine. `None` for revealed cells.
+  fn danger(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<f32>>>;
+
+  /// A covered cell it is certain about, safe ones first, or else a reveal of the one least likely a mine.
+  fn next_move(&self, board: &Board, neighbors: Neighbors) -> Option<Move> {
+    let covered: Vec<(usize, usize)> = board.positions().filter(|&(x, y)| board[x][y].status == CellStatus::Covered).collect();
+    if covered.len() == board.width * board.height {
+      return Some(Move {action: Action::Reveal(board.width / 2, board.height / 2), reason: Reason::Guess});
+    }
+    let known = self.known(board, neighbors);
+    if let Some(&(x, y)) = covered.iter().find(|&&(x, y)| known[x][y] == Some(false)) {
+      return Some(Move {action: Action::Reveal(x, y), reason: Reason::Deduced});
+    }
+    if let Some(&(x, y)) = covered.iter().find(|&&(x, y)| known[x][y] == Some(true)) {
+      return Some(Move {action: Action::Flag(x, y), reason: Reason::Deduced});
+    }
+    let danger = self.danger(board, neighbors);
+    let &(x, y) = covered.iter().min_by(|&&(x, y), &&(other_x, other_y)| danger[x][y].partial_cmp(&danger[other_x][other_y]).unwrap_or(std::cmp::Ordering::Equal))?;
+    Some(Move {action: Action::Reveal(x, y), reason: Reason::Guess})
+  }
+}
+
+/// One number at a time, with [`certain`] and [`danger`].
+pub struct Single;
+
+impl Solver for Single {
+  fn name(&self) -> &'static str {
+    "single"
+  }
+
+  fn known(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<bool>>> {
+    certain(board, neighbors)
+  }
+
+  fn danger(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<f32>>> {
+    danger(board, neighbors)
+  }
+
+  //Names the number behind each move, and guesses at random.
+  fn next_move(&self, board: &Board, neighbors: Neighbors) -> Option<Move> {
+    next_move(board, neighbors)
+  }
+}
+
+/// Numbers compared in pairs too, with [`deduce`], and the danger estimated on top of what that settles.
+pub struct Pairs;
+
+impl Solver for Pairs {
+  fn name(&self) -> &'static str {
+    "pairs"
+  }
+
+  fn known(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<bool>>> {
+    deduce(board, neighbors)
+  }
+
+  fn danger(&self, board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<f32>>> {
+    estimate(board, neighbors, &deduce(board, neighbors))
+  }
+}
+
+/// A solver to pick in `settings.toml`.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+  Single,
+  Pairs,
+  /// [`probability::Exact`].
+  #[default]
+  Exact,
+}
+
+impl Kind {
+  pub const ALL: [Kind; 3] = [Kind::Single, Kind::Pairs, Kind::Exact];
+
+  pub fn solver(self) -> &'static dyn Solver {
+    match self {
+      Kind::Single => &Single,
+      Kind::Pairs => &Pairs,
+      Kind::Exact => &probability::Exact,
+    }
+  }
+}
+
+/// Which cells are certainly mines, `Some(true)`, or certainly safe, `Some(false)`.
+///
+/// Only the numbers are trusted, so it works the same with no flags or with wrong ones.
+/// Like [`next_move`], this only looks at one number at a time, so a few positions it calls uncertain could be worked out with more effort.
+pub fn certain(board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<bool>>> {
+  let mut known = board.grid(None);
+  for (x, y) in board.positions() {
+    match board[x][y] {
+      Cell {status: CellStatus::Revealed, value: CellValue::Mined} => known[x][y] = Some(true),
+      Cell {status: CellStatus::Revealed, ..} => known[x][y] = Some(false),
+      _ => (),
+    }
+  }
+  settle(board, neighbors, &mut known);
+  known
+}
+
+/// Works out what single numbers settle, on top of what is already `known`.
+fn settle(board: &Board, neighbors: Neighbors, known: &mut [Vec<Option<bool>>]) {
+  //Each cell worked out can settle more numbers, so keep going until nothing changes.
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for x in 0..board.width {
+      for y in 0..board.height {
+        let Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} = board[x][y] else { continue };
+        let Ok(number) = u8::try_from(number) else { continue };
+        let mines = neighbors.of(board, x, y).filter(|&(new_x, new_y)| known[new_x][new_y] == Some(true)).count();
+        let unknown: Vec<(usize, usize)> = neighbors.of(board, x, y).filter(|&(new_x, new_y)| known[new_x][new_y].is_none()).collect();
+        let mined = if mines == number as usize {
+          false
+        } else if mines + unknown.len() == number as usize {
+          true
+        } else {
+          continue;
+        };
+        for &(new_x, new_y) in &unknown {
+          known[new_x][new_y] = Some(mined);
+          changed = true;
+        }
+      }
+    }
+  }
+}
+
+/// Like [`certain`], but also compares numbers which share unsettled neighbors.
+///
+/// When one number is missing as many more mines than another as it has cells the other doesn't,
+/// those cells are all mines and the other's own cells are all safe.
+/// That works out patterns like 1-1 on an edge and 1-2-1, which no number settles alone.
+pub fn deduce(board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<bool>>> {
+  let mut known = certain(board, neighbors);
+  let mut changed = true;
+  while changed {
+    changed = false;
+    //Each number's missing mines and unsettled neighbors.
+    let mut numbers = Vec::new();
+    for x in 0..board.width {
+      for y in 0..board.height {
+        let Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} = board[x][y] else { continue };
+        let Ok(number) = u8::try_from(number) else { continue };
+        let mines = neighbors.of(board, x, y).filter(|&(near_x, near_y)| known[near_x][near_y] == Some(true)).count();
+        let unsettled: Vec<(usize, usize)> = neighbors.of(board, x, y).filter(|&(near_x, near_y)| known[near_x][near_y].is_none()).collect();
+        if !unsettled.is_empty() {
+          numbers.push((number as usize - mines.min(number as usize), unsettled));
+        }
+      }
+    }
+    for (missing, cells) in &numbers {
+      for (other_missing, other_cells) in &numbers {
+        if !cells.iter().any(|cell| other_cells.contains(cell)) {
+          continue;
+        }
+        //Mines only the other has, less mines only this has, make up the difference in missing mines.
+        //When that takes every cell only the other has, none are left for the cells only this has.
+        let only_other: Vec<&(usize, usize)> = other_cells.iter().filter(|cell| !cells.contains(cell)).collect();
+        if *other_missing != missing + only_other.len() {
+          continue;
+        }
+        let only_this = cells.iter().filter(|cell| !other_cells.contains(cell));
+        for (&(x, y), mined) in only_other.into_iter().map(|cell| (cell, true)).chain(only_this.map(|cell| (cell, false))) {
+          if known[x][y].is_none() {
+            known[x][y] = Some(mined);
+            changed = true;
+          }
+        }
+      }
+    }
+    //What was just worked out can settle single numbers again.
+    settle(board, neighbors, &mut known);
+  }
+  known
+}
+
+/// A rough chance of each covered cell being a mine, going by what the player can see. `None` for revealed cells.
+///
+/// Cells the numbers settle get 0 or 1. Other cells next to a number get the biggest share of a number's missing mines
+/// among its unsettled neighbors, and the rest split whatever mines are left evenly. It is an estimate, not the exact odds,
+/// which [`probability::Exact`] works out.
+pub fn danger(board: &Board, neighbors: Neighbors) -> Vec<Vec<Option<f32>>> {
+  estimate(board, neighbors, &certain(board, neighbors))
+}
+
+/// [`danger`], going by the cells already `known`.
+fn estimate(board: &Board, neighbors: Neighbors, known: &[Vec<Option<bool>>]) -> Vec<Vec<Option<f32>>> {
+  let mut danger = board.grid(None);
+  for x in 0..board.width {
+    for y in 0..board.height {
+      match (board[x][y].status, known[x][y]) {
+        (CellStatus::Revealed, _) => (),
+        (_, Some(mine)) => danger[x][y] = Some(if mine { 1.0 } else { 0.0 }),
+        _ => {
+          let mut shares = neighbors.of(board, x, y).filter_map(|(number_x, number_y)| {
+            let Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} = board[number_x][number_y] else { return None };
+            let number = u8::try_from(number).ok()?;
+            let mines = neighbors.of(board, number_x, number_y).filter(|&(near_x, near_y)| known[near_x][near_y] == Some(true)).count();
+            let unsettled = neighbors.of(board, number_x, number_y).filter(|&(near_x, near_y)| known[near_x][near_y].is_none()).count();
+            Some(number.saturating_sub(mines as u8) as f32 / unsettled as f32)
+          }).peekable();
+          if shares.peek().is_some() {
+            danger[x][y] = shares.reduce(f32::max);
+          }
+        },
+      }
+    }
+  }
+  let expected: f32 = danger.iter().flatten().flatten().sum();
+  let rest: Vec<(usize, usize)> = board.positions()
+    .filter(|&(x, y)| board[x][y].status != CellStatus::Revealed && danger[x][y].is_none()).collect();
+  let share = ((board.mines as f32 - expected) / rest.len() as f32).clamp(0.0, 1.0);
+  for (x, y) in rest {
+    danger[x][y] = Some(share);
+  }
+  danger
+}
+
+/// Judges revealing the cell at `x`, `y` before it happens.
+pub fn judge(board: &Board, neighbors: Neighbors, x: usize, y: usize) -> Judgement {
+  let known = certain(board, neighbors);
+  if known[x][y] == Some(false) {
+    Judgement::Certain
+  } else if known.iter().flatten().zip(board.iter().flatten()).any(|(known, cell)| *known == Some(false) && cell.status != CellStatus::Revealed) {
+    Judgement::Guess
+  } else {
+    Judgement::ForcedGuess
+  }
+}
+
+pub fn next_move(board: &Board, neighbors: Neighbors) -> Option<Move> {
+  let mut covered_cells = Vec::new();
+  for y in 0..board.height {
+    for x in 0..board.width {
+      match board[x][y] {
+        Cell {status: CellStatus::Covered, ..} => covered_cells.push((x, y)),
+        Cell {status: CellStatus::Revealed, value: CellValue::Number(number) | CellValue::Treasure(number)} if number > 0 => {
+          let flagged = neighbors.of(board, x, y).filter(|&(new_x, new_y)| board[new_x][new_y].is_known_mine()).count() as i8;
+          let covered: Vec<(usize, usize)> = neighbors.of(board, x, y).filter(|&(new_x, new_y)| board[new_x][new_y].status == CellStatus::Covered).collect();
+          if let Some(&(new_x, new_y)) = covered.first() {
+            if flagged == number {
+              return Some(Move {action: Action::Reveal(new_x, new_y), reason: Reason::Satisfied(x, y)});
+            }
+            if flagged + covered.len() as i8 == number {
+              return Some(Move {action: Action::Flag(new_x, new_y), reason: Reason::OnlyMines(x, y)});
+            }
+          }
+        },
+        _ => (),
+      }
+    }
+  }
+
+  //Nothing certain. Start in the middle if the board is untouched, otherwise pick any covered cell.
+  if covered_cells.len() == board.width * board.height {
+    return Some(Move {action: Action::Reveal(board.width / 2, board.height / 2), reason: Reason::Guess});
+  }
+  use rand::seq::SliceRandom;
+  let &(x, y) = covered_cells.choose(&mut rand::thread_rng())?;
+  Some(Move {action: Action::Reveal(x, y), reason: Reason::Guess})
+}
@@ -0,0 +1,277 @@
+//! History of finished games, stored as `stats.json` in the config directory.
+use crate::{hotseat, settings};
+use minesweeper::action::Action;
+use minesweeper::engine::{Board, Engine, Neighbors};
+use minesweeper::schema;
+use serde::{Deserialize, Serialize};
+use std::{fs, io};
+
+/// What the statistics screen asks for.
+#[derive(Clone, Debug)]
+pub enum Message {
+  Category(Category),
+  Filter(String),
+  /// Picks a game from the list under the charts, to tag or write a note on.
+  Game(usize),
+  TagInput(String),
+  AddTag,
+  RemoveTag(String),
+  Note(String),
+  /// Saves the note being written.
+  Save,
+}
+
+/// Rules for each kind of help, which keep games with it apart from games without.
+/// `assisted` is from before the kinds were told apart.
+const ASSIST_RULES: [&str; 5] = ["assisted", "hints", "undo", "warnings", "tooltips"];
+
+/// Help a game had, counted as it was played.
+#[derive(Clone, Copy, Default)]
+pub struct Assists {
+  pub hints: usize,
+  pub undos: usize,
+  /// Fatal moves it asked about first.
+  pub warnings: usize,
+}
+
+/// The board configuration and rules a game was played under. Times are only compared within the same category.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Category {
+  /// Board size and mine count.
+  pub difficulty: String,
+  /// Rule flags, like `NF` for a game won without flags. Empty for the standard rules.
+  #[serde(default)]
+  pub rules: Vec<String>,
+}
+
+impl Category {
+  /// The size of `board`, under the standard rules.
+  pub fn standard(board: &Board) -> Category {
+    Category {difficulty: difficulty(board), rules: Vec::new()}
+  }
+
+  /// The category of a finished game. Every rule and kind of help which changes how a game plays is in it,
+  /// so games are never compared with ones played another way.
+  pub fn of(engine: &Engine, settings: &settings::Settings, assists: Assists, hot_seat: Option<hotseat::Mode>) -> Category {
+    let mut rules = Vec::new();
+    for (rule, used) in [("hints", assists.hints > 0), ("undo", assists.undos > 0), ("warnings", assists.warnings > 0), ("tooltips", settings.number_tooltips)] {
+      if used {
+        rules.push(String::from(rule));
+      }
+    }
+    if !engine.moves.iter().any(|action| matches!(action, Action::Flag(..))) {
+      rules.push(String::from("NF"));
+    }
+    if engine.neighbors != Neighbors::All {
+      rules.push(engine.neighbors.name().to_lowercase());
+    }
+    if engine.safe_start {
+      rules.push(String::from("safe start"));
+    }
+    if let Some(limit) = engine.flag_limit.filter(|&limit| limit < engine.board.mines) {
+      rules.push(format!("{} flags", limit));
+    }
+    if engine.flag_win {
+      rules.push(String::from("flag win"));
+    }
+    if engine.fair {
+      rules.push(String::from("fair"));
+    }
+    if engine.gradient {
+      rules.push(String::from("gradient"));
+    }
+    if engine.antimines > 0 {
+      rules.push(String::from("antimines"));
+    }
+    if engine.treasures > 0 {
+      rules.push(String::from("treasure"));
+    }
+    if settings.countdown {
+      rules.push(String::from("countdown"));
+    }
+    if settings.scanner {
+      rules.push(String::from("scanner"));
+    }
+    match hot_seat {
+      Some(hotseat::Mode::CoOp) => rules.push(String::from("hot seat")),
+      Some(hotseat::Mode::Versus) => rules.push(String::from("versus")),
+      None => (),
+    }
+    Category {difficulty: difficulty(&engine.board), rules}
+  }
+
+  /// The same category played the other way, with flags instead of without, or the other way around.
+  pub fn other_playstyle(&self) -> Category {
+    let mut rules = self.rules.clone();
+    match rules.iter().position(|rule| rule == "NF") {
+      Some(index) => {
+        rules.remove(index);
+      },
+      //Where [`Category::of`] puts it, so the two match.
+      None => rules.insert(rules.iter().take_while(|rule| ASSIST_RULES.contains(&rule.as_str())).count(), String::from("NF")),
+    }
+    Category {difficulty: self.difficulty.clone(), rules}
+  }
+
+  pub fn is_no_flags(&self) -> bool {
+    self.rules.iter().any(|rule| rule == "NF")
+  }
+
+  pub fn rules_name(&self) -> String {
+    if self.rules.is_empty() { String::from("Standard") } else { self.rules.join(" + ") }
+  }
+}
+
+impl std::fmt::Display for Category {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(formatter, "{}, {}", self.difficulty, self.rules_name())
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Record {
+  #[serde(flatten)]
+  pub category: Category,
+  pub seed: u64,
+  pub won: bool,
+  pub seconds: f32,
+  pub three_bv: usize,
+  /// Reveals which weren't certainly safe, when something else was.
+  #[serde(default)]
+  pub guesses: usize,
+  /// Reveals made when nothing was certainly safe.
+  #[serde(default)]
+  pub forced_guesses: usize,
+  /// Labels like "50/50 loss" or "PB attempt", for finding games again.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub note: String,
+  /// Left before it was over, for a new game or by closing the window. Never won.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub abandoned: bool,
+}
+
+impl Record {
+  /// One line about the game, with its tags and note.
+  pub fn describe(&self) -> String {
+    let result = match (self.won, self.abandoned) {
+      (true, _) => "Won",
+      (false, true) => "Abandoned",
+      (false, false) => "Lost",
+    };
+    let mut text = format!("{} {:.1}s, 3BV {}", result, self.seconds, self.three_bv);
+    if !self.tags.is_empty() {
+      text += &format!(" [{}]", self.tags.join(", "));
+    }
+    if !self.note.is_empty() {
+      text += &format!(" {}", self.note);
+    }
+    text
+  }
+
+  /// Whether any tag contains `filter`, ignoring case. Everything matches an empty filter.
+  pub fn matches(&self, filter: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    filter.is_empty() || self.tags.iter().any(|tag| tag.to_lowercase().contains(&filter))
+  }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+  /// Oldest first.
+  pub games: Vec<Record>,
+  /// Territory points of every game played, added up.
+  pub territory: u64,
+}
+
+/// Name of a board's size and mine count, which games are grouped by.
+pub fn difficulty(board: &Board) -> String {
+  format!("{}x{} {} mines", board.width, board.height, board.mines)
+}
+
+impl Stats {
+  /// Missing or unreadable files start with no history.
+  pub fn load() -> Stats {
+    settings::config_path("stats.json")
+      .and_then(|path| fs::read_to_string(path).ok())
+      .and_then(|text| schema::STATS.from_json(&text).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let path = settings::config_path("stats.json").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    fs::write(path, schema::STATS.write(self).to_string())
+  }
+
+  /// Every difficulty with games in the history, in the order they were first played.
+  pub fn difficulties(&self) -> Vec<&str> {
+    let mut difficulties: Vec<&str> = Vec::new();
+    for record in &self.games {
+      if !difficulties.contains(&record.category.difficulty.as_str()) {
+        difficulties.push(&record.category.difficulty);
+      }
+    }
+    difficulties
+  }
+
+  /// Every category played on a difficulty, in the order they were first played.
+  pub fn categories(&self, difficulty: &str) -> Vec<&Category> {
+    let mut categories: Vec<&Category> = Vec::new();
+    for record in &self.games {
+      if record.category.difficulty == difficulty && !categories.contains(&&record.category) {
+        categories.push(&record.category);
+      }
+    }
+    categories
+  }
+
+  /// Share of the games in a category which were won, and how many were abandoned.
+  /// `strict` counts abandoned games as losses, otherwise they are left out. `None` without any games to count.
+  pub fn win_rate(&self, category: &Category, strict: bool) -> (Option<f32>, usize) {
+    let games: Vec<&Record> = self.games.iter().filter(|record| record.category == *category).collect();
+    let abandoned = games.iter().filter(|record| record.abandoned).count();
+    let counted = if strict { games.len() } else { games.len() - abandoned };
+    let won = games.iter().filter(|record| record.won).count();
+    ((counted > 0).then(|| won as f32 / counted as f32), abandoned)
+  }
+
+  /// Seconds taken by the last `count` wins in a category, oldest first.
+  pub fn solve_times(&self, category: &Category, count: usize) -> Vec<f32> {
+    let mut times: Vec<f32> = self.games.iter().rev()
+      .filter(|record| record.won && record.category == *category)
+      .take(count)
+      .map(|record| record.seconds)
+      .collect();
+    times.reverse();
+    times
+  }
+
+  /// The fastest win in `category`, its record.
+  pub fn best_time(&self, category: &Category) -> Option<f32> {
+    self.games.iter().filter(|record| record.won && record.category == *category).map(|record| record.seconds).reduce(f32::min)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use minesweeper::config::GameConfig;
+
+  #[test]
+  fn each_assist_is_its_own_category() {
+    let engine = GameConfig::new(1).build();
+    let settings = settings::Settings::default();
+    let hinted = Category::of(&engine, &settings, Assists {hints: 1, ..Default::default()}, None);
+    let undone = Category::of(&engine, &settings, Assists {undos: 2, ..Default::default()}, None);
+    assert_eq!(hinted.rules, ["hints", "NF"]);
+    assert_ne!(hinted, undone);
+    assert_ne!(Category::of(&engine, &settings, Assists::default(), None), hinted);
+    assert_eq!(hinted.other_playstyle().other_playstyle(), hinted);
+    assert_eq!(undone.other_playstyle().rules, ["undo"]);
+  }
+}
@@ -0,0 +1,163 @@
+//! Colors, borders, grid lines and fonts, loaded from `theme.toml` in the config directory.
+//!
+//! The file is checked for changes every second, so edits show up without restarting.
+//! Anything left out of the file keeps its default.
+//! Without a file, a built in light or dark theme is used, following the system unless `color_scheme` is set.
+use crate::{cell, face, settings};
+use serde::{Deserialize, Serialize};
+use std::{fs, time};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+  pub background: [u8; 3],
+  pub text: [u8; 3],
+  /// Covered cells.
+  pub primary: [u8; 3],
+  pub success: [u8; 3],
+  pub danger: [u8; 3],
+  /// Background of revealed cells.
+  pub revealed: [u8; 3],
+  pub border_width: f32,
+  pub border_color: [u8; 3],
+  /// Pixels between cells. With no border and a spacing of 0 the board looks flat.
+  pub cell_spacing: f32,
+  /// Color of the grid lines showing between cells. When left out, the background shows through.
+  pub grid_color: Option<[u8; 3]>,
+  /// Colors of the numbers 1 to 8. When left out, the colors from the settings are used.
+  pub number_colors: Option<[[u8; 3]; 8]>,
+  /// Name of an installed font family. When left out, cells use a monospace font and the header uses the default font.
+  pub font: Option<String>,
+  /// What shows how the game is going in the header, in place of the emoji face.
+  pub face: face::Indicator,
+  /// Folder with `playing.png`, `pressed.png`, `won.png` and `lost.png` to show instead of the face, relative to the config directory or absolute.
+  pub face_images: Option<String>,
+  #[serde(skip)]
+  resolved_font: Option<iced::Font>,
+  #[serde(skip)]
+  images: face::Images,
+}
+
+impl Default for Theme {
+  fn default() -> Self {
+    Theme {
+      background: [230, 230, 230],
+      text: [0, 0, 0],
+      primary: [92, 122, 224],
+      success: [18, 102, 79],
+      danger: [194, 66, 64],
+      revealed: [255, 255, 255],
+      border_width: 0.0,
+      border_color: [0, 0, 0],
+      cell_spacing: 1.0,
+      grid_color: None,
+      number_colors: None,
+      font: None,
+      face: face::Indicator::Emoji,
+      face_images: None,
+      resolved_font: None,
+      images: face::Images::default(),
+    }
+  }
+}
+
+fn color([r, g, b]: [u8; 3]) -> iced::Color {
+  iced::Color::from_rgb8(r, g, b)
+}
+
+/// Whether the system asks apps to be dark. Unknown counts as light.
+pub fn system_is_dark() -> bool {
+  matches!(dark_light::detect(), Ok(dark_light::Mode::Dark))
+}
+
+impl Theme {
+  pub fn dark() -> Theme {
+    Theme {
+      background: [32, 33, 36],
+      text: [230, 230, 230],
+      primary: [70, 92, 168],
+      success: [46, 160, 120],
+      danger: [220, 90, 88],
+      revealed: [58, 60, 64],
+      border_color: [20, 20, 20],
+      //The default number colors are too dark to read on dark cells.
+      number_colors: Some([
+        [110, 150, 255],
+        [90, 200, 90],
+        [255, 100, 100],
+        [170, 150, 255],
+        [230, 130, 90],
+        [90, 220, 220],
+        [230, 230, 230],
+        [160, 160, 160],
+      ]),
+      ..Default::default()
+    }
+  }
+
+  /// Missing or invalid theme files fall back to the built in light or dark theme.
+  pub fn load(dark: bool) -> Theme {
+    let preset = if dark { Theme::dark() } else { Theme::default() };
+    let mut theme: Theme = settings::config_path("theme.toml")
+      .and_then(|path| fs::read_to_string(path).ok())
+      .and_then(|text| toml::from_str(&text).ok())
+      .unwrap_or(preset);
+    if let Some(name) = &theme.font {
+      //Fonts are looked up by a static name. Leaking it is fine, since it only happens when the theme file changes.
+      theme.resolved_font = Some(iced::Font::with_name(Box::leak(name.clone().into_boxed_str())));
+    }
+    if let Some(folder) = theme.face_images.as_ref().and_then(|folder| settings::config_path(folder)) {
+      theme.images = face::Images::load(&folder);
+    }
+    theme
+  }
+
+  /// When the theme file was last changed, if it exists.
+  pub fn modified() -> Option<time::SystemTime> {
+    let path = settings::config_path("theme.toml")?;
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+  }
+
+  pub fn palette(&self) -> iced::theme::Palette {
+    iced::theme::Palette {
+      background: color(self.background),
+      text: color(self.text),
+      primary: color(self.primary),
+      success: color(self.success),
+      danger: color(self.danger),
+    }
+  }
+
+  pub fn number_color(&self, settings: &settings::Settings, number: u8) -> iced::Color {
+    match (self.number_colors, number) {
+      (Some(colors), 1..=8) => color(colors[number as usize - 1]),
+      _ => settings.number_color(number),
+    }
+  }
+
+  pub fn grid_color(&self) -> Option<iced::Color> {
+    self.grid_color.map(color)
+  }
+
+  pub fn cell_spacing(&self) -> f32 {
+    self.cell_spacing.max(0.0)
+  }
+
+  /// The theme's image for `mood`, if it has one.
+  pub fn face_image(&self, mood: face::Mood) -> Option<&face::Image> {
+    self.images.get(mood)
+  }
+
+  pub fn header_font(&self) -> iced::Font {
+    self.resolved_font.unwrap_or_default()
+  }
+
+  pub fn cell_style(&self) -> cell::Style {
+    cell::Style {
+      revealed: color(self.revealed),
+      border_width: self.border_width,
+      border_color: color(self.border_color),
+      font: self.resolved_font.unwrap_or(iced::Font::MONOSPACE),
+    }
+  }
+}
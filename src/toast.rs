@@ -0,0 +1,68 @@
+//! Short messages shown for a moment in the header, like "Link copied", one after another.
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long each toast is shown.
+pub const DURATION: Duration = Duration::from_secs(3);
+/// How often the shown toast is checked for running out.
+pub const STEP: Duration = Duration::from_millis(250);
+//Toasts waiting past this many have the oldest waiting one dropped, so a burst doesn't keep them coming for long.
+const MAX_QUEUED: usize = 5;
+
+#[derive(Default)]
+pub struct Toasts {
+  /// The one shown first, then the ones waiting.
+  queue: VecDeque<String>,
+  /// When the one shown started showing.
+  shown: Option<Instant>,
+}
+
+impl Toasts {
+  pub fn push(&mut self, text: impl Into<String>) {
+    self.queue.push_back(text.into());
+    if self.queue.len() > MAX_QUEUED {
+      self.queue.remove(1);
+    }
+  }
+
+  pub fn current(&self) -> Option<&str> {
+    self.queue.front().map(String::as_str)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.queue.is_empty()
+  }
+
+  /// Starts timing the toast shown, and moves on to the next once it has been up long enough.
+  pub fn tick(&mut self, now: Instant) {
+    match self.shown {
+      Some(shown) if now.duration_since(shown) >= DURATION => {
+        self.queue.pop_front();
+        self.shown = (!self.queue.is_empty()).then_some(now);
+      },
+      None if !self.queue.is_empty() => self.shown = Some(now),
+      _ => (),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn toasts_show_in_turn() {
+    let mut toasts = Toasts::default();
+    let start = Instant::now();
+    toasts.push("First");
+    toasts.push("Second");
+    toasts.tick(start);
+    assert_eq!(toasts.current(), Some("First"));
+    toasts.tick(start + DURATION / 2);
+    assert_eq!(toasts.current(), Some("First"));
+    toasts.tick(start + DURATION);
+    assert_eq!(toasts.current(), Some("Second"));
+    toasts.tick(start + DURATION * 2);
+    assert!(toasts.is_empty());
+  }
+}